@@ -0,0 +1,553 @@
+//! ANALYZE Management Module
+//!
+//! Runs `ANALYZE` (optionally bounded by `PRAGMA analysis_limit`) and shows,
+//! edits, exports and imports the `sqlite_stat1`/`sqlite_stat4` rows the query
+//! planner relies on. Importing statistics exported from a production database
+//! reproduces its query plans on an empty development copy.
+
+use crate::core::db::schema::quote_identifier;
+use crate::core::{Result, TuiqlError};
+use crate::db;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Version of the statistics export format
+pub const STATS_EXPORT_VERSION: u32 = 1;
+
+/// A row of `sqlite_stat1`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stat1Row {
+    pub tbl: String,
+    /// Index the row describes; None for the table's own row count
+    pub idx: Option<String>,
+    pub stat: String,
+}
+
+impl Stat1Row {
+    /// Leading integers of the stat: the row count, then average rows per key prefix
+    pub fn numbers(&self) -> Vec<u64> {
+        self.stat.split_whitespace().map_while(|token| token.parse().ok()).collect()
+    }
+
+    /// Estimated number of rows in the table or index
+    pub fn row_estimate(&self) -> Option<u64> {
+        self.numbers().first().copied()
+    }
+}
+
+/// A sample of `sqlite_stat4`; the sample record is stored as hex
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stat4Row {
+    pub tbl: String,
+    pub idx: String,
+    pub neq: String,
+    pub nlt: String,
+    pub ndlt: String,
+    pub sample: String,
+}
+
+/// Statistics of a database, as exported by `:analyze export`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannerStats {
+    pub version: u32,
+    pub stat1: Vec<Stat1Row>,
+    #[serde(default)]
+    pub stat4: Vec<Stat4Row>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>> {
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        return Err(TuiqlError::Query(format!("Invalid stat4 sample '{}'", hex)));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| TuiqlError::Query(format!("Invalid stat4 sample '{}'", hex)))
+        })
+        .collect()
+}
+
+fn table_exists(conn: &Connection, name: &str) -> Result<bool> {
+    Ok(conn
+        .query_row("SELECT 1 FROM sqlite_master WHERE name = ?1 COLLATE NOCASE", [name], |_| Ok(()))
+        .optional()?
+        .is_some())
+}
+
+/// Creates the statistics tables if needed and makes the planner reload them
+fn reload_stats(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ANALYZE sqlite_schema")?;
+    Ok(())
+}
+
+/// Runs ANALYZE on the database or one table, with `analysis_limit` rows per index if given.
+///
+/// The previous `analysis_limit` is restored afterwards. Returns the number of stat1 rows.
+pub fn run_analyze(conn: &Connection, table: Option<&str>, limit: Option<u32>) -> Result<usize> {
+    if let Some(table) = table {
+        if !table_exists(conn, table)? {
+            return Err(TuiqlError::Schema(format!("Table '{}' not found", table)));
+        }
+    }
+
+    let previous: i64 = conn.query_row("PRAGMA analysis_limit", [], |row| row.get(0))?;
+    if let Some(limit) = limit {
+        conn.execute_batch(&format!("PRAGMA analysis_limit = {}", limit))?;
+    }
+    let result = match table {
+        Some(table) => conn.execute_batch(&format!("ANALYZE {}", quote_identifier(table))),
+        None => conn.execute_batch("ANALYZE"),
+    };
+    conn.execute_batch(&format!("PRAGMA analysis_limit = {}", previous))?;
+    result?;
+
+    Ok(read_stat1(conn, table)?.len())
+}
+
+/// Reads `sqlite_stat1`, optionally for one table, ordered by table and index
+pub fn read_stat1(conn: &Connection, table: Option<&str>) -> Result<Vec<Stat1Row>> {
+    if !table_exists(conn, "sqlite_stat1")? {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare(
+        "SELECT tbl, idx, stat FROM sqlite_stat1
+         WHERE ?1 IS NULL OR tbl = ?1 COLLATE NOCASE
+         ORDER BY tbl, idx IS NOT NULL, idx",
+    )?;
+    let rows = stmt.query_map([table], |row| {
+        Ok(Stat1Row {
+            tbl: row.get(0)?,
+            idx: row.get(1)?,
+            stat: row.get(2)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Reads `sqlite_stat4`, optionally for one table; empty when the table does not exist
+pub fn read_stat4(conn: &Connection, table: Option<&str>) -> Result<Vec<Stat4Row>> {
+    if !table_exists(conn, "sqlite_stat4")? {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare(
+        "SELECT tbl, idx, neq, nlt, ndlt, sample FROM sqlite_stat4
+         WHERE ?1 IS NULL OR tbl = ?1 COLLATE NOCASE
+         ORDER BY tbl, idx, rowid",
+    )?;
+    let rows = stmt.query_map([table], |row| {
+        Ok(Stat4Row {
+            tbl: row.get(0)?,
+            idx: row.get(1)?,
+            neq: row.get(2)?,
+            nlt: row.get(3)?,
+            ndlt: row.get(4)?,
+            sample: to_hex(&row.get::<_, Vec<u8>>(5)?),
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Checks that a stat1 row names an existing table (and index of that table) and has a row count
+fn validate_stat1(conn: &Connection, row: &Stat1Row) -> Result<()> {
+    if !table_exists(conn, &row.tbl)? {
+        return Err(TuiqlError::Schema(format!("Table '{}' not found", row.tbl)));
+    }
+    if let Some(idx) = &row.idx {
+        let owner: Option<String> = conn
+            .query_row(
+                "SELECT tbl_name FROM sqlite_master WHERE type = 'index' AND name = ?1 COLLATE NOCASE",
+                [idx],
+                |r| r.get(0),
+            )
+            .optional()?;
+        if !owner.is_some_and(|owner| owner.eq_ignore_ascii_case(&row.tbl)) {
+            return Err(TuiqlError::Schema(format!("Index '{}' not found on table '{}'", idx, row.tbl)));
+        }
+    }
+    if row.row_estimate().is_none() {
+        return Err(TuiqlError::Query(format!(
+            "Invalid stat '{}': expected a row count followed by rows per key, e.g. '10000 10 1'",
+            row.stat
+        )));
+    }
+    Ok(())
+}
+
+/// Sets the stat1 row of a table or index, replacing any existing one, and reloads statistics
+pub fn set_stat1(conn: &Connection, row: &Stat1Row) -> Result<()> {
+    validate_stat1(conn, row)?;
+    reload_stats(conn)?;
+    conn.execute(
+        "DELETE FROM sqlite_stat1 WHERE tbl = ?1 COLLATE NOCASE AND idx IS ?2 COLLATE NOCASE",
+        (&row.tbl, &row.idx),
+    )?;
+    conn.execute(
+        "INSERT INTO sqlite_stat1 (tbl, idx, stat) VALUES (?1, ?2, ?3)",
+        (&row.tbl, &row.idx, &row.stat),
+    )?;
+    reload_stats(conn)
+}
+
+/// Deletes the statistics of a table (all its rows) or of one index, and reloads statistics
+///
+/// Returns the number of stat1 rows removed.
+pub fn delete_stats(conn: &Connection, table: &str, index: Option<&str>) -> Result<usize> {
+    if !table_exists(conn, "sqlite_stat1")? {
+        return Ok(0);
+    }
+    let removed = match index {
+        Some(index) => conn.execute(
+            "DELETE FROM sqlite_stat1 WHERE tbl = ?1 COLLATE NOCASE AND idx = ?2 COLLATE NOCASE",
+            (table, index),
+        )?,
+        None => conn.execute("DELETE FROM sqlite_stat1 WHERE tbl = ?1 COLLATE NOCASE", [table])?,
+    };
+    if table_exists(conn, "sqlite_stat4")? {
+        match index {
+            Some(index) => conn.execute(
+                "DELETE FROM sqlite_stat4 WHERE tbl = ?1 COLLATE NOCASE AND idx = ?2 COLLATE NOCASE",
+                (table, index),
+            )?,
+            None => conn.execute("DELETE FROM sqlite_stat4 WHERE tbl = ?1 COLLATE NOCASE", [table])?,
+        };
+    }
+    reload_stats(conn)?;
+    Ok(removed)
+}
+
+/// Collects the statistics of the database for export
+pub fn export_stats(conn: &Connection) -> Result<PlannerStats> {
+    Ok(PlannerStats {
+        version: STATS_EXPORT_VERSION,
+        stat1: read_stat1(conn, None)?,
+        stat4: read_stat4(conn, None)?,
+    })
+}
+
+/// Imports exported statistics in one savepoint, replacing the statistics of
+/// every table they mention. Rows for tables or indexes missing here are skipped.
+///
+/// Returns the number of stat1 and stat4 rows written.
+pub fn import_stats(conn: &Connection, stats: &PlannerStats) -> Result<(usize, usize)> {
+    if stats.version > STATS_EXPORT_VERSION {
+        return Err(TuiqlError::Query(format!(
+            "Unsupported statistics version {} (expected {} or older)",
+            stats.version, STATS_EXPORT_VERSION
+        )));
+    }
+    reload_stats(conn)?;
+    let has_stat4 = table_exists(conn, "sqlite_stat4")?;
+
+    // A savepoint nests inside a transaction the user may already have open
+    conn.execute_batch("SAVEPOINT tuiql_import_stats")?;
+    let result = (|| -> Result<(usize, usize)> {
+        let mut tables: Vec<&str> = stats.stat1.iter().map(|r| r.tbl.as_str()).collect();
+        tables.extend(stats.stat4.iter().map(|r| r.tbl.as_str()));
+        tables.sort_unstable();
+        tables.dedup();
+        for table in tables {
+            conn.execute("DELETE FROM sqlite_stat1 WHERE tbl = ?1 COLLATE NOCASE", [table])?;
+            if has_stat4 {
+                conn.execute("DELETE FROM sqlite_stat4 WHERE tbl = ?1 COLLATE NOCASE", [table])?;
+            }
+        }
+
+        let mut stat1 = 0;
+        for row in &stats.stat1 {
+            if validate_stat1(conn, row).is_err() {
+                continue;
+            }
+            conn.execute(
+                "INSERT INTO sqlite_stat1 (tbl, idx, stat) VALUES (?1, ?2, ?3)",
+                (&row.tbl, &row.idx, &row.stat),
+            )?;
+            stat1 += 1;
+        }
+
+        let mut stat4 = 0;
+        if has_stat4 {
+            for row in &stats.stat4 {
+                let index = Stat1Row { tbl: row.tbl.clone(), idx: Some(row.idx.clone()), stat: "1".to_string() };
+                if validate_stat1(conn, &index).is_err() {
+                    continue;
+                }
+                conn.execute(
+                    "INSERT INTO sqlite_stat4 (tbl, idx, neq, nlt, ndlt, sample) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    (&row.tbl, &row.idx, &row.neq, &row.nlt, &row.ndlt, from_hex(&row.sample)?),
+                )?;
+                stat4 += 1;
+            }
+        }
+        Ok((stat1, stat4))
+    })();
+
+    match result {
+        Ok(counts) => {
+            conn.execute_batch("RELEASE tuiql_import_stats")?;
+            reload_stats(conn)?;
+            Ok(counts)
+        }
+        Err(e) => {
+            conn.execute_batch("ROLLBACK TO tuiql_import_stats; RELEASE tuiql_import_stats")?;
+            Err(e)
+        }
+    }
+}
+
+/// Key column names of an index; expression columns are shown as "expr"
+fn index_key_columns(conn: &Connection, index: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_index_xinfo(?1) WHERE key = 1 ORDER BY seqno")?;
+    let columns = stmt
+        .query_map([index], |row| row.get::<_, Option<String>>(0))?
+        .map(|name| name.map(|n| n.unwrap_or_else(|| "expr".to_string())))
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(columns)
+}
+
+/// Renders stat1 rows grouped by table, with per-index rows-per-key decoded and
+/// the number of stat4 samples
+pub fn render_stats(conn: &Connection, stat1: &[Stat1Row], stat4: &[Stat4Row]) -> Result<String> {
+    let mut output = String::from("📊 Planner statistics\n");
+    output.push_str(&"=".repeat(60));
+    output.push('\n');
+    if stat1.is_empty() {
+        output.push_str("No sqlite_stat1 entries. Run ':analyze' to collect statistics.\n");
+        return Ok(output);
+    }
+
+    let mut current_table = None;
+    for row in stat1 {
+        if current_table != Some(&row.tbl) {
+            current_table = Some(&row.tbl);
+            output.push_str(&format!("\n📋 {}\n", row.tbl));
+        }
+        match &row.idx {
+            None => output.push_str(&format!("  rows: {}  (stat \"{}\")\n", row.row_estimate().unwrap_or(0), row.stat)),
+            Some(idx) => {
+                let columns = index_key_columns(conn, idx).unwrap_or_default();
+                let numbers = row.numbers();
+                let per_key: Vec<String> = numbers
+                    .iter()
+                    .skip(1)
+                    .enumerate()
+                    .map(|(i, n)| {
+                        let prefix = columns.get(..=i).map(|c| c.join(",")).unwrap_or_else(|| format!("col{}", i + 1));
+                        format!("{} → {}", prefix, n)
+                    })
+                    .collect();
+                let samples = stat4.iter().filter(|s| &s.idx == idx).count();
+                output.push_str(&format!("  📇 {} (stat \"{}\")\n", idx, row.stat));
+                output.push_str(&format!("     {} rows", numbers.first().copied().unwrap_or(0)));
+                if !per_key.is_empty() {
+                    output.push_str(&format!("; rows per key: {}", per_key.join(", ")));
+                }
+                if samples > 0 {
+                    output.push_str(&format!("; {} stat4 sample(s)", samples));
+                }
+                output.push('\n');
+            }
+        }
+    }
+    Ok(output)
+}
+
+const USAGE: &str = "Usage: :analyze [table] [--limit N] | show [table] | set <table> [index] <stat> | delete <table> [index] | export [file] | import <file>";
+
+/// Executes `:analyze` and its subcommands.
+pub fn execute_analyze_command(command: &str) -> Result<()> {
+    let args: Vec<&str> = command.split_whitespace().collect();
+
+    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::App("No database connection found".to_string()))?;
+    let state_guard = state_cell
+        .lock()
+        .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+    let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+
+    match args.first().copied() {
+        Some("show") => {
+            let table = args.get(1).copied();
+            print!("{}", render_stats(conn, &read_stat1(conn, table)?, &read_stat4(conn, table)?)?);
+        }
+        Some("set") => {
+            let (table, rest) = args[1..].split_first().ok_or_else(|| TuiqlError::Command(USAGE.to_string()))?;
+            // The index is optional; the stat always starts with the row count
+            let (index, stat) = match rest.split_first() {
+                Some((first, stat)) if first.parse::<u64>().is_err() => (Some(first.to_string()), stat),
+                _ => (None, rest),
+            };
+            if stat.is_empty() {
+                return Err(TuiqlError::Command(USAGE.to_string()));
+            }
+            let row = Stat1Row { tbl: table.to_string(), idx: index, stat: stat.join(" ") };
+            set_stat1(conn, &row)?;
+            println!("✅ sqlite_stat1 updated for {}", row.idx.as_deref().unwrap_or(&row.tbl));
+        }
+        Some("delete") => {
+            let table = args.get(1).ok_or_else(|| TuiqlError::Command(USAGE.to_string()))?;
+            let removed = delete_stats(conn, table, args.get(2).copied())?;
+            println!("🗑️  Removed {} sqlite_stat1 row(s)", removed);
+        }
+        Some("export") => {
+            let json = serde_json::to_string_pretty(&export_stats(conn)?)?;
+            match args.get(1) {
+                Some(path) => {
+                    std::fs::write(path, json)?;
+                    println!("✅ Statistics written to {}", path);
+                }
+                None => println!("{}", json),
+            }
+        }
+        Some("import") => {
+            let path = args.get(1).ok_or_else(|| TuiqlError::Command(USAGE.to_string()))?;
+            let stats: PlannerStats = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+            let (stat1, stat4) = import_stats(conn, &stats)?;
+            println!("✅ Imported {} sqlite_stat1 and {} sqlite_stat4 row(s) from {}", stat1, stat4, path);
+        }
+        _ => {
+            let mut table = None;
+            let mut limit = None;
+            let mut args = args.into_iter();
+            while let Some(arg) = args.next() {
+                match arg {
+                    "--limit" => {
+                        let value = args
+                            .next()
+                            .ok_or_else(|| TuiqlError::Command("--limit requires a number of rows".to_string()))?;
+                        limit = Some(value.parse::<u32>().map_err(|_| {
+                            TuiqlError::Command(format!("Invalid analysis limit '{}'", value))
+                        })?);
+                    }
+                    other if other.starts_with("--") => {
+                        return Err(TuiqlError::Command(format!("Unknown option '{}'", other)));
+                    }
+                    other => {
+                        if table.replace(other).is_some() {
+                            return Err(TuiqlError::Command(USAGE.to_string()));
+                        }
+                    }
+                }
+            }
+            let rows = run_analyze(conn, table, limit)?;
+            println!(
+                "✅ Analyzed {}{}: {} sqlite_stat1 row(s)",
+                table.unwrap_or("all tables"),
+                limit.map(|l| format!(" (analysis_limit {})", l)).unwrap_or_default(),
+                rows
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::query_plan;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE events (id INTEGER PRIMARY KEY, kind INTEGER, owner INTEGER);
+             CREATE INDEX idx_events_kind ON events(kind);
+             CREATE INDEX idx_events_owner ON events(owner);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 500)
+             INSERT INTO events (kind, owner) SELECT i % 5, i FROM n;",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_run_analyze_and_show() {
+        let conn = setup();
+        assert!(read_stat1(&conn, None).unwrap().is_empty());
+
+        assert_eq!(run_analyze(&conn, Some("events"), Some(100)).unwrap(), 2);
+        let limit: i64 = conn.query_row("PRAGMA analysis_limit", [], |row| row.get(0)).unwrap();
+        assert_eq!(limit, 0);
+
+        run_analyze(&conn, None, None).unwrap();
+        let stat1 = read_stat1(&conn, Some("EVENTS")).unwrap();
+        let kind = stat1.iter().find(|r| r.idx.as_deref() == Some("idx_events_kind")).unwrap();
+        assert_eq!(kind.numbers(), vec![500, 100]);
+        assert!(!read_stat4(&conn, Some("events")).unwrap().is_empty());
+
+        let rendered = render_stats(&conn, &stat1, &read_stat4(&conn, None).unwrap()).unwrap();
+        assert!(rendered.contains("📋 events"));
+        assert!(rendered.contains("rows per key: kind → 100"));
+        assert!(rendered.contains("stat4 sample(s)"));
+
+        assert!(run_analyze(&conn, Some("missing"), None).is_err());
+    }
+
+    #[test]
+    fn test_set_stat1_changes_plan() {
+        let conn = setup();
+        let query = "SELECT * FROM events WHERE kind = 1 AND owner = 2";
+        let plan_uses = |index: &str| query_plan(&conn, query).unwrap().iter().any(|n| n.details.contains(index));
+
+        let set = |index: &str, stat: &str| {
+            set_stat1(
+                &conn,
+                &Stat1Row { tbl: "events".to_string(), idx: Some(index.to_string()), stat: stat.to_string() },
+            )
+            .unwrap()
+        };
+        set("idx_events_kind", "1000000 2 1");
+        set("idx_events_owner", "1000000 50000 1");
+        assert!(plan_uses("idx_events_kind"));
+
+        set("idx_events_kind", "1000000 50000 1");
+        set("idx_events_owner", "1000000 2 1");
+        assert!(plan_uses("idx_events_owner"));
+        assert_eq!(read_stat1(&conn, None).unwrap().len(), 2);
+
+        let invalid = Stat1Row { tbl: "events".to_string(), idx: Some("idx_missing".to_string()), stat: "1".to_string() };
+        assert!(set_stat1(&conn, &invalid).is_err());
+        let invalid = Stat1Row { tbl: "events".to_string(), idx: None, stat: "many".to_string() };
+        assert!(set_stat1(&conn, &invalid).is_err());
+
+        // Names match case-insensitively, like they do in :analyze show
+        assert_eq!(delete_stats(&conn, "Events", Some("IDX_events_owner")).unwrap(), 1);
+        assert_eq!(delete_stats(&conn, "EVENTS", None).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let production = setup();
+        run_analyze(&production, None, None).unwrap();
+        let exported = export_stats(&production).unwrap();
+        let json = serde_json::to_string(&exported).unwrap();
+
+        let development = Connection::open_in_memory().unwrap();
+        development
+            .execute_batch(
+                "CREATE TABLE events (id INTEGER PRIMARY KEY, kind INTEGER, owner INTEGER);
+                 CREATE INDEX idx_events_kind ON events(kind);",
+            )
+            .unwrap();
+        let imported: PlannerStats = serde_json::from_str(&json).unwrap();
+        let (stat1, stat4) = import_stats(&development, &imported).unwrap();
+
+        // The idx_events_owner rows have no index to describe on the development copy
+        assert_eq!(stat1, 1);
+        assert!(stat4 > 0);
+        assert_eq!(
+            read_stat1(&development, None).unwrap(),
+            exported.stat1.into_iter().filter(|r| r.idx.as_deref() != Some("idx_events_owner")).collect::<Vec<_>>()
+        );
+        assert_eq!(from_hex(&to_hex(&[0, 15, 255])).unwrap(), vec![0, 15, 255]);
+
+        // Importing inside an open transaction nests and leaves the transaction to the user
+        development.execute_batch("BEGIN").unwrap();
+        assert_eq!(import_stats(&development, &imported).unwrap().0, 1);
+        assert!(!development.is_autocommit());
+        development.execute_batch("ROLLBACK").unwrap();
+        assert_eq!(read_stat1(&development, None).unwrap().len(), 1);
+    }
+}
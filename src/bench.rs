@@ -0,0 +1,377 @@
+//! Query Benchmark Module
+//!
+//! Runs a query a number of times after warm-up runs and reports the
+//! distribution of its execution times, the rows it returns and the VM steps
+//! it takes. Results are stored so later runs of the same query can be
+//! compared against them.
+
+use crate::core::{Result, TuiqlError};
+use crate::db::{self, StatementStats};
+use crate::plan::normalize_query_text;
+use crate::storage::Storage;
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Relative median change below which two benchmarks are considered equal
+pub const NOISE_THRESHOLD: f64 = 0.05;
+
+/// How a benchmark is run
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BenchOptions {
+    pub runs: usize,
+    /// Untimed runs before the measured ones
+    pub warmup: usize,
+    /// Drop SQLite's page cache before every run
+    pub cold: bool,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        BenchOptions { runs: 10, warmup: 2, cold: false }
+    }
+}
+
+/// Timing distribution of a benchmark, in milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub options: BenchOptions,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub mean_ms: f64,
+    /// Rows returned per run
+    pub rows: u64,
+    /// VM steps per run
+    pub vm_steps: i32,
+}
+
+/// Median of sorted samples
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Runs the query once to completion and returns its time, rows and counters
+fn run_once(conn: &Connection, query: &str, params: &[Value], cold: bool) -> Result<(f64, u64, StatementStats)> {
+    if cold {
+        conn.execute_batch("PRAGMA shrink_memory")?;
+    }
+    let mut stmt = conn.prepare(query)?;
+    // Timing a query with every parameter NULL would measure a plan nobody runs
+    if stmt.parameter_count() != params.len() {
+        return Err(TuiqlError::Command(format!(
+            "The query has {} parameter(s) but {} value(s) were given; pass one --param per parameter",
+            stmt.parameter_count(),
+            params.len()
+        )));
+    }
+
+    let start = Instant::now();
+    let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+    let mut count = 0;
+    while rows.next()?.is_some() {
+        count += 1;
+    }
+    drop(rows);
+    let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+    Ok((elapsed, count, StatementStats::from_statement(&stmt)))
+}
+
+/// Benchmarks `query` on `conn`, binding `params` to its parameters in order.
+///
+/// Every run happens inside a savepoint that is rolled back afterwards, so
+/// statements that write are measured against the same data each time.
+pub fn run_benchmark(conn: &Connection, query: &str, params: &[Value], options: &BenchOptions) -> Result<BenchResult> {
+    if options.runs == 0 {
+        return Err(TuiqlError::Command("The number of runs must be at least 1".to_string()));
+    }
+    let query = query.trim().trim_end_matches(';');
+
+    let mut samples = Vec::with_capacity(options.runs);
+    let mut rows = 0;
+    let mut stats = StatementStats::default();
+    for i in 0..options.warmup + options.runs {
+        conn.execute_batch("SAVEPOINT tuiql_bench")?;
+        let result = run_once(conn, query, params, options.cold);
+        conn.execute_batch("ROLLBACK TO tuiql_bench; RELEASE tuiql_bench")?;
+        let (elapsed, count, run_stats) = result?;
+        if i >= options.warmup {
+            samples.push(elapsed);
+            rows = count;
+            stats = run_stats;
+        }
+    }
+
+    samples.sort_by(|a, b| a.total_cmp(b));
+    Ok(BenchResult {
+        options: *options,
+        min_ms: samples[0],
+        median_ms: median(&samples),
+        p95_ms: percentile(&samples, 95.0),
+        max_ms: samples[samples.len() - 1],
+        mean_ms: samples.iter().sum::<f64>() / samples.len() as f64,
+        rows,
+        vm_steps: stats.vm_steps,
+    })
+}
+
+/// Formats a benchmark, compared against a previous one if given
+pub fn format_bench(result: &BenchResult, previous: Option<(&BenchResult, i64)>) -> String {
+    let options = &result.options;
+    let mut output = format!(
+        "⏱️  Benchmark: {} run(s) after {} warm-up run(s), {} cache\n",
+        options.runs,
+        options.warmup,
+        if options.cold { "cold" } else { "warm" }
+    );
+    output.push_str(&"=".repeat(60));
+    output.push('\n');
+    for (label, value) in [
+        ("min", result.min_ms),
+        ("median", result.median_ms),
+        ("p95", result.p95_ms),
+        ("max", result.max_ms),
+        ("mean", result.mean_ms),
+    ] {
+        output.push_str(&format!("  {:<8} {:>10.3} ms\n", label, value));
+    }
+    output.push_str(&format!("  {:<8} {:>10} per run\n", "rows", result.rows));
+    output.push_str(&format!("  {:<8} {:>10} per run\n", "VM steps", result.vm_steps));
+
+    if let Some((previous, timestamp)) = previous {
+        let recorded = chrono::DateTime::from_timestamp(timestamp, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let change = if previous.median_ms > 0.0 {
+            (result.median_ms - previous.median_ms) / previous.median_ms
+        } else {
+            0.0
+        };
+        let verdict = if change <= -NOISE_THRESHOLD {
+            "🏎️ faster"
+        } else if change >= NOISE_THRESHOLD {
+            "🐢 slower"
+        } else {
+            "≈ unchanged"
+        };
+        output.push_str(&format!(
+            "\n📊 vs {}: median {:.3} ms vs {:.3} ms ({:+.1}%) {}\n",
+            recorded,
+            result.median_ms,
+            previous.median_ms,
+            change * 100.0,
+            verdict
+        ));
+        if previous.vm_steps != result.vm_steps {
+            output.push_str(&format!("   VM steps {} → {}\n", previous.vm_steps, result.vm_steps));
+        }
+        if previous.options.cold != options.cold {
+            output.push_str(&format!(
+                "   ⚠️  The previous benchmark used a {} cache\n",
+                if previous.options.cold { "cold" } else { "warm" }
+            ));
+        }
+    }
+    output
+}
+
+const USAGE: &str = "Usage: :bench N [--warmup W] [--cold] [--param V]... <query>";
+
+/// Reads a `--param` value: an integer, a real, NULL, or text, optionally in single quotes
+pub fn parse_param(value: &str) -> Value {
+    if let Ok(integer) = value.parse::<i64>() {
+        Value::Integer(integer)
+    } else if let Ok(real) = value.parse::<f64>() {
+        Value::Real(real)
+    } else if value.eq_ignore_ascii_case("null") {
+        Value::Null
+    } else {
+        let text = value
+            .strip_prefix('\'')
+            .and_then(|v| v.strip_suffix('\''))
+            .unwrap_or(value);
+        Value::Text(text.to_string())
+    }
+}
+
+/// Parses `N [--warmup W] [--cold] [--param V]... <query>`, keeping the query text as typed
+pub fn parse_bench_args(command: &str) -> Result<(BenchOptions, Vec<Value>, &str)> {
+    let mut options = BenchOptions::default();
+    let mut params = Vec::new();
+    let (runs, mut rest) = command
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| TuiqlError::Command(USAGE.to_string()))?;
+    options.runs = runs
+        .parse()
+        .ok()
+        .filter(|runs| *runs > 0)
+        .ok_or_else(|| TuiqlError::Command(format!("Invalid number of runs '{}'. {}", runs, USAGE)))?;
+
+    loop {
+        rest = rest.trim_start();
+        let (word, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        match word {
+            "--cold" => options.cold = true,
+            "--warmup" => {
+                let (value, after) = after.trim_start().split_once(char::is_whitespace).unwrap_or((after.trim(), ""));
+                options.warmup = value
+                    .parse()
+                    .map_err(|_| TuiqlError::Command("--warmup requires a number of runs".to_string()))?;
+                rest = after;
+                continue;
+            }
+            "--param" => {
+                let (value, after) = after.trim_start().split_once(char::is_whitespace).unwrap_or((after.trim(), ""));
+                if value.is_empty() {
+                    return Err(TuiqlError::Command("--param requires a value".to_string()));
+                }
+                params.push(parse_param(value));
+                rest = after;
+                continue;
+            }
+            other if other.starts_with("--") => {
+                return Err(TuiqlError::Command(format!("Unknown option '{}'", other)));
+            }
+            _ => break,
+        }
+        rest = after;
+    }
+
+    if rest.trim().is_empty() {
+        return Err(TuiqlError::Command(USAGE.to_string()));
+    }
+    Ok((options, params, rest.trim()))
+}
+
+/// Key under which results are stored: the query, plus its parameter values
+fn bench_key(query: &str, params: &[Value]) -> String {
+    let key = normalize_query_text(query);
+    if params.is_empty() {
+        return key;
+    }
+    let values: Vec<String> = params
+        .iter()
+        .map(|value| match value {
+            Value::Text(text) => format!("'{}'", text),
+            other => db::format_value(other.into()),
+        })
+        .collect();
+    format!("{} -- params: {}", key, values.join(", "))
+}
+
+/// Executes `:bench`, storing the result and comparing it with the previous one.
+pub fn execute_bench_command<F>(command: &str, storage: &Storage, cancellation_monitor: F) -> Result<()>
+where
+    F: FnOnce(rusqlite::InterruptHandle) + Send + 'static,
+{
+    let (options, params, query) = parse_bench_args(command)?;
+
+    let database_path = db::DB_STATE
+        .get()
+        .and_then(|state| state.lock().ok()?.current_path.clone())
+        .unwrap_or_else(|| "main".to_string());
+
+    let result = db::with_cancellable_connection(cancellation_monitor, |_| {}, |conn, _| {
+        run_benchmark(conn, query, &params, &options)
+    })
+    .map_err(|e| {
+        if db::is_interrupted(&e) {
+            TuiqlError::Query("Benchmark cancelled by user (Ctrl+C)".to_string())
+        } else {
+            e
+        }
+    })?;
+
+    let key = bench_key(query, &params);
+    let previous = storage
+        .get_last_benchmark(&database_path, &key)?
+        .and_then(|(json, timestamp)| serde_json::from_str::<BenchResult>(&json).ok().map(|r| (r, timestamp)));
+    print!("{}", format_bench(&result, previous.as_ref().map(|(r, t)| (r, *t))));
+    storage.save_benchmark(&database_path, &key, &serde_json::to_string(&result)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statistics() {
+        let samples = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+        assert_eq!(median(&samples), 5.5);
+        assert_eq!(median(&samples[..3]), 2.0);
+        assert_eq!(percentile(&samples, 95.0), 10.0);
+        assert_eq!(percentile(&samples, 50.0), 5.0);
+        assert_eq!(percentile(&[4.0], 95.0), 4.0);
+    }
+
+    #[test]
+    fn test_run_benchmark() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, value INTEGER);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200)
+             INSERT INTO items (value) SELECT i FROM n;",
+        )
+        .unwrap();
+
+        let options = BenchOptions { runs: 5, warmup: 1, cold: true };
+        // Parameters must be given values rather than benchmarked as NULL
+        assert!(run_benchmark(&conn, "SELECT * FROM items WHERE value > ?;", &[], &options).is_err());
+        let result = run_benchmark(&conn, "SELECT * FROM items WHERE value > ?;", &[Value::Integer(150)], &options).unwrap();
+        assert_eq!(result.rows, 50);
+        let result = run_benchmark(&conn, "SELECT * FROM items WHERE value > 100", &[], &options).unwrap();
+        assert_eq!(result.rows, 100);
+        assert!(result.vm_steps > 0);
+        assert!(result.min_ms <= result.median_ms && result.median_ms <= result.p95_ms && result.p95_ms <= result.max_ms);
+
+        // Writes are rolled back after every run
+        run_benchmark(&conn, "DELETE FROM items", &[], &options).unwrap();
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 200);
+
+        let slower = BenchResult { median_ms: result.median_ms * 2.0 + 1.0, ..result.clone() };
+        let report = format_bench(&result, Some((&slower, 0)));
+        assert!(report.contains("median"));
+        assert!(report.contains("🏎️ faster"));
+        assert!(format_bench(&result, Some((&result, 0))).contains("≈ unchanged"));
+    }
+
+    #[test]
+    fn test_parse_bench_args() {
+        let (options, params, query) = parse_bench_args("20 --warmup 3 --cold SELECT  'a  b'").unwrap();
+        assert_eq!(options, BenchOptions { runs: 20, warmup: 3, cold: true });
+        assert!(params.is_empty());
+        assert_eq!(query, "SELECT  'a  b'");
+
+        let (options, _, query) = parse_bench_args("5 SELECT 1").unwrap();
+        assert_eq!(options.warmup, BenchOptions::default().warmup);
+        assert_eq!(query, "SELECT 1");
+
+        let (_, params, query) = parse_bench_args("5 --param 42 --param 'shipped' --param null SELECT ?, ?, ?").unwrap();
+        assert_eq!(params, vec![Value::Integer(42), Value::Text("shipped".to_string()), Value::Null]);
+        assert_eq!(query, "SELECT ?, ?, ?");
+        assert_eq!(bench_key(query, &params), "SELECT ?, ?, ? -- params: 42, 'shipped', NULL");
+        assert_eq!(parse_param("1.5"), Value::Real(1.5));
+        assert!(parse_bench_args("5 --param").is_err());
+
+        assert!(parse_bench_args("SELECT 1").is_err());
+        assert!(parse_bench_args("0 SELECT 1").is_err());
+        assert!(parse_bench_args("5 --fast SELECT 1").is_err());
+        assert!(parse_bench_args("5 --cold").is_err());
+    }
+}
@@ -1,7 +1,30 @@
 use tracing::info;
-use tuiql::{config, db, repl};
+use tuiql::{config, db, repl, schema_map};
+
+/// Runs `tuiql erd <database> [:erd arguments]` without starting the REPL.
+///
+/// The database is opened read-only, so exporting never creates or changes it.
+fn run_headless_erd(db_path: &str, args: &[String]) -> i32 {
+    if let Err(e) = db::connect_read_only(db_path) {
+        eprintln!("Failed to connect to database: {}", e);
+        return 1;
+    }
+    match schema_map::execute_erd_command(&args.join(" ")) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Failed to generate ERD: {}", e);
+            1
+        }
+    }
+}
 
 fn main() {
+    // Headless commands write only their own output, so they run before logging and the banner
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() >= 3 && args[1] == "erd" {
+        std::process::exit(run_headless_erd(&args[2], &args[3..]));
+    }
+
     // Initialize the logging system using tracing subscriber
     tracing_subscriber::fmt::init();
 
@@ -23,7 +46,6 @@ fn main() {
     println!("Welcome to tuiql! A blazing-fast, terminal-native SQLite client.");
 
     // Parse CLI arguments
-    let args: Vec<String> = std::env::args().collect();
     match args.len() {
         1 => {
             println!("No database provided. Running in interactive mode.");
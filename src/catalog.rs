@@ -1,4 +1,17 @@
+//! Catalog Module
+//!
+//! This module keeps a cached copy of the connected database's schema so that the
+//! schema navigator, SQL completer, ERD and diff views share a single introspection
+//! pass. The cache is keyed on `PRAGMA schema_version` of every database on the
+//! connection and is rebuilt only when that key changes; checking the key takes one
+//! pragma call per database.
+
+use crate::core::db::schema::{Column, Schema};
 use crate::core::{Result, TuiqlError};
+use crate::db;
+use once_cell::sync::Lazy;
+use rusqlite::Connection;
+use std::sync::{Arc, Mutex};
 
 pub struct TableInfo {
     pub name: String,
@@ -13,9 +26,243 @@ pub struct ColumnInfo {
     pub default_value: Option<String>,
 }
 
-/// Simulates discovery of a database schema by returning dummy table information.
-/// In a real implementation, this function would connect to a SQLite database,
-/// query the sqlite_master table and PRAGMA table_info for each table.
+/// Kind of a schema object in `sqlite_master`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    Table,
+    VirtualTable,
+    View,
+    Trigger,
+    Index,
+}
+
+/// A named object from `sqlite_master`, excluding internal and shadow tables
+#[derive(Debug, Clone)]
+pub struct SchemaObject {
+    pub name: String,
+    pub kind: ObjectKind,
+    /// Table the object belongs to (the object itself for tables and views)
+    pub table_name: String,
+    /// Defining SQL; `None` for automatic indexes
+    pub sql: Option<String>,
+    /// Result columns, for views only
+    pub columns: Vec<Column>,
+    /// Why a view's columns could not be read, e.g. it references a dropped table
+    pub error: Option<String>,
+}
+
+impl SchemaObject {
+    /// Module name of a virtual table (e.g. "fts5"), parsed from its `USING` clause
+    pub fn module(&self) -> Option<String> {
+        if self.kind != ObjectKind::VirtualTable {
+            return None;
+        }
+        let sql = self.sql.as_deref()?;
+        let upper = sql.to_uppercase();
+        let start = upper.find(" USING ")? + " USING ".len();
+        let module: String = sql[start..]
+            .trim_start()
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        Some(module.to_lowercase()).filter(|m| !m.is_empty())
+    }
+}
+
+/// Introspected schema information shared by the schema-aware views
+#[derive(Debug)]
+pub struct Catalog {
+    /// Tables, columns, indexes and foreign keys of the main database
+    pub schema: Schema,
+    /// All user objects of the main database, ordered by kind and name
+    pub objects: Vec<SchemaObject>,
+    /// `PRAGMA schema_version` of the main database when the catalog was built
+    pub schema_version: i64,
+}
+
+impl Catalog {
+    /// Introspects the main database of `conn`
+    fn load(conn: &Connection, schema_version: i64) -> Result<Self> {
+        let schema = Schema::from_connection(conn)?;
+        let objects = load_objects(conn)?;
+
+        Ok(Catalog {
+            schema,
+            objects,
+            schema_version,
+        })
+    }
+
+    /// Returns the objects of one kind, ordered by name
+    pub fn objects_of(&self, kind: ObjectKind) -> impl Iterator<Item = &SchemaObject> {
+        self.objects.iter().filter(move |o| o.kind == kind)
+    }
+}
+
+/// Reads the user objects from `sqlite_master`, skipping internal and shadow tables
+fn load_objects(conn: &Connection) -> Result<Vec<SchemaObject>> {
+    let mut stmt = conn.prepare(
+        "SELECT type, name, tbl_name, sql FROM sqlite_master
+         WHERE name NOT LIKE 'sqlite_%'
+           AND name NOT IN (SELECT name FROM pragma_table_list WHERE type = 'shadow')
+         ORDER BY CASE type WHEN 'table' THEN 0 WHEN 'view' THEN 1 WHEN 'trigger' THEN 2 ELSE 3 END, name",
+    )?;
+    let rows: Vec<(String, String, String, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut objects = Vec::with_capacity(rows.len());
+    for (object_type, name, table_name, sql) in rows {
+        let kind = match object_type.as_str() {
+            "table" if sql.as_deref().is_some_and(|s| s.to_uppercase().starts_with("CREATE VIRTUAL TABLE")) => {
+                ObjectKind::VirtualTable
+            }
+            "table" => ObjectKind::Table,
+            "view" => ObjectKind::View,
+            "trigger" => ObjectKind::Trigger,
+            _ => ObjectKind::Index,
+        };
+        // A broken view must not hide the rest of the schema
+        let (columns, error) = match kind {
+            ObjectKind::View => match view_columns(conn, &name) {
+                Ok(columns) => (columns, None),
+                Err(e) => (Vec::new(), Some(e.to_string())),
+            },
+            _ => (Vec::new(), None),
+        };
+        objects.push(SchemaObject {
+            name,
+            kind,
+            table_name,
+            sql,
+            columns,
+            error,
+        });
+    }
+    Ok(objects)
+}
+
+/// Reads the result columns of a view
+fn view_columns(conn: &Connection, view: &str) -> Result<Vec<Column>> {
+    let mut stmt = conn.prepare("SELECT name, type, \"notnull\", pk, dflt_value FROM pragma_table_info(?1)")?;
+    let columns = stmt
+        .query_map([view], |row| {
+            Ok(Column {
+                name: row.get(0)?,
+                type_name: row.get(1)?,
+                notnull: row.get(2)?,
+                pk: row.get(3)?,
+                dflt_value: row.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(columns)
+}
+
+/// Identifies one version of the schema of every database on a connection
+#[derive(Debug, Clone, PartialEq)]
+struct CatalogKey {
+    /// (name, file, schema_version) per database; schema_version is read from the
+    /// file, so schema changes made by other processes are picked up too
+    databases: Vec<(String, String, i64)>,
+}
+
+impl CatalogKey {
+    fn read(conn: &Connection) -> Result<Self> {
+        let mut stmt = conn.prepare("PRAGMA database_list")?;
+        let databases: Vec<(String, String)> = stmt
+            .query_map([], |row| Ok((row.get(1)?, row.get::<_, Option<String>>(2)?.unwrap_or_default())))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut key = Vec::with_capacity(databases.len());
+        for (name, file) in databases {
+            let quoted = name.replace('"', "\"\"");
+            let schema_version: i64 =
+                conn.query_row(&format!("PRAGMA \"{}\".schema_version", quoted), [], |row| row.get(0))?;
+            key.push((name, file, schema_version));
+        }
+
+        Ok(CatalogKey { databases: key })
+    }
+
+    fn main_schema_version(&self) -> i64 {
+        self.databases
+            .iter()
+            .find(|(name, ..)| name == "main")
+            .map(|(_, _, version)| *version)
+            .unwrap_or(0)
+    }
+}
+
+/// A catalog cache for a single connection
+#[derive(Debug, Default)]
+pub struct CatalogCache {
+    entry: Option<(CatalogKey, Arc<Catalog>)>,
+    rebuilds: usize,
+}
+
+impl CatalogCache {
+    /// Creates an empty cache; the first lookup builds the catalog
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the catalog for `conn`, rebuilding it only if the schema changed
+    pub fn get(&mut self, conn: &Connection) -> Result<Arc<Catalog>> {
+        let key = CatalogKey::read(conn)?;
+        if let Some((cached_key, catalog)) = &self.entry {
+            if *cached_key == key {
+                return Ok(Arc::clone(catalog));
+            }
+        }
+
+        let catalog = Arc::new(Catalog::load(conn, key.main_schema_version())?);
+        self.entry = Some((key, Arc::clone(&catalog)));
+        self.rebuilds += 1;
+        Ok(catalog)
+    }
+
+    /// Drops the cached catalog, e.g. when a different database is opened
+    pub fn invalidate(&mut self) {
+        self.entry = None;
+    }
+
+    /// Number of times the catalog has been (re)built
+    pub fn rebuild_count(&self) -> usize {
+        self.rebuilds
+    }
+}
+
+/// Catalog cache for the global database connection in `db::DB_STATE`
+static CATALOG_CACHE: Lazy<Mutex<CatalogCache>> = Lazy::new(|| Mutex::new(CatalogCache::new()));
+
+/// Returns the catalog of the current database connection
+///
+/// Returns `TuiqlError::Schema` when no database is connected.
+pub fn current_catalog() -> Result<Arc<Catalog>> {
+    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::Schema("No database connection found. Please connect to a database first.".to_string()))?;
+    let state_guard = state_cell
+        .lock()
+        .map_err(|_| TuiqlError::Schema("Failed to acquire database connection lock".to_string()))?;
+    let conn = state_guard
+        .connection
+        .as_ref()
+        .ok_or(TuiqlError::Schema("No active database connection".to_string()))?;
+
+    CATALOG_CACHE
+        .lock()
+        .map_err(|_| TuiqlError::App("Failed to acquire catalog cache lock".to_string()))?
+        .get(conn)
+}
+
+/// Discards the cached catalog of the current database connection
+pub fn invalidate() {
+    if let Ok(mut cache) = CATALOG_CACHE.lock() {
+        cache.invalidate();
+    }
+}
+
+/// Discovers the tables and columns of the database file at `db_path`
 pub fn discover_schema(db_path: &str) -> Result<Vec<TableInfo>> {
     if db_path.is_empty() {
         return Err(TuiqlError::Schema(
@@ -23,42 +270,57 @@ pub fn discover_schema(db_path: &str) -> Result<Vec<TableInfo>> {
         ));
     }
 
-    // Stub: Return dummy table information.
-    let dummy_table = TableInfo {
-        name: "dummy_table".to_string(),
-        columns: vec![
-            ColumnInfo {
-                name: "id".to_string(),
-                data_type: "INTEGER".to_string(),
-                not_null: true,
-                primary_key: true,
-                default_value: None,
-            },
-            ColumnInfo {
-                name: "name".to_string(),
-                data_type: "TEXT".to_string(),
-                not_null: false,
-                primary_key: false,
-                default_value: Some("unknown".to_string()),
-            },
-        ],
-    };
+    let conn = Connection::open(db_path)?;
+    let catalog = CatalogCache::new().get(&conn)?;
 
-    Ok(vec![dummy_table])
+    let mut tables: Vec<TableInfo> = catalog
+        .schema
+        .tables
+        .values()
+        .map(|table| TableInfo {
+            name: table.name.clone(),
+            columns: table
+                .columns
+                .iter()
+                .map(|col| ColumnInfo {
+                    name: col.name.clone(),
+                    data_type: col.type_name.clone(),
+                    not_null: col.notnull,
+                    primary_key: col.pk,
+                    default_value: col.dflt_value.clone(),
+                })
+                .collect(),
+        })
+        .collect();
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(tables)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::NamedTempFile;
 
     #[test]
     fn test_discover_schema_valid_db() {
-        let result = discover_schema("dummy.db");
+        let db_file = NamedTempFile::new().unwrap();
+        let conn = Connection::open(db_file.path()).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT DEFAULT 'unknown');
+             CREATE TABLE accounts (id INTEGER PRIMARY KEY);",
+        )
+        .unwrap();
+
+        let result = discover_schema(db_file.path().to_str().unwrap());
         assert!(result.is_ok());
         let tables = result.unwrap();
-        assert_eq!(tables.len(), 1);
-        assert_eq!(tables[0].name, "dummy_table");
-        assert_eq!(tables[0].columns.len(), 2);
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].name, "accounts");
+        assert_eq!(tables[1].name, "users");
+        assert_eq!(tables[1].columns.len(), 2);
+        assert!(tables[1].columns[0].primary_key);
+        assert_eq!(tables[1].columns[1].default_value.as_deref(), Some("'unknown'"));
     }
 
     #[test]
@@ -75,4 +337,108 @@ mod tests {
             panic!("Expected Schema error for empty database path");
         }
     }
+
+    #[test]
+    fn test_catalog_cache_rebuilds_only_on_schema_change() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE a (id INTEGER PRIMARY KEY);").unwrap();
+        let mut cache = CatalogCache::new();
+
+        let first = cache.get(&conn).unwrap();
+        assert_eq!(first.schema.tables.len(), 1);
+
+        // Data changes do not invalidate the catalog
+        conn.execute("INSERT INTO a (id) VALUES (1)", []).unwrap();
+        let second = cache.get(&conn).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(cache.rebuild_count(), 1);
+
+        // Schema changes do
+        conn.execute_batch("CREATE TABLE b (id INTEGER PRIMARY KEY);").unwrap();
+        let third = cache.get(&conn).unwrap();
+        assert_eq!(third.schema.tables.len(), 2);
+        assert!(third.schema_version > first.schema_version);
+        assert_eq!(cache.rebuild_count(), 2);
+
+        cache.invalidate();
+        cache.get(&conn).unwrap();
+        assert_eq!(cache.rebuild_count(), 3);
+    }
+
+    #[test]
+    fn test_catalog_objects() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+             CREATE INDEX idx_users_name ON users(name);
+             CREATE VIEW user_names AS SELECT name FROM users;
+             CREATE TRIGGER users_ai AFTER INSERT ON users BEGIN SELECT 1; END;
+             CREATE VIRTUAL TABLE docs USING fts5(body);",
+        )
+        .unwrap();
+
+        let catalog = CatalogCache::new().get(&conn).unwrap();
+        let names = |kind| catalog.objects_of(kind).map(|o| o.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names(ObjectKind::Table), vec!["users"]);
+        assert_eq!(names(ObjectKind::VirtualTable), vec!["docs"]);
+        assert_eq!(names(ObjectKind::View), vec!["user_names"]);
+        assert_eq!(names(ObjectKind::Trigger), vec!["users_ai"]);
+        assert_eq!(names(ObjectKind::Index), vec!["idx_users_name"]);
+
+        let docs = catalog.objects_of(ObjectKind::VirtualTable).next().unwrap();
+        assert_eq!(docs.module().as_deref(), Some("fts5"));
+        let view = catalog.objects_of(ObjectKind::View).next().unwrap();
+        assert_eq!(view.columns.len(), 1);
+        assert_eq!(view.columns[0].name, "name");
+
+        // Shadow tables such as docs_data are hidden from the schema
+        let mut tables: Vec<_> = catalog.schema.tables.keys().map(|s| s.as_str()).collect();
+        tables.sort();
+        assert_eq!(tables, vec!["docs", "users"]);
+    }
+
+    #[test]
+    fn test_catalog_with_broken_view() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+             CREATE TABLE old (id INTEGER);
+             CREATE VIEW stale AS SELECT id FROM old;
+             DROP TABLE old;",
+        )
+        .unwrap();
+
+        let catalog = CatalogCache::new().get(&conn).unwrap();
+        assert!(catalog.schema.tables.contains_key("users"));
+        let stale = catalog.objects_of(ObjectKind::View).next().unwrap();
+        assert!(stale.columns.is_empty());
+        assert!(stale.error.as_deref().unwrap().contains("no such table"));
+    }
+
+    #[test]
+    fn test_catalog_cache_tracks_attached_databases() {
+        let attached = NamedTempFile::new().unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        let mut cache = CatalogCache::new();
+        cache.get(&conn).unwrap();
+
+        conn.execute(
+            "ATTACH DATABASE ?1 AS aux",
+            [attached.path().to_str().unwrap()],
+        )
+        .unwrap();
+        cache.get(&conn).unwrap();
+        assert_eq!(cache.rebuild_count(), 2);
+
+        // Another connection changing the attached file's schema is picked up
+        let other = Connection::open(attached.path()).unwrap();
+        other.execute_batch("CREATE TABLE remote (id INTEGER);").unwrap();
+        cache.get(&conn).unwrap();
+        assert_eq!(cache.rebuild_count(), 3);
+
+        // Data changes leave the schema, and so the cache, as it was
+        other.execute_batch("INSERT INTO remote VALUES (1);").unwrap();
+        cache.get(&conn).unwrap();
+        assert_eq!(cache.rebuild_count(), 3);
+    }
 }
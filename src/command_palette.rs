@@ -51,7 +51,11 @@ impl CommandPalette {
             },
             Command {
                 name: "plan".to_string(),
-                description: "Visualize the query plan".to_string(),
+                description: "Visualize, compare, export or check query plans for regressions".to_string(),
+            },
+            Command {
+                name: "explain".to_string(),
+                description: "Explain a query's plan or bytecode (--vm)".to_string(),
             },
             Command {
                 name: "fmt".to_string(),
@@ -65,6 +69,10 @@ impl CommandPalette {
                 name: "find".to_string(),
                 description: "Search for text in the database schema or queries".to_string(),
             },
+            Command {
+                name: "tables".to_string(),
+                description: "Browse the schema tree with row estimates and key badges".to_string(),
+            },
             Command {
                 name: "erd".to_string(),
                 description: "Show ER-diagram for the schema".to_string(),
@@ -81,6 +89,50 @@ impl CommandPalette {
                 name: "diff".to_string(),
                 description: "Perform a schema diff between databases".to_string(),
             },
+            Command {
+                name: "schema".to_string(),
+                description: "Schema snapshots history and FK load/delete order".to_string(),
+            },
+            Command {
+                name: "fkcheck".to_string(),
+                description: "Find foreign key violations and generate repair SQL".to_string(),
+            },
+            Command {
+                name: "health".to_string(),
+                description: "Check database integrity, free pages, WAL and statistics".to_string(),
+            },
+            Command {
+                name: "space".to_string(),
+                description: "Show which tables and indexes take up space".to_string(),
+            },
+            Command {
+                name: "profile".to_string(),
+                description: "Per-column statistics of a table".to_string(),
+            },
+            Command {
+                name: "lint".to_string(),
+                description: "Find schema design problems".to_string(),
+            },
+            Command {
+                name: "stats".to_string(),
+                description: "Show statement status counters after each query".to_string(),
+            },
+            Command {
+                name: "analyze".to_string(),
+                description: "Run ANALYZE and view, edit or import planner statistics".to_string(),
+            },
+            Command {
+                name: "advise".to_string(),
+                description: "Propose indexes for a query".to_string(),
+            },
+            Command {
+                name: "bench".to_string(),
+                description: "Benchmark a query and compare with the previous run".to_string(),
+            },
+            Command {
+                name: "migrate".to_string(),
+                description: "Run versioned schema migrations".to_string(),
+            },
             Command {
                 name: "help".to_string(),
                 description: "List all available commands and their descriptions".to_string(),
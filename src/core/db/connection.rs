@@ -1,7 +1,7 @@
-/// Connection Management Module
-///
-/// This module provides database connection management, state handling,
-/// and transaction lifecycle management for TUIQL.
+//! Connection Management Module
+//!
+//! This module provides database connection management, state handling,
+//! and transaction lifecycle management for TUIQL.
 
 use crate::core::{Result, TuiqlError};
 use once_cell::sync::OnceCell;
@@ -1,7 +1,7 @@
-/// Query Execution Module
-///
-/// This module provides functionality for executing SQL queries and formatting results.
-/// It handles the query execution layer, including result processing and data formatting.
+//! Query Execution Module
+//!
+//! This module provides functionality for executing SQL queries and formatting results.
+//! It handles the query execution layer, including result processing and data formatting.
 
 use crate::core::{Result, TuiqlError};
 use rusqlite::{types::ValueRef, Connection};
@@ -368,7 +368,7 @@ impl<'a> QueryExecutor<'a> {
     /// # Errors
     ///
     /// Returns `TuiqlError::Query` if the SQL statement cannot be prepared.
-    pub fn prepare(&self, sql: &str) -> Result<rusqlite::Statement> {
+    pub fn prepare(&self, sql: &str) -> Result<rusqlite::Statement<'_>> {
         self.connection.prepare(sql)
             .map_err(|e| TuiqlError::Query(format!("Failed to prepare statement: {}", e)))
     }
@@ -1,22 +1,124 @@
-/// Schema Introspection Module
-///
-/// This module provides functionality for introspecting database schema,
-/// including tables, columns, indexes, and their relationships.
-/// It handles the metadata layer of database operations.
-
-use crate::core::Result;
-use rusqlite::{Connection, Row};
+//! Schema Introspection Module
+//!
+//! This module provides functionality for introspecting database schema,
+//! including tables, columns, indexes, and their relationships.
+//! It handles the metadata layer of database operations.
+
+use crate::core::{Result, TuiqlError};
+use rusqlite::{Connection, OptionalExtension, Row};
 use std::collections::HashMap;
 
-/// Represents a foreign key relationship
-#[derive(Debug, Clone)]
+/// Action taken on child rows when the referenced parent row is updated or deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FkAction {
+    #[default]
+    NoAction,
+    Restrict,
+    SetNull,
+    SetDefault,
+    Cascade,
+}
+
+impl FkAction {
+    /// Parses the action names reported by `PRAGMA foreign_key_list`
+    pub fn parse(action: &str) -> Self {
+        match action.to_uppercase().as_str() {
+            "RESTRICT" => FkAction::Restrict,
+            "SET NULL" => FkAction::SetNull,
+            "SET DEFAULT" => FkAction::SetDefault,
+            "CASCADE" => FkAction::Cascade,
+            _ => FkAction::NoAction,
+        }
+    }
+
+    /// The action as written in SQL
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            FkAction::NoAction => "NO ACTION",
+            FkAction::Restrict => "RESTRICT",
+            FkAction::SetNull => "SET NULL",
+            FkAction::SetDefault => "SET DEFAULT",
+            FkAction::Cascade => "CASCADE",
+        }
+    }
+}
+
+/// When a foreign key constraint is checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Deferrable {
+    /// Checked at the end of each statement (the default)
+    #[default]
+    NotDeferrable,
+    /// `DEFERRABLE INITIALLY IMMEDIATE`: immediate unless deferred by pragma
+    InitiallyImmediate,
+    /// `DEFERRABLE INITIALLY DEFERRED`: checked at commit
+    InitiallyDeferred,
+}
+
+impl Deferrable {
+    /// The clause as written in SQL, empty for the default
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Deferrable::NotDeferrable => "",
+            Deferrable::InitiallyImmediate => "DEFERRABLE INITIALLY IMMEDIATE",
+            Deferrable::InitiallyDeferred => "DEFERRABLE INITIALLY DEFERRED",
+        }
+    }
+}
+
+/// Represents a foreign key constraint, possibly spanning several columns
+#[derive(Debug, Clone, PartialEq)]
 pub struct ForeignKey {
+    /// Constraint id from `PRAGMA foreign_key_list`
+    pub id: i64,
     /// The table this foreign key references
     pub referenced_table: String,
-    /// The column in this table that is the foreign key
-    pub from_column: String,
-    /// The referenced column in the foreign table
-    pub to_column: String,
+    /// The columns in this table, in constraint order
+    pub from_columns: Vec<String>,
+    /// The referenced columns, resolved to the parent's primary key when omitted
+    pub to_columns: Vec<String>,
+    pub on_update: FkAction,
+    pub on_delete: FkAction,
+    /// MATCH clause (SQLite parses but does not enforce it)
+    pub match_type: String,
+    pub deferrable: Deferrable,
+}
+
+impl ForeignKey {
+    /// Formats the constraint as `(a, b) → parent(x, y)`, without parentheses for single columns
+    pub fn describe(&self) -> String {
+        let columns = |cols: &[String]| {
+            if cols.len() == 1 {
+                cols[0].clone()
+            } else {
+                format!("({})", cols.join(", "))
+            }
+        };
+        format!(
+            "{} → {}({})",
+            columns(&self.from_columns),
+            self.referenced_table,
+            self.to_columns.join(", ")
+        )
+    }
+
+    /// The non-default ON DELETE / ON UPDATE / MATCH / DEFERRABLE clauses
+    pub fn actions(&self) -> String {
+        let mut clauses = Vec::new();
+        if self.on_delete != FkAction::NoAction {
+            clauses.push(format!("ON DELETE {}", self.on_delete.as_sql()));
+        }
+        if self.on_update != FkAction::NoAction {
+            clauses.push(format!("ON UPDATE {}", self.on_update.as_sql()));
+        }
+        if !self.match_type.is_empty() && self.match_type != "NONE" {
+            clauses.push(format!("MATCH {}", self.match_type));
+        }
+        if self.deferrable != Deferrable::NotDeferrable {
+            clauses.push(self.deferrable.as_sql().to_string());
+        }
+        clauses.join(" ")
+    }
 }
 
 /// Represents a database column with its metadata
@@ -54,8 +156,10 @@ pub struct Index {
     pub name: String,
     /// Whether this is a UNIQUE index
     pub unique: bool,
-    /// Column names that make up this index
+    /// Column names that make up this index; expression columns are `<expr>`
     pub columns: Vec<String>,
+    /// Whether this is a partial index with a WHERE clause
+    pub partial: bool,
 }
 
 impl Index {
@@ -64,20 +168,23 @@ impl Index {
         conn: &Connection,
         index_name: String,
         unique: bool,
+        partial: bool,
     ) -> Result<Self> {
         let mut columns = Vec::new();
 
         let mut stmt = conn.prepare(&format!("PRAGMA index_info('{}')", index_name))?;
-        let column_iter = stmt.query_map([], |row| row.get::<_, String>(2))?;
+        // index_info reports no column name for expressions such as lower(email)
+        let column_iter = stmt.query_map([], |row| row.get::<_, Option<String>>(2))?;
 
         for column_result in column_iter {
-            columns.push(column_result?);
+            columns.push(column_result?.unwrap_or_else(|| "<expr>".to_string()));
         }
 
         Ok(Index {
             name: index_name,
             unique,
             columns,
+            partial,
         })
     }
 }
@@ -93,6 +200,8 @@ pub struct Table {
     pub indexes: Vec<Index>,
     /// List of foreign key relationships
     pub foreign_keys: Vec<ForeignKey>,
+    /// The CREATE TABLE statement from sqlite_master
+    pub sql: Option<String>,
 }
 
 impl Table {
@@ -101,14 +210,64 @@ impl Table {
         let columns = get_table_columns(conn, table_name)?;
         let indexes = get_table_indexes(conn, table_name)?;
         let foreign_keys = get_table_foreign_keys(conn, table_name)?;
+        let sql = conn
+            .query_row(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                [table_name],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
 
         Ok(Table {
             name: table_name.to_string(),
             columns,
             indexes,
             foreign_keys,
+            sql,
         })
     }
+
+    /// Upper-cased table options that follow the column list, such as STRICT or WITHOUT ROWID
+    fn table_options(&self) -> Vec<String> {
+        let Some(sql) = &self.sql else {
+            return Vec::new();
+        };
+        let tokens = tokenize_sql(sql);
+        let mut depth = 0;
+        let mut end = None;
+        for (i, token) in tokens.iter().enumerate() {
+            match token.as_str() {
+                "(" => depth += 1,
+                ")" => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(i);
+                    }
+                }
+                _ => {}
+            }
+        }
+        end.map(|end| tokens[end + 1..].iter().map(|t| t.to_uppercase()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the table was declared STRICT
+    pub fn is_strict(&self) -> bool {
+        self.table_options().iter().any(|t| t == "STRICT")
+    }
+
+    /// Whether the table was declared WITHOUT ROWID
+    pub fn is_without_rowid(&self) -> bool {
+        self.table_options().windows(2).any(|w| w[0] == "WITHOUT" && w[1] == "ROWID")
+    }
+
+    /// Whether the primary key was declared AUTOINCREMENT
+    pub fn has_autoincrement(&self) -> bool {
+        self.sql
+            .as_deref()
+            .is_some_and(|sql| tokenize_sql(sql).iter().any(|t| t.eq_ignore_ascii_case("AUTOINCREMENT")))
+    }
 }
 
 /// Comprehensive schema information for a database
@@ -124,15 +283,108 @@ impl Schema {
         let tables = get_all_tables(conn)?;
         Ok(Schema { tables })
     }
+
+    /// Creates a Schema by replaying DDL (as produced by `schema_ddl`) into an in-memory database
+    pub fn from_ddl(ddl: &str) -> Result<Self> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch(ddl)
+            .map_err(|e| TuiqlError::Schema(format!("Failed to replay schema DDL: {}", e)))?;
+        Self::from_connection(&conn)
+    }
+}
+
+/// SQLite column affinity, derived from a declared type name
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Affinity {
+    Integer,
+    Text,
+    Blob,
+    Real,
+    Numeric,
+}
+
+impl Affinity {
+    /// Applies the affinity rules from the SQLite documentation (section 3.1 of "Datatypes")
+    pub fn of(type_name: &str) -> Self {
+        let type_name = type_name.to_uppercase();
+        if type_name.contains("INT") {
+            Affinity::Integer
+        } else if type_name.contains("CHAR") || type_name.contains("CLOB") || type_name.contains("TEXT") {
+            Affinity::Text
+        } else if type_name.contains("BLOB") || type_name.is_empty() {
+            Affinity::Blob
+        } else if type_name.contains("REAL") || type_name.contains("FLOA") || type_name.contains("DOUB") {
+            Affinity::Real
+        } else {
+            Affinity::Numeric
+        }
+    }
+
+    /// Lowercase affinity name as used in the SQLite documentation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Affinity::Integer => "integer",
+            Affinity::Text => "text",
+            Affinity::Blob => "blob",
+            Affinity::Real => "real",
+            Affinity::Numeric => "numeric",
+        }
+    }
+}
+
+/// Quotes an identifier for use in generated SQL, doubling embedded quotes
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Returns `PRAGMA schema_version`, which SQLite increments on every schema change
+pub fn schema_version(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row("PRAGMA schema_version", [], |row| row.get(0))?)
+}
+
+/// Returns the DDL of all user-defined schema objects as a replayable script
+///
+/// Tables come first, then indexes, views and triggers, each group ordered by name.
+/// Internal `sqlite_%` objects and virtual table shadow tables are left out, since
+/// SQLite recreates them itself.
+pub fn schema_ddl(conn: &Connection) -> Result<String> {
+    let mut stmt = conn.prepare(
+        "SELECT sql FROM sqlite_master
+         WHERE sql IS NOT NULL
+           AND name NOT LIKE 'sqlite_%'
+           AND name NOT IN (SELECT name FROM pragma_table_list WHERE type = 'shadow')
+         ORDER BY CASE type WHEN 'table' THEN 0 WHEN 'index' THEN 1 WHEN 'view' THEN 2 ELSE 3 END, name",
+    )?;
+    let statements = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+    let mut ddl = String::new();
+    for statement in statements {
+        ddl.push_str(&statement?);
+        ddl.push_str(";\n");
+    }
+    Ok(ddl)
+}
+
+/// Computes a short fingerprint of a DDL script (64-bit FNV-1a, as hex)
+pub fn ddl_fingerprint(ddl: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in ddl.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
 }
 
 /// Helper function to retrieve all user-defined tables from the database
+///
+/// Virtual table shadow tables are left out, like in `schema_ddl`.
 fn get_all_tables(conn: &Connection) -> Result<HashMap<String, Table>> {
     let mut tables = HashMap::new();
 
     let mut stmt = conn.prepare(
         "SELECT name FROM sqlite_master
-         WHERE type='table' AND name NOT LIKE 'sqlite_%'"
+         WHERE type='table' AND name NOT LIKE 'sqlite_%'
+           AND name NOT IN (SELECT name FROM pragma_table_list WHERE type = 'shadow')"
     )?;
 
     let table_iter = stmt.query_map([], |row| row.get::<_, String>(0))?;
@@ -171,42 +423,227 @@ fn get_table_indexes(conn: &Connection, table_name: &str) -> Result<Vec<Index>>
         Ok((
             row.get::<_, String>(1)?, // index name
             row.get::<_, bool>(2)?,   // unique
+            row.get::<_, bool>(4)?,   // partial
         ))
     })?;
 
     for index_result in index_iter {
-        let (index_name, unique) = index_result?;
-        indexes.push(Index::from_pragma_info(conn, index_name, unique)?);
+        let (index_name, unique, partial) = index_result?;
+        indexes.push(Index::from_pragma_info(conn, index_name, unique, partial)?);
     }
 
     Ok(indexes)
 }
 
 /// Helper function to retrieve foreign key information for a specific table
+///
+/// Rows of `PRAGMA foreign_key_list` are grouped by `id` and ordered by `seq`, so
+/// multi-column constraints become one `ForeignKey`. The pragma does not report
+/// deferrability or the MATCH clause, so those are read from the table's SQL.
 fn get_table_foreign_keys(conn: &Connection, table_name: &str) -> Result<Vec<ForeignKey>> {
-    let mut foreign_keys = Vec::new();
+    let mut foreign_keys: Vec<ForeignKey> = Vec::new();
 
     let mut stmt = conn.prepare(&format!("PRAGMA foreign_key_list('{}')", table_name))?;
     let fk_iter = stmt.query_map([], |row| {
         Ok((
-            row.get::<_, String>(2)?, // referenced table
-            row.get::<_, String>(3)?, // from column
-            row.get::<_, String>(4)?, // to column
+            row.get::<_, i64>(0)?,            // id
+            row.get::<_, i64>(1)?,            // seq
+            row.get::<_, String>(2)?,         // referenced table
+            row.get::<_, String>(3)?,         // from column
+            row.get::<_, Option<String>>(4)?, // to column, NULL for the parent's primary key
+            row.get::<_, String>(5)?,         // on update
+            row.get::<_, String>(6)?,         // on delete
+            row.get::<_, String>(7)?,         // match
         ))
     })?;
 
-    for fk_result in fk_iter {
-        let (referenced_table, from_column, to_column) = fk_result?;
-        foreign_keys.push(ForeignKey {
-            referenced_table,
-            from_column,
-            to_column,
-        });
+    let mut rows = fk_iter.collect::<rusqlite::Result<Vec<_>>>()?;
+    rows.sort_by_key(|row| (row.0, row.1));
+
+    for (id, _seq, referenced_table, from_column, to_column, on_update, on_delete, match_type) in rows {
+        match foreign_keys.last_mut() {
+            Some(fk) if fk.id == id => {
+                fk.from_columns.push(from_column);
+                fk.to_columns.push(to_column.unwrap_or_default());
+            }
+            _ => foreign_keys.push(ForeignKey {
+                id,
+                referenced_table,
+                from_columns: vec![from_column],
+                to_columns: vec![to_column.unwrap_or_default()],
+                on_update: FkAction::parse(&on_update),
+                on_delete: FkAction::parse(&on_delete),
+                match_type,
+                deferrable: Deferrable::NotDeferrable,
+            }),
+        }
+    }
+
+    // Implicit references target the parent's primary key
+    for fk in &mut foreign_keys {
+        if fk.to_columns.iter().any(|c| c.is_empty()) {
+            let parent_pk = primary_key_columns(conn, &fk.referenced_table)?;
+            if parent_pk.len() == fk.to_columns.len() {
+                fk.to_columns = parent_pk;
+            }
+        }
+    }
+
+    // SQLite numbers constraints in reverse declaration order
+    let sql: Option<String> = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [table_name],
+            |row| row.get(0),
+        )
+        .unwrap_or(None);
+    if let Some(sql) = sql {
+        let clauses = parse_references_clauses(&sql);
+        if clauses.len() == foreign_keys.len() {
+            for (fk, clause) in foreign_keys.iter_mut().zip(clauses.iter().rev()) {
+                if !clause.table.eq_ignore_ascii_case(&fk.referenced_table) {
+                    continue;
+                }
+                fk.deferrable = clause.deferrable;
+                if let Some(match_type) = &clause.match_type {
+                    fk.match_type = match_type.clone();
+                }
+            }
+        }
     }
 
     Ok(foreign_keys)
 }
 
+/// Returns the primary key columns of a table in key order
+fn primary_key_columns(conn: &Connection, table_name: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?1) WHERE pk > 0 ORDER BY pk")?;
+    let columns = stmt
+        .query_map([table_name], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(columns)
+}
+
+/// The parts of a `REFERENCES` clause that `PRAGMA foreign_key_list` does not report
+#[derive(Debug, PartialEq)]
+struct ReferencesClause {
+    table: String,
+    match_type: Option<String>,
+    deferrable: Deferrable,
+}
+
+/// Finds every `REFERENCES` clause of a CREATE TABLE statement, in declaration order
+fn parse_references_clauses(sql: &str) -> Vec<ReferencesClause> {
+    let tokens = tokenize_sql(sql);
+    let mut clauses = Vec::new();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        if !tokens[i].eq_ignore_ascii_case("references") || i + 1 >= tokens.len() {
+            i += 1;
+            continue;
+        }
+        let mut clause = ReferencesClause {
+            table: unquote_identifier(&tokens[i + 1]),
+            match_type: None,
+            deferrable: Deferrable::NotDeferrable,
+        };
+
+        // The clause ends at a comma or closing parenthesis outside its column list
+        let mut depth = 0i32;
+        let mut j = i + 2;
+        while j < tokens.len() {
+            let token = tokens[j].to_uppercase();
+            match token.as_str() {
+                "(" => depth += 1,
+                ")" if depth == 0 => break,
+                ")" => depth -= 1,
+                "," if depth == 0 => break,
+                "REFERENCES" => break,
+                "MATCH" if j + 1 < tokens.len() => clause.match_type = Some(tokens[j + 1].to_uppercase()),
+                "DEFERRABLE" => {
+                    let negated = tokens[j - 1].eq_ignore_ascii_case("not");
+                    let deferred = tokens.get(j + 1).is_some_and(|t| t.eq_ignore_ascii_case("initially"))
+                        && tokens.get(j + 2).is_some_and(|t| t.eq_ignore_ascii_case("deferred"));
+                    clause.deferrable = match (negated, deferred) {
+                        (true, _) => Deferrable::NotDeferrable,
+                        (false, true) => Deferrable::InitiallyDeferred,
+                        (false, false) => Deferrable::InitiallyImmediate,
+                    };
+                }
+                _ => {}
+            }
+            j += 1;
+        }
+        clauses.push(clause);
+        i = j;
+    }
+    clauses
+}
+
+/// Splits SQL into identifier/keyword, quoted and punctuation tokens, dropping comments
+fn tokenize_sql(sql: &str) -> Vec<String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if matches!(c, '"' | '`' | '\'' | '[') {
+            let close = if c == '[' { ']' } else { c };
+            let start = i;
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == close {
+                    // Doubled quotes escape themselves
+                    if close != ']' && chars.get(i + 1) == Some(&close) {
+                        i += 2;
+                        continue;
+                    }
+                    break;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+            tokens.push(chars[start..i].iter().collect());
+        } else if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else {
+            tokens.push(c.to_string());
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// Removes SQL identifier quoting (`"x"`, `` `x` ``, `[x]`, `'x'`)
+fn unquote_identifier(token: &str) -> String {
+    let mut chars = token.chars();
+    match (chars.next(), token.chars().last()) {
+        (Some(open @ ('"' | '`' | '\'')), Some(close)) if open == close && token.len() >= 2 => {
+            let inner = &token[1..token.len() - 1];
+            inner.replace(&format!("{}{}", open, open), &open.to_string())
+        }
+        (Some('['), Some(']')) => token[1..token.len() - 1].to_string(),
+        _ => token.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -308,4 +745,123 @@ mod tests {
         assert!(!columns[3].pk);
         assert!(!columns[3].notnull);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_schema_ddl_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        setup_test_schema(&conn).unwrap();
+        conn.execute_batch(
+            "CREATE VIEW adults AS SELECT * FROM users WHERE age >= 18;
+             CREATE TRIGGER posts_ai AFTER INSERT ON posts BEGIN SELECT 1; END;
+             CREATE VIRTUAL TABLE docs USING fts5(body);",
+        )
+        .unwrap();
+
+        let ddl = schema_ddl(&conn).unwrap();
+        assert!(ddl.starts_with("CREATE VIRTUAL TABLE docs"));
+        assert!(ddl.find("CREATE TABLE users").unwrap() < ddl.find("CREATE INDEX").unwrap());
+        assert!(ddl.contains("CREATE INDEX idx_users_age"));
+        assert!(ddl.contains("CREATE TRIGGER posts_ai"));
+        assert!(!ddl.contains("docs_data"), "shadow tables are recreated by the virtual table");
+
+        let replayed = Schema::from_ddl(&ddl).unwrap();
+        let original = Schema::from_connection(&conn).unwrap();
+        assert!(!original.tables.contains_key("docs_data"));
+        let mut replayed_tables: Vec<_> = replayed.tables.keys().collect();
+        let mut original_tables: Vec<_> = original.tables.keys().collect();
+        replayed_tables.sort();
+        original_tables.sort();
+        assert_eq!(replayed_tables, original_tables);
+        assert_eq!(replayed.tables["posts"].foreign_keys.len(), 1);
+
+        // Replaying the same DDL yields the same script and fingerprint
+        let replay_conn = Connection::open_in_memory().unwrap();
+        replay_conn.execute_batch(&ddl).unwrap();
+        assert_eq!(ddl_fingerprint(&schema_ddl(&replay_conn).unwrap()), ddl_fingerprint(&ddl));
+    }
+
+    #[test]
+    fn test_expression_index_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        setup_test_schema(&conn).unwrap();
+        conn.execute_batch("CREATE INDEX idx_users_lower_name ON users(lower(name), age);").unwrap();
+
+        let schema = Schema::from_connection(&conn).unwrap();
+        let index = schema.tables["users"]
+            .indexes
+            .iter()
+            .find(|i| i.name == "idx_users_lower_name")
+            .unwrap();
+        assert_eq!(index.columns, vec!["<expr>", "age"]);
+    }
+
+    #[test]
+    fn test_schema_version_changes_with_ddl() {
+        let conn = Connection::open_in_memory().unwrap();
+        let before = schema_version(&conn).unwrap();
+        setup_test_schema(&conn).unwrap();
+        assert!(schema_version(&conn).unwrap() > before);
+    }
+
+    #[test]
+    fn test_from_ddl_rejects_invalid_sql() {
+        assert!(matches!(Schema::from_ddl("CREATE TABLE ("), Err(TuiqlError::Schema(_))));
+    }
+
+    #[test]
+    fn test_composite_and_action_aware_foreign_keys() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE parents (id INTEGER PRIMARY KEY, a TEXT, b TEXT, UNIQUE (a, b));
+             CREATE TABLE kids (
+                 id INTEGER PRIMARY KEY,
+                 parent_id INTEGER REFERENCES parents(id) ON DELETE CASCADE DEFERRABLE INITIALLY DEFERRED,
+                 owner_id INTEGER REFERENCES parents,
+                 a TEXT,
+                 b TEXT,
+                 CONSTRAINT fk_ab FOREIGN KEY (a, b) REFERENCES \"parents\" (a, b)
+                     ON UPDATE SET NULL MATCH FULL DEFERRABLE
+             );",
+        )
+        .unwrap();
+
+        let schema = Schema::from_connection(&conn).unwrap();
+        let fks = &schema.tables["kids"].foreign_keys;
+        assert_eq!(fks.len(), 3);
+
+        let composite = fks.iter().find(|fk| fk.from_columns.len() == 2).unwrap();
+        assert_eq!(composite.from_columns, vec!["a", "b"]);
+        assert_eq!(composite.to_columns, vec!["a", "b"]);
+        assert_eq!(composite.on_update, FkAction::SetNull);
+        assert_eq!(composite.on_delete, FkAction::NoAction);
+        assert_eq!(composite.match_type, "FULL");
+        assert_eq!(composite.deferrable, Deferrable::InitiallyImmediate);
+        assert_eq!(composite.describe(), "(a, b) → parents(a, b)");
+        assert_eq!(
+            composite.actions(),
+            "ON UPDATE SET NULL MATCH FULL DEFERRABLE INITIALLY IMMEDIATE"
+        );
+
+        let cascade = fks.iter().find(|fk| fk.from_columns == ["parent_id"]).unwrap();
+        assert_eq!(cascade.on_delete, FkAction::Cascade);
+        assert_eq!(cascade.deferrable, Deferrable::InitiallyDeferred);
+        assert_eq!(cascade.describe(), "parent_id → parents(id)");
+
+        // An implicit reference resolves to the parent's primary key
+        let implicit = fks.iter().find(|fk| fk.from_columns == ["owner_id"]).unwrap();
+        assert_eq!(implicit.to_columns, vec!["id"]);
+        assert_eq!(implicit.actions(), "");
+    }
+
+    #[test]
+    fn test_parse_references_clauses() {
+        let clauses = parse_references_clauses(
+            "CREATE TABLE t (x REFERENCES [p q](id) NOT DEFERRABLE, -- references nothing
+                             y REFERENCES `r`, FOREIGN KEY (z) REFERENCES s(id) MATCH SIMPLE)",
+        );
+        let tables: Vec<&str> = clauses.iter().map(|c| c.table.as_str()).collect();
+        assert_eq!(tables, vec!["p q", "r", "s"]);
+        assert_eq!(clauses[0].deferrable, Deferrable::NotDeferrable);
+        assert_eq!(clauses[2].match_type.as_deref(), Some("SIMPLE"));
+    }
+}
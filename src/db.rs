@@ -1,9 +1,12 @@
 use crate::core::{Result, TuiqlError};
 use once_cell::sync::OnceCell;
-use rusqlite::{types::ValueRef, Connection};
+use rusqlite::{types::ValueRef, Connection, OpenFlags, Statement, StatementStatus};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
 pub struct Column {
@@ -61,6 +64,97 @@ pub struct QueryResult {
     pub columns: Vec<String>,
     pub rows: Vec<Vec<String>>,
     pub row_count: usize,
+    /// Status counters of the executed statement
+    pub stats: Option<StatementStats>,
+}
+
+/// Counters from `sqlite3_stmt_status` for one executed statement
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatementStats {
+    /// Steps taken in full table scans
+    pub fullscan_steps: i32,
+    /// Sort operations
+    pub sorts: i32,
+    /// Rows inserted into automatic indexes built for this statement
+    pub auto_indexes: i32,
+    /// Virtual machine operations executed
+    pub vm_steps: i32,
+    /// Times the statement was re-prepared after a schema change
+    pub reprepares: i32,
+    /// Heap bytes used by the prepared statement
+    pub memory_used: i32,
+}
+
+impl StatementStats {
+    /// Reads the counters of a statement that has finished running
+    pub fn from_statement(stmt: &Statement) -> Self {
+        StatementStats {
+            fullscan_steps: stmt.get_status(StatementStatus::FullscanStep),
+            sorts: stmt.get_status(StatementStatus::Sort),
+            auto_indexes: stmt.get_status(StatementStatus::AutoIndex),
+            vm_steps: stmt.get_status(StatementStatus::VmStep),
+            reprepares: stmt.get_status(StatementStatus::RePrepare),
+            memory_used: stmt.get_status(StatementStatus::MemUsed),
+        }
+    }
+
+    /// One-line summary of all counters
+    pub fn summary(&self) -> String {
+        format!(
+            "📈 fullscan steps {} · sorts {} · auto-index rows {} · VM steps {} · reprepares {} · memory {} bytes",
+            self.fullscan_steps, self.sorts, self.auto_indexes, self.vm_steps, self.reprepares, self.memory_used
+        )
+    }
+
+    /// Problems worth pointing out without running `:plan`
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.auto_indexes > 0 {
+            warnings.push(format!(
+                "This query built an automatic index ({} rows); a permanent index would avoid rebuilding it on every run",
+                self.auto_indexes
+            ));
+        }
+        if self.fullscan_steps > 0 {
+            warnings.push(format!("This query stepped through {} rows in full table scans", self.fullscan_steps));
+        }
+        if self.reprepares > 0 {
+            warnings.push("The statement was re-prepared because the schema changed".to_string());
+        }
+        warnings
+    }
+}
+
+/// Virtual machine instructions between two calls of the progress handler
+pub const PROGRESS_INTERVAL_OPS: i32 = 1000;
+
+/// How long a query runs before its progress is reported
+pub const PROGRESS_DELAY: Duration = Duration::from_millis(300);
+
+/// Minimum time between two progress reports
+const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Progress of a query that is still running
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QueryProgress {
+    pub elapsed: Duration,
+    /// Virtual machine instructions executed so far, counted in steps of
+    /// `PROGRESS_INTERVAL_OPS`
+    pub vm_steps: u64,
+    /// Rows produced so far
+    pub rows: u64,
+}
+
+impl QueryProgress {
+    /// One-line status shown by the REPL while a query runs
+    pub fn status_line(&self) -> String {
+        format!(
+            "⏳ {:.1}s · {} VM steps · {} rows · Press Ctrl+C to cancel",
+            self.elapsed.as_secs_f64(),
+            self.vm_steps,
+            self.rows
+        )
+    }
 }
 
 impl QueryResult {
@@ -70,11 +164,12 @@ impl QueryResult {
             columns,
             rows,
             row_count,
+            stats: None,
         }
     }
 }
 
-fn format_value(value: ValueRef) -> String {
+pub(crate) fn format_value(value: ValueRef) -> String {
     match value {
         ValueRef::Null => "NULL".to_string(),
         ValueRef::Integer(i) => i.to_string(),
@@ -95,27 +190,41 @@ pub fn connect(db_path: &str) -> Result<()> {
                 PRAGMA journal_mode = WAL;
             ").map_err(|e| TuiqlError::Query(format!("Failed to set initial PRAGMA settings: {}", e)))?;
 
-            // Store the connection in our global state
-            DB_STATE.get_or_init(|| {
-                Mutex::new(DbState {
-                    connection: None,
-                    current_path: None,
-                    transaction_state: TransactionState::default(),
-                })
-            });
-
-            if let Ok(mut guard) = DB_STATE.get().unwrap().lock() {
-                guard.connection = Some(conn);
-                guard.current_path = Some(db_path.to_string());
-                Ok(())
-            } else {
-                Err(TuiqlError::App("Failed to acquire connection lock. Global database state is corrupted or locked by another process.".to_string()))
-            }
+            set_connection(conn, db_path)
         }
         Err(e) => Err(TuiqlError::App(format!("Failed to connect to database '{}': {}. Ensure the path exists and the database file is accessible.", db_path, e))),
     }
 }
 
+/// Opens an existing database read-only, for commands that must not modify their input.
+///
+/// Unlike `connect`, a missing file is an error and the journal mode is left as it is.
+pub fn connect_read_only(db_path: &str) -> Result<()> {
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map_err(|e| TuiqlError::App(format!("Failed to open database '{}' read-only: {}", db_path, e)))?;
+    set_connection(conn, db_path)
+}
+
+/// Makes `conn` the current connection in the global state
+fn set_connection(conn: Connection, db_path: &str) -> Result<()> {
+    DB_STATE.get_or_init(|| {
+        Mutex::new(DbState {
+            connection: None,
+            current_path: None,
+            transaction_state: TransactionState::default(),
+        })
+    });
+
+    if let Ok(mut guard) = DB_STATE.get().unwrap().lock() {
+        guard.connection = Some(conn);
+        guard.current_path = Some(db_path.to_string());
+        crate::catalog::invalidate();
+        Ok(())
+    } else {
+        Err(TuiqlError::App("Failed to acquire connection lock. Global database state is corrupted or locked by another process.".to_string()))
+    }
+}
+
 /// Executes a SQL query and returns the results.
 pub fn execute_query(sql: &str) -> Result<QueryResult> {
     let state_cell = DB_STATE.get().ok_or(TuiqlError::App("No database connection found. Please connect to a database first.".to_string()))?;
@@ -277,10 +386,15 @@ pub fn get_schema() -> Result<Schema> {
 /// for cancellation signals (such as Ctrl+C). The callback is responsible for calling
 /// the interrupt handle when cancellation is requested.
 ///
+/// While the query runs, a progress handler reports its elapsed time, VM steps and
+/// rows produced so far to `progress_reporter`. Reports start once the query has run
+/// for `PROGRESS_DELAY`, so quick queries are never reported.
+///
 /// # Arguments
 ///
 /// * `sql` - The SQL query to execute
 /// * `cancellation_monitor` - A callback that monitors for cancellation and calls interrupt
+/// * `progress_reporter` - A callback that receives the progress of a long-running query
 ///
 /// # Returns
 ///
@@ -290,9 +404,40 @@ pub fn get_schema() -> Result<Schema> {
 ///
 /// Returns `TuiqlError::Query` if the query fails.
 /// Returns a specific error message if the query was interrupted.
-pub fn execute_cancellable_query<F>(sql: &str, cancellation_monitor: F) -> Result<QueryResult>
+pub fn execute_cancellable_query<F, P>(sql: &str, cancellation_monitor: F, progress_reporter: P) -> Result<QueryResult>
 where
     F: FnOnce(rusqlite::InterruptHandle) + Send + 'static,
+    P: FnMut(&QueryProgress) + Send + 'static,
+{
+    // Execute the query using the local function - this may be interrupted mid-execution
+    let result = with_cancellable_connection(cancellation_monitor, progress_reporter, |conn, rows_produced| {
+        execute_query_on_connection_local(conn, sql, rows_produced).map_err(TuiqlError::Database)
+    });
+
+    match result {
+        Err(TuiqlError::Database(rusqlite_err)) => {
+            // Check if this is an interrupt error by examining the error message
+            let error_str = rusqlite_err.to_string();
+            if error_str.contains("interrupt") || error_str.contains("cancel") {
+                Err(TuiqlError::Query("Query execution cancelled by user (Ctrl+C)".to_string()))
+            } else {
+                Err(TuiqlError::Database(rusqlite_err))
+            }
+        }
+        other => other,
+    }
+}
+
+/// Runs `run` on the current connection with cancellation and progress reporting.
+///
+/// The cancellation monitor gets the connection's interrupt handle on a separate
+/// thread, and a progress handler reports to `progress_reporter` while `run` executes.
+/// `run` receives the counter it should add the rows it produces to.
+pub fn with_cancellable_connection<T, F, P, R>(cancellation_monitor: F, progress_reporter: P, run: R) -> Result<T>
+where
+    F: FnOnce(rusqlite::InterruptHandle) + Send + 'static,
+    P: FnMut(&QueryProgress) + Send + 'static,
+    R: FnOnce(&Connection, &AtomicU64) -> Result<T>,
 {
     let state_cell = DB_STATE.get().ok_or(TuiqlError::Query("No database connection found. Please connect to a database first.".to_string()))?;
     let state_guard = state_cell.lock().map_err(|_| TuiqlError::Query("Failed to acquire database lock".to_string()))?;
@@ -305,27 +450,72 @@ where
         cancellation_monitor(interrupt_handle);
     });
 
-    // Execute the query using the local function - this may be interrupted mid-execution
-    match execute_query_on_connection_local(conn, sql) {
-        Ok(query_result) => Ok(query_result),
-        Err(rusqlite_err) => {
-            // Check if this is an interrupt error by examining the error message
-            let error_str = rusqlite_err.to_string();
-            if error_str.contains("interrupt") || error_str.contains("cancel") {
-                Err(TuiqlError::Query("Query execution cancelled by user (Ctrl+C)".to_string()))
-            } else {
-                Err(TuiqlError::Database(rusqlite_err))
-            }
-        }
+    let rows_produced = Arc::new(AtomicU64::new(0));
+    install_progress_handler(conn, rows_produced.clone(), PROGRESS_DELAY, progress_reporter);
+    let result = run(conn, &rows_produced);
+    remove_progress_handler(conn);
+    result
+}
+
+/// Whether an error comes from a statement stopped through the connection's interrupt handle
+pub fn is_interrupted(error: &TuiqlError) -> bool {
+    match error {
+        TuiqlError::Database(rusqlite::Error::SqliteFailure(err, _)) => err.code == rusqlite::ErrorCode::OperationInterrupted,
+        // Statements stepped through the raw C API report sqlite3_errmsg()
+        TuiqlError::Query(message) => message == "interrupted",
+        _ => false,
     }
 }
 
+/// Installs a progress handler that reports to `reporter` once a query has run for `delay`.
+///
+/// `rows_produced` is read at each report, so the caller should count rows into it.
+fn install_progress_handler<P>(conn: &Connection, rows_produced: Arc<AtomicU64>, delay: Duration, reporter: P)
+where
+    P: FnMut(&QueryProgress) + Send + 'static,
+{
+    let start = Instant::now();
+    let mut last_report: Option<Instant> = None;
+    let mut calls: u64 = 0;
+    // The reporter only prints, so a panic in it cannot leave shared state half-updated
+    let mut reporter = std::panic::AssertUnwindSafe(reporter);
+    conn.progress_handler(
+        PROGRESS_INTERVAL_OPS,
+        Some(move || {
+            calls += 1;
+            let elapsed = start.elapsed();
+            let due = last_report.map_or(elapsed >= delay, |last| last.elapsed() >= PROGRESS_REPORT_INTERVAL);
+            if due {
+                last_report = Some(Instant::now());
+                // Borrow the wrapper as a whole so the closure captures it rather than the reporter
+                let reporter = &mut reporter;
+                (reporter.0)(&QueryProgress {
+                    elapsed,
+                    vm_steps: calls * PROGRESS_INTERVAL_OPS as u64,
+                    rows: rows_produced.load(Ordering::Relaxed),
+                });
+            }
+            // Cancellation goes through the interrupt handle, never through the handler
+            false
+        }),
+    );
+}
+
+/// Removes the progress handler so later statements run without it
+fn remove_progress_handler(conn: &Connection) {
+    conn.progress_handler(0, None::<fn() -> bool>);
+}
+
 /// Local helper function to execute a query on a connection
 ///
 /// This function returns both normal errors and interrupt errors that can be differentiated.
 /// Returns a Result containing either a QueryResult or a raw rusqlite::Error to allow
 /// proper pattern matching on interrupt conditions.
-fn execute_query_on_connection_local(conn: &Connection, sql: &str) -> std::result::Result<QueryResult, rusqlite::Error> {
+fn execute_query_on_connection_local(
+    conn: &Connection,
+    sql: &str,
+    rows_produced: &AtomicU64,
+) -> std::result::Result<QueryResult, rusqlite::Error> {
     let mut stmt = conn.prepare(sql)?;
 
     // Get column names
@@ -339,12 +529,15 @@ fn execute_query_on_connection_local(conn: &Connection, sql: &str) -> std::resul
             for i in 0..column_count {
                 values.push(format_value(row.get_ref(i)?));
             }
+            rows_produced.fetch_add(1, Ordering::Relaxed);
             Ok(values)
         })?
         .filter_map(|row| row.ok()) // Ignore processing errors
         .collect();
 
-    Ok(QueryResult::new(columns, rows))
+    let mut result = QueryResult::new(columns, rows);
+    result.stats = Some(StatementStats::from_statement(&stmt));
+    Ok(result)
 }
 
 #[cfg(test)]
@@ -623,6 +816,59 @@ pub(crate) mod tests {
         assert_eq!(value_idx.columns, vec!["value"]);
     }
 
+    #[test]
+    fn test_statement_stats() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE a (x INTEGER); CREATE TABLE b (x INTEGER);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100)
+             INSERT INTO a SELECT i FROM n;
+             INSERT INTO b SELECT x FROM a;",
+        )
+        .unwrap();
+
+        let result = execute_query_on_connection_local(&conn, "SELECT * FROM a JOIN b ON a.x = b.x ORDER BY a.x DESC", &AtomicU64::new(0)).unwrap();
+        let stats = result.stats.unwrap();
+        assert!(stats.auto_indexes > 0);
+        assert!(stats.fullscan_steps > 0);
+        assert_eq!(stats.sorts, 1);
+        assert!(stats.vm_steps > 0);
+        assert!(stats.memory_used > 0);
+        assert!(stats.warnings()[0].contains("automatic index"));
+        assert!(stats.summary().contains("sorts 1"));
+
+        let simple = execute_query_on_connection_local(&conn, "SELECT 1", &AtomicU64::new(0)).unwrap().stats.unwrap();
+        assert!(simple.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_progress_handler() {
+        let conn = Connection::open_in_memory().unwrap();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let reports_clone = reports.clone();
+        let rows_produced = Arc::new(AtomicU64::new(0));
+        install_progress_handler(&conn, rows_produced.clone(), Duration::ZERO, move |progress| {
+            reports_clone.lock().unwrap().push(*progress);
+        });
+
+        let sql = "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200000)
+                   SELECT i FROM n WHERE i % 1000 = 0";
+        let result = execute_query_on_connection_local(&conn, sql, &rows_produced).unwrap();
+        assert_eq!(result.row_count, 200);
+        assert_eq!(rows_produced.load(Ordering::Relaxed), 200);
+
+        let reported = reports.lock().unwrap().clone();
+        assert!(!reported.is_empty());
+        assert!(reported[0].vm_steps >= PROGRESS_INTERVAL_OPS as u64);
+        assert!(reported.windows(2).all(|pair| pair[0].vm_steps < pair[1].vm_steps && pair[0].rows <= pair[1].rows));
+        assert!(reported[0].status_line().contains("Press Ctrl+C to cancel"));
+
+        // Nothing is reported once the handler is removed
+        remove_progress_handler(&conn);
+        execute_query_on_connection_local(&conn, sql, &AtomicU64::new(0)).unwrap();
+        assert_eq!(reports.lock().unwrap().len(), reported.len());
+    }
+
     #[test]
     fn test_cancellable_query_execution_normal() {
         setup_test_db_global();
@@ -632,7 +878,7 @@ pub(crate) mod tests {
 
         let result = execute_cancellable_query(sql, |_| {
             // No-op cancellation monitor
-        });
+        }, |_| {});
 
         assert!(result.is_ok());
         let query_result = result.unwrap();
@@ -655,7 +901,7 @@ pub(crate) mod tests {
                 thread::sleep(Duration::from_millis(10));
                 *interrupt_called_clone.lock().unwrap() = true;
             });
-        });
+        }, |_| {});
 
         assert!(result.is_ok());
         let query_result = result.unwrap();
@@ -677,7 +923,7 @@ pub(crate) mod tests {
                 // Call interrupt - this may or may not affect the already executing query
                 interrupt_handle.interrupt();
             });
-        });
+        }, |_| {});
 
         // Result could be OK or Err depending on timing and whether interrupt actually triggered
         // The important thing is that it doesn't crash and returns a valid result
@@ -693,7 +939,7 @@ pub(crate) mod tests {
 
         let result = execute_cancellable_query(sql, |_| {
             // No-op cancellation monitor
-        });
+        }, |_| {});
 
         assert!(result.is_err());
         match result.unwrap_err() {
@@ -703,6 +949,27 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    fn test_is_interrupted() {
+        let conn = Connection::open_in_memory().unwrap();
+        // A progress handler that returns true interrupts the statement like the interrupt handle
+        conn.progress_handler(100, Some(|| true));
+        let err = conn
+            .query_row("WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n) SELECT COUNT(*) FROM n", [], |row| row.get::<_, i64>(0))
+            .unwrap_err();
+        assert!(is_interrupted(&TuiqlError::Database(err)));
+        assert!(is_interrupted(&TuiqlError::Query("interrupted".to_string())));
+        assert!(!is_interrupted(&TuiqlError::Query("no such table: t".to_string())));
+    }
+
+    #[test]
+    fn test_connect_read_only_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("typo.db");
+        assert!(connect_read_only(path.to_str().unwrap()).is_err());
+        assert!(std::fs::read_dir(dir.path()).unwrap().next().is_none(), "no files are created");
+    }
+
     #[test]
     fn test_interrupt_error_message_formatting() {
         // Test that our string-based error detection works for interrupt errors
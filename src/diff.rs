@@ -1,10 +1,18 @@
 use crate::core::{Result, TuiqlError};
-use crate::core::db::schema::{Schema, Column, Index, ForeignKey, Table};
-use std::collections::HashMap;
+use crate::core::db::schema::{Affinity, Schema, Column, Index, ForeignKey, Table};
+use std::collections::BTreeMap;
 use rusqlite::Connection;
+use serde::Serialize;
+
+/// Version of the machine-readable diff report layout; bumped on breaking changes
+pub const DIFF_REPORT_VERSION: u32 = 1;
 
 /// Represents the type of schema difference
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Serialized in `snake_case` (e.g. `table_added`); these names are part of the
+/// JSON report contract and must not change.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DiffType {
     TableAdded,
     TableRemoved,
@@ -12,23 +20,139 @@ pub enum DiffType {
     ColumnAdded,
     ColumnRemoved,
     ColumnChanged,
+    ColumnRenamed,
+    ColumnTypeChanged,
+    ColumnNullabilityChanged,
     IndexAdded,
     IndexRemoved,
     ForeignKeyAdded,
     ForeignKeyRemoved,
+    ForeignKeyChanged,
+}
+
+impl DiffType {
+    /// SARIF-style severity level for this kind of change
+    ///
+    /// Removals can drop data and are reported as `error`, in-place changes as
+    /// `warning`, and additions as `note`.
+    pub fn severity(&self) -> &'static str {
+        match self {
+            DiffType::TableRemoved | DiffType::ColumnRemoved => "error",
+            DiffType::TableChanged
+            | DiffType::ColumnChanged
+            | DiffType::ColumnRenamed
+            | DiffType::ColumnTypeChanged
+            | DiffType::ColumnNullabilityChanged
+            | DiffType::IndexRemoved
+            | DiffType::ForeignKeyRemoved
+            | DiffType::ForeignKeyChanged => "warning",
+            DiffType::TableAdded
+            | DiffType::ColumnAdded
+            | DiffType::IndexAdded
+            | DiffType::ForeignKeyAdded => "note",
+        }
+    }
+}
+
+/// Minimum `rename_confidence` score for a removed and an added column to be paired as a rename
+pub const RENAME_CONFIDENCE_THRESHOLD: f64 = 0.6;
+
+/// Minimum name similarity for a rename, unless the names share a word such as `email` in `email_address`
+const RENAME_NAME_SIMILARITY_MIN: f64 = 0.5;
+
+/// A column pairing named by the user as `[table.]old=new`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenamePair {
+    /// Table the pairing applies to; `None` matches any table
+    pub table: Option<String>,
+    pub old: String,
+    pub new: String,
+}
+
+impl RenamePair {
+    pub fn parse(value: &str) -> Result<Self> {
+        let usage = || TuiqlError::Command(format!("Invalid rename '{}': expected [table.]old=new", value));
+        let (old, new) = value.split_once('=').ok_or_else(usage)?;
+        let (table, old) = match old.rsplit_once('.') {
+            Some((table, old)) => (Some(table.to_string()), old),
+            None => (None, old),
+        };
+        if old.is_empty() || new.is_empty() || table.as_deref() == Some("") {
+            return Err(usage());
+        }
+        Ok(RenamePair { table, old: old.to_string(), new: new.to_string() })
+    }
+
+    fn matches(&self, table_name: &str, old: &str, new: &str) -> bool {
+        self.table.as_deref().is_none_or(|t| t == table_name) && self.old == old && self.new == new
+    }
+}
+
+/// The user's decisions on rename pairings, from `--rename` and `--no-rename`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenameOptions {
+    /// Pairings reported as renames whatever their confidence
+    pub accepted: Vec<RenamePair>,
+    /// Pairings never reported as renames
+    pub rejected: Vec<RenamePair>,
+    /// Whether to infer renames besides the accepted ones
+    pub disabled: bool,
+}
+
+/// Whether existing data survives a column change
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataCompatibility {
+    /// Every existing value stays valid and keeps its meaning
+    Safe,
+    /// Existing values may violate the new definition; check the data before migrating
+    NeedsReview,
+    /// Existing values may be converted and lose their original representation
+    Lossy,
+}
+
+impl DataCompatibility {
+    /// Short explanation shown alongside the change in text reports
+    pub fn describe(&self) -> &'static str {
+        match self {
+            DataCompatibility::Safe => "safe: existing data is preserved",
+            DataCompatibility::NeedsReview => "needs review: existing rows may violate the new definition",
+            DataCompatibility::Lossy => "lossy: existing values may be converted",
+        }
+    }
 }
 
 /// Represents a single diff item
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SchemaDiff {
     pub diff_type: DiffType,
     pub table_name: String,
     pub field_name: Option<String>,
     pub description: String,
+    /// New column name for `ColumnRenamed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_name: Option<String>,
+    /// Heuristic confidence (0.0 to 1.0) for inferred changes such as renames
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// Data-compatibility assessment for column type and nullability changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<DataCompatibility>,
+}
+
+impl SchemaDiff {
+    /// Severity of this change, escalated when existing data is at risk
+    pub fn severity(&self) -> &'static str {
+        match self.compatibility {
+            Some(DataCompatibility::Lossy) => "error",
+            Some(DataCompatibility::NeedsReview) => "warning",
+            _ => self.diff_type.severity(),
+        }
+    }
 }
 
 /// Result of comparing two schemas
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct SchemaComparison {
     pub added_tables: Vec<String>,
     pub removed_tables: Vec<String>,
@@ -48,6 +172,11 @@ pub struct SchemaComparison {
 /// * `Ok(SchemaComparison)` with the detailed comparison
 /// * `Err(TuiqlError)` if either database cannot be opened or schemas cannot be introspected
 pub fn compare_databases(path_a: &str, path_b: &str) -> Result<SchemaComparison> {
+    compare_databases_with(path_a, path_b, &RenameOptions::default())
+}
+
+/// Like `compare_databases`, with the user's rename decisions applied
+pub fn compare_databases_with(path_a: &str, path_b: &str, renames: &RenameOptions) -> Result<SchemaComparison> {
     let conn_a = Connection::open(path_a)
         .map_err(TuiqlError::Database)?;
     let conn_b = Connection::open(path_b)
@@ -56,18 +185,29 @@ pub fn compare_databases(path_a: &str, path_b: &str) -> Result<SchemaComparison>
     let schema_a = Schema::from_connection(&conn_a)?;
     let schema_b = Schema::from_connection(&conn_b)?;
 
-    compare_schemas(&schema_a, &schema_b)
+    compare_schemas_with(&schema_a, &schema_b, renames)
 }
 
 /// Compares two Schema objects and returns detailed differences
 pub fn compare_schemas(schema_a: &Schema, schema_b: &Schema) -> Result<SchemaComparison> {
+    compare_schemas_with(schema_a, schema_b, &RenameOptions::default())
+}
+
+/// Like `compare_schemas`, with the user's rename decisions applied
+pub fn compare_schemas_with(schema_a: &Schema, schema_b: &Schema, renames: &RenameOptions) -> Result<SchemaComparison> {
     let mut added_tables = Vec::new();
     let mut removed_tables = Vec::new();
     let mut changed_tables = Vec::new();
     let mut detailed_diffs = Vec::new();
 
+    // Iterate in name order so that reports are deterministic
+    let mut table_names_a: Vec<&String> = schema_a.tables.keys().collect();
+    table_names_a.sort();
+    let mut table_names_b: Vec<&String> = schema_b.tables.keys().collect();
+    table_names_b.sort();
+
     // Find added tables (in B but not in A)
-    for table_name in schema_b.tables.keys() {
+    for table_name in table_names_b {
         if !schema_a.tables.contains_key(table_name) {
             added_tables.push(table_name.clone());
             detailed_diffs.push(SchemaDiff {
@@ -75,12 +215,15 @@ pub fn compare_schemas(schema_a: &Schema, schema_b: &Schema) -> Result<SchemaCom
                 table_name: table_name.clone(),
                 field_name: None,
                 description: format!("Table '{}' was added", table_name),
+                new_name: None,
+                confidence: None,
+                compatibility: None,
             });
         }
     }
 
     // Find removed tables (in A but not in B)
-    for table_name in schema_a.tables.keys() {
+    for &table_name in &table_names_a {
         if !schema_b.tables.contains_key(table_name) {
             removed_tables.push(table_name.clone());
             detailed_diffs.push(SchemaDiff {
@@ -88,14 +231,18 @@ pub fn compare_schemas(schema_a: &Schema, schema_b: &Schema) -> Result<SchemaCom
                 table_name: table_name.clone(),
                 field_name: None,
                 description: format!("Table '{}' was removed", table_name),
+                new_name: None,
+                confidence: None,
+                compatibility: None,
             });
         }
     }
 
     // Compare common tables
-    for (table_name, table_a) in &schema_a.tables {
+    for table_name in table_names_a {
+        let table_a = &schema_a.tables[table_name];
         if let Some(table_b) = schema_b.tables.get(table_name) {
-            if let Some(table_diffs) = compare_tables(table_a, table_b) {
+            if let Some(table_diffs) = compare_tables(table_a, table_b, renames) {
                 changed_tables.push(table_name.clone());
                 detailed_diffs.extend(table_diffs);
             }
@@ -111,11 +258,11 @@ pub fn compare_schemas(schema_a: &Schema, schema_b: &Schema) -> Result<SchemaCom
 }
 
 /// Compares two tables and returns their differences if any
-fn compare_tables(table_a: &Table, table_b: &Table) -> Option<Vec<SchemaDiff>> {
+fn compare_tables(table_a: &Table, table_b: &Table, renames: &RenameOptions) -> Option<Vec<SchemaDiff>> {
     let mut diffs = Vec::new();
 
     // Compare columns
-    diffs.extend(compare_columns(&table_a.name, &table_a.columns, &table_b.columns));
+    diffs.extend(compare_columns(&table_a.name, &table_a.columns, &table_b.columns, renames));
 
     // Compare indexes
     diffs.extend(compare_indexes(&table_a.name, &table_a.indexes, &table_b.indexes));
@@ -131,62 +278,155 @@ fn compare_tables(table_a: &Table, table_b: &Table) -> Option<Vec<SchemaDiff>> {
 }
 
 /// Compares columns between two tables
-fn compare_columns(table_name: &str, cols_a: &[Column], cols_b: &[Column]) -> Vec<SchemaDiff> {
+///
+/// Removed and added columns that look alike are paired up as likely renames
+/// (see `rename_confidence`), as are those the user accepted with `--rename`;
+/// only unpaired columns are reported as removals and additions.
+fn compare_columns(table_name: &str, cols_a: &[Column], cols_b: &[Column], options: &RenameOptions) -> Vec<SchemaDiff> {
     let mut diffs = Vec::new();
-    let cols_a_map: HashMap<&str, &Column> = cols_a.iter().map(|c| (c.name.as_str(), c)).collect();
-    let cols_b_map: HashMap<&str, &Column> = cols_b.iter().map(|c| (c.name.as_str(), c)).collect();
+    // Ordered maps keep the report deterministic
+    let cols_a_map: BTreeMap<&str, &Column> = cols_a.iter().map(|c| (c.name.as_str(), c)).collect();
+    let cols_b_map: BTreeMap<&str, &Column> = cols_b.iter().map(|c| (c.name.as_str(), c)).collect();
+
+    let removed: Vec<&Column> = cols_a_map
+        .values()
+        .filter(|c| !cols_b_map.contains_key(c.name.as_str()))
+        .copied()
+        .collect();
+    let added: Vec<&Column> = cols_b_map
+        .values()
+        .filter(|c| !cols_a_map.contains_key(c.name.as_str()))
+        .copied()
+        .collect();
+    let renames = detect_renames(table_name, cols_a, cols_b, &removed, &added, options);
 
     // Find added columns
-    for (col_name, col_b) in &cols_b_map {
-        if !cols_a_map.contains_key(col_name) {
-            diffs.push(SchemaDiff {
-                diff_type: DiffType::ColumnAdded,
-                table_name: table_name.to_string(),
-                field_name: Some(col_name.to_string()),
-                description: format!("Column '{}' was added with type {}", col_name, col_b.type_name),
-            });
-        }
+    for col_b in added.iter().filter(|c| !renames.iter().any(|(_, new, _)| new.name == c.name)) {
+        diffs.push(SchemaDiff {
+            diff_type: DiffType::ColumnAdded,
+            table_name: table_name.to_string(),
+            field_name: Some(col_b.name.to_string()),
+            description: format!("Column '{}' was added with type {}", col_b.name, col_b.type_name),
+            new_name: None,
+            confidence: None,
+            compatibility: None,
+        });
     }
 
     // Find removed columns
-    for col_name in cols_a_map.keys() {
-        if !cols_b_map.contains_key(col_name) {
-            diffs.push(SchemaDiff {
-                diff_type: DiffType::ColumnRemoved,
-                table_name: table_name.to_string(),
-                field_name: Some(col_name.to_string()),
-                description: format!("Column '{}' was removed", col_name),
-            });
-        }
+    for col_a in removed.iter().filter(|c| !renames.iter().any(|(old, _, _)| old.name == c.name)) {
+        diffs.push(SchemaDiff {
+            diff_type: DiffType::ColumnRemoved,
+            table_name: table_name.to_string(),
+            field_name: Some(col_a.name.to_string()),
+            description: format!("Column '{}' was removed", col_a.name),
+            new_name: None,
+            confidence: None,
+            compatibility: None,
+        });
+    }
+
+    // Report likely renames, followed by any definition changes made alongside them
+    for (col_a, col_b, confidence) in &renames {
+        let description = if *confidence >= 1.0 {
+            format!("Column '{}' was renamed to '{}' (confirmed)", col_a.name, col_b.name)
+        } else {
+            format!(
+                "Column '{}' was likely renamed to '{}' (confidence {:.0}%)",
+                col_a.name,
+                col_b.name,
+                confidence * 100.0
+            )
+        };
+        diffs.push(SchemaDiff {
+            diff_type: DiffType::ColumnRenamed,
+            table_name: table_name.to_string(),
+            field_name: Some(col_a.name.to_string()),
+            description,
+            new_name: Some(col_b.name.to_string()),
+            confidence: Some(*confidence),
+            compatibility: Some(DataCompatibility::Safe),
+        });
+        diffs.extend(compare_column_definitions(table_name, col_a, col_b));
     }
 
     // Compare common columns
     for (col_name, col_a) in &cols_a_map {
         if let Some(col_b) = cols_b_map.get(col_name) {
-            if let Some(diff) = compare_column(col_a, col_b) {
-                diffs.push(SchemaDiff {
-                    diff_type: DiffType::ColumnChanged,
-                    table_name: table_name.to_string(),
-                    field_name: Some(col_name.to_string()),
-                    description: diff,
-                });
-            }
+            diffs.extend(compare_column_definitions(table_name, col_a, col_b));
         }
     }
 
     diffs
 }
 
-/// Compares two individual columns and returns description of differences if any
-fn compare_column(col_a: &Column, col_b: &Column) -> Option<String> {
-    let mut differences = Vec::new();
+/// Classifies the differences between two definitions of the same column
+///
+/// Type and nullability changes get their own diff types with a data-compatibility
+/// assessment; primary key and default changes are reported as `ColumnChanged`.
+fn compare_column_definitions(table_name: &str, col_a: &Column, col_b: &Column) -> Vec<SchemaDiff> {
+    let mut diffs = Vec::new();
 
-    if col_a.type_name != col_b.type_name {
-        differences.push(format!("type: {} → {}", col_a.type_name, col_b.type_name));
+    if !col_a.type_name.eq_ignore_ascii_case(&col_b.type_name) {
+        let compatibility = type_change_compatibility(&col_a.type_name, &col_b.type_name);
+        diffs.push(SchemaDiff {
+            diff_type: DiffType::ColumnTypeChanged,
+            table_name: table_name.to_string(),
+            field_name: Some(col_b.name.to_string()),
+            description: format!(
+                "Column '{}' type changed: {} → {} ({})",
+                col_b.name,
+                col_a.type_name,
+                col_b.type_name,
+                compatibility.describe()
+            ),
+            new_name: None,
+            confidence: None,
+            compatibility: Some(compatibility),
+        });
     }
+
     if col_a.notnull != col_b.notnull {
-        differences.push(format!("notnull: {} → {}", col_a.notnull, col_b.notnull));
+        let (change, compatibility) = if col_b.notnull {
+            ("nullable → NOT NULL", DataCompatibility::NeedsReview)
+        } else {
+            ("NOT NULL → nullable", DataCompatibility::Safe)
+        };
+        diffs.push(SchemaDiff {
+            diff_type: DiffType::ColumnNullabilityChanged,
+            table_name: table_name.to_string(),
+            field_name: Some(col_b.name.to_string()),
+            description: format!(
+                "Column '{}' changed from {} ({})",
+                col_b.name,
+                change,
+                compatibility.describe()
+            ),
+            new_name: None,
+            confidence: None,
+            compatibility: Some(compatibility),
+        });
+    }
+
+    if let Some(diff) = compare_column(col_a, col_b) {
+        diffs.push(SchemaDiff {
+            diff_type: DiffType::ColumnChanged,
+            table_name: table_name.to_string(),
+            field_name: Some(col_b.name.to_string()),
+            description: diff,
+            new_name: None,
+            confidence: None,
+            compatibility: None,
+        });
     }
+
+    diffs
+}
+
+/// Compares primary key and default of two columns and returns description of differences if any
+fn compare_column(col_a: &Column, col_b: &Column) -> Option<String> {
+    let mut differences = Vec::new();
+
     if col_a.pk != col_b.pk {
         differences.push(format!("primary key: {} → {}", col_a.pk, col_b.pk));
     }
@@ -203,32 +443,171 @@ fn compare_column(col_a: &Column, col_b: &Column) -> Option<String> {
     }
 }
 
+/// Pairs removed columns with added columns that are likely the same column renamed
+///
+/// Candidate pairs are scored with `rename_confidence` and matched greedily from the
+/// highest score down, so each column takes part in at most one rename. Pairings
+/// accepted by the user score 1.0; rejected ones, and all inferred ones when
+/// detection is disabled, are never made.
+fn detect_renames<'a>(
+    table_name: &str,
+    cols_a: &[Column],
+    cols_b: &[Column],
+    removed: &[&'a Column],
+    added: &[&'a Column],
+    options: &RenameOptions,
+) -> Vec<(&'a Column, &'a Column, f64)> {
+    let position = |cols: &[Column], name: &str| cols.iter().position(|c| c.name == name);
+
+    let mut candidates = Vec::new();
+    for &col_a in removed {
+        for &col_b in added {
+            let is = |pairs: &[RenamePair]| pairs.iter().any(|p| p.matches(table_name, &col_a.name, &col_b.name));
+            let confidence = if is(&options.accepted) {
+                1.0
+            } else if options.disabled || is(&options.rejected) {
+                continue;
+            } else {
+                let same_position = position(cols_a, &col_a.name) == position(cols_b, &col_b.name);
+                rename_confidence(col_a, col_b, same_position)
+            };
+            if confidence >= RENAME_CONFIDENCE_THRESHOLD {
+                candidates.push((col_a, col_b, confidence));
+            }
+        }
+    }
+    // Highest confidence first; names break ties so the pairing is deterministic
+    candidates.sort_by(|x, y| {
+        y.2.total_cmp(&x.2)
+            .then_with(|| x.0.name.cmp(&y.0.name))
+            .then_with(|| x.1.name.cmp(&y.1.name))
+    });
+
+    let mut renames: Vec<(&Column, &Column, f64)> = Vec::new();
+    for (col_a, col_b, confidence) in candidates {
+        let taken = renames
+            .iter()
+            .any(|(a, b, _)| a.name == col_a.name || b.name == col_b.name);
+        if !taken {
+            renames.push((col_a, col_b, confidence));
+        }
+    }
+    renames.sort_by(|x, y| x.0.name.cmp(&y.0.name));
+    renames
+}
+
+/// Scores how likely it is that `col_b` is `col_a` under a new name, from 0.0 to 1.0
+///
+/// Weights: declared type 0.3 (0.2 for the same affinity only), constraints 0.3
+/// (NOT NULL, primary key and default, 0.1 each), position 0.15 and name similarity 0.25.
+/// Columns with unrelated names score 0.0: a dropped `foo TEXT` and an added `bar TEXT`
+/// match on everything but the name, yet are not a rename.
+fn rename_confidence(col_a: &Column, col_b: &Column, same_position: bool) -> f64 {
+    let similarity = name_similarity(&col_a.name, &col_b.name);
+    if similarity < RENAME_NAME_SIMILARITY_MIN && !share_name_word(&col_a.name, &col_b.name) {
+        return 0.0;
+    }
+
+    let mut score = 0.0;
+
+    if col_a.type_name.eq_ignore_ascii_case(&col_b.type_name) {
+        score += 0.3;
+    } else if Affinity::of(&col_a.type_name) == Affinity::of(&col_b.type_name) {
+        score += 0.2;
+    }
+    if col_a.notnull == col_b.notnull {
+        score += 0.1;
+    }
+    if col_a.pk == col_b.pk {
+        score += 0.1;
+    }
+    if col_a.dflt_value == col_b.dflt_value {
+        score += 0.1;
+    }
+    if same_position {
+        score += 0.15;
+    }
+    score += 0.25 * similarity;
+
+    (score * 100.0).round() / 100.0
+}
+
+/// Whether two identifiers have a `_`-separated word in common, ignoring case
+fn share_name_word(a: &str, b: &str) -> bool {
+    let a = a.to_lowercase();
+    let b = b.to_lowercase();
+    let words_b: Vec<&str> = b.split('_').filter(|w| !w.is_empty()).collect();
+    a.split('_').filter(|w| !w.is_empty()).any(|w| words_b.contains(&w))
+}
+
+/// Normalized Levenshtein similarity of two identifiers, ignoring case
+fn name_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    1.0 - previous[b.len()] as f64 / max_len as f64
+}
+
+/// Assesses whether existing values survive a change of declared type
+fn type_change_compatibility(from: &str, to: &str) -> DataCompatibility {
+    use Affinity::*;
+
+    match (Affinity::of(from), Affinity::of(to)) {
+        (a, b) if a == b => DataCompatibility::Safe,
+        (_, Blob) | (Integer, Real) | (Integer, Numeric) | (Real, Numeric) => DataCompatibility::Safe,
+        (Text, Integer | Real | Numeric) | (Blob, Integer | Real | Numeric) => DataCompatibility::Lossy,
+        _ => DataCompatibility::NeedsReview,
+    }
+}
+
 /// Compares indexes between two tables
 fn compare_indexes(table_name: &str, indexes_a: &[Index], indexes_b: &[Index]) -> Vec<SchemaDiff> {
     let mut diffs = Vec::new();
-    let idx_a_map: HashMap<&str, &Index> = indexes_a.iter().map(|i| (i.name.as_str(), i)).collect();
-    let idx_b_map: HashMap<&str, &Index> = indexes_b.iter().map(|i| (i.name.as_str(), i)).collect();
+    let idx_a_map: BTreeMap<&str, &Index> = indexes_a.iter().map(|i| (i.name.as_str(), i)).collect();
+    let idx_b_map: BTreeMap<&str, &Index> = indexes_b.iter().map(|i| (i.name.as_str(), i)).collect();
 
     // Find added indexes
-    for idx_name in idx_b_map.keys() {
+    for &idx_name in idx_b_map.keys() {
         if !idx_a_map.contains_key(idx_name) {
             diffs.push(SchemaDiff {
                 diff_type: DiffType::IndexAdded,
                 table_name: table_name.to_string(),
                 field_name: Some(idx_name.to_string()),
                 description: format!("Index '{}' was added", idx_name),
+                new_name: None,
+                confidence: None,
+                compatibility: None,
             });
         }
     }
 
     // Find removed indexes
-    for idx_name in idx_a_map.keys() {
+    for &idx_name in idx_a_map.keys() {
         if !idx_b_map.contains_key(idx_name) {
             diffs.push(SchemaDiff {
                 diff_type: DiffType::IndexRemoved,
                 table_name: table_name.to_string(),
                 field_name: Some(idx_name.to_string()),
                 description: format!("Index '{}' was removed", idx_name),
+                new_name: None,
+                confidence: None,
+                compatibility: None,
             });
         }
     }
@@ -237,25 +616,62 @@ fn compare_indexes(table_name: &str, indexes_a: &[Index], indexes_b: &[Index]) -
 }
 
 /// Compares foreign keys between two tables
+///
+/// Constraints are matched by parent table and child columns; a matched pair whose
+/// referenced columns, actions, MATCH or deferrability differ is reported as changed.
 fn compare_foreign_keys(table_name: &str, fks_a: &[ForeignKey], fks_b: &[ForeignKey]) -> Vec<SchemaDiff> {
-    let mut diffs = Vec::new();
-
-    // Simple comparison: check if the number of FKs differs
-    if fks_a.len() != fks_b.len() {
-        if fks_a.len() < fks_b.len() {
-            diffs.push(SchemaDiff {
-                diff_type: DiffType::ForeignKeyAdded,
-                table_name: table_name.to_string(),
-                field_name: None,
-                description: format!("Foreign key(s) were added to table '{}'", table_name),
-            });
+    let key = |fk: &ForeignKey| (fk.referenced_table.to_lowercase(), fk.from_columns.join(", "));
+    let fk_a_map: BTreeMap<_, &ForeignKey> = fks_a.iter().map(|fk| (key(fk), fk)).collect();
+    let fk_b_map: BTreeMap<_, &ForeignKey> = fks_b.iter().map(|fk| (key(fk), fk)).collect();
+    let diff = |diff_type, fk: &ForeignKey, description: String| SchemaDiff {
+        diff_type,
+        table_name: table_name.to_string(),
+        field_name: Some(fk.from_columns.join(", ")),
+        description,
+        new_name: None,
+        confidence: None,
+        compatibility: None,
+    };
+    let describe = |fk: &ForeignKey| {
+        let actions = fk.actions();
+        if actions.is_empty() {
+            fk.describe()
         } else {
-            diffs.push(SchemaDiff {
-                diff_type: DiffType::ForeignKeyRemoved,
-                table_name: table_name.to_string(),
-                field_name: None,
-                description: format!("Foreign key(s) were removed from table '{}'", table_name),
-            });
+            format!("{} {}", fk.describe(), actions)
+        }
+    };
+
+    let mut diffs = Vec::new();
+    for (fk_key, fk_b) in &fk_b_map {
+        match fk_a_map.get(fk_key) {
+            None => diffs.push(diff(
+                DiffType::ForeignKeyAdded,
+                fk_b,
+                format!("Foreign key {} was added", describe(fk_b)),
+            )),
+            Some(fk_a) => {
+                let same = fk_a.to_columns == fk_b.to_columns
+                    && fk_a.on_delete == fk_b.on_delete
+                    && fk_a.on_update == fk_b.on_update
+                    && fk_a.match_type == fk_b.match_type
+                    && fk_a.deferrable == fk_b.deferrable;
+                if !same {
+                    diffs.push(diff(
+                        DiffType::ForeignKeyChanged,
+                        fk_b,
+                        format!("Foreign key changed from {} to {}", describe(fk_a), describe(fk_b)),
+                    ));
+                }
+            }
+        }
+    }
+    for (fk_key, fk_a) in &fk_a_map {
+        if !fk_b_map.contains_key(fk_key) {
+            diffs.push(diff(
+                DiffType::ForeignKeyRemoved,
+                fk_a,
+                format!("Foreign key {} was removed", describe(fk_a)),
+            ));
         }
     }
 
@@ -301,9 +717,148 @@ pub fn format_comparison(comparison: &SchemaComparison, path_a: &str, path_b: &s
         }
     }
 
+    let inferred: Vec<String> = comparison
+        .detailed_diffs
+        .iter()
+        .filter(|d| d.diff_type == DiffType::ColumnRenamed && d.confidence.is_some_and(|c| c < 1.0))
+        .map(|d| {
+            let old = d.field_name.as_deref().unwrap_or("");
+            format!("{}.{}={}", d.table_name, old, d.new_name.as_deref().unwrap_or(""))
+        })
+        .collect();
+    if !inferred.is_empty() {
+        output.push_str("\n💡 Renames are inferred. Re-run the diff with --rename <pair> to confirm one, \
+                         --no-rename <pair> to reject it, or --no-rename alone to report removals and additions:\n");
+        for pair in inferred {
+            output.push_str(&format!("  {}\n", pair));
+        }
+    }
+
     output
 }
 
+/// Summary counts included at the top of machine-readable reports
+#[derive(Debug, Serialize)]
+struct DiffSummary {
+    added_tables: usize,
+    removed_tables: usize,
+    changed_tables: usize,
+    total_changes: usize,
+}
+
+/// A single finding in the JSON report, pairing a diff with its severity
+#[derive(Debug, Serialize)]
+struct DiffResult<'a> {
+    #[serde(flatten)]
+    diff: &'a SchemaDiff,
+    severity: &'static str,
+}
+
+/// Versioned envelope for the JSON diff report
+#[derive(Debug, Serialize)]
+struct DiffReport<'a> {
+    version: u32,
+    source: &'a str,
+    target: &'a str,
+    summary: DiffSummary,
+    added_tables: &'a [String],
+    removed_tables: &'a [String],
+    changed_tables: &'a [String],
+    results: Vec<DiffResult<'a>>,
+}
+
+/// Renders a SchemaComparison in the requested output format.
+///
+/// Supported formats: text (the default emoji-decorated summary), json and markdown.
+pub fn render_comparison(comparison: &SchemaComparison, path_a: &str, path_b: &str, format: &str) -> Result<String> {
+    match format.to_lowercase().as_str() {
+        "text" => Ok(format_comparison(comparison, path_a, path_b)),
+        "json" => format_comparison_json(comparison, path_a, path_b),
+        "markdown" | "md" => Ok(format_comparison_markdown(comparison, path_a, path_b)),
+        _ => Err(TuiqlError::Ui(format!(
+            "Unsupported diff format: '{}'. Supported formats: text, json, markdown",
+            format
+        ))),
+    }
+}
+
+/// Generates a machine-readable JSON report from a SchemaComparison
+///
+/// The layout is versioned by `DIFF_REPORT_VERSION`. Each entry in `results` carries the
+/// `diff_type`, `table_name`, `field_name`, `description` and `severity` fields.
+pub fn format_comparison_json(comparison: &SchemaComparison, path_a: &str, path_b: &str) -> Result<String> {
+    let report = DiffReport {
+        version: DIFF_REPORT_VERSION,
+        source: path_a,
+        target: path_b,
+        summary: DiffSummary {
+            added_tables: comparison.added_tables.len(),
+            removed_tables: comparison.removed_tables.len(),
+            changed_tables: comparison.changed_tables.len(),
+            total_changes: comparison.detailed_diffs.len(),
+        },
+        added_tables: &comparison.added_tables,
+        removed_tables: &comparison.removed_tables,
+        changed_tables: &comparison.changed_tables,
+        results: comparison
+            .detailed_diffs
+            .iter()
+            .map(|diff| DiffResult {
+                diff,
+                severity: diff.severity(),
+            })
+            .collect(),
+    };
+
+    serde_json::to_string_pretty(&report).map_err(TuiqlError::Json)
+}
+
+/// Generates a Markdown report suitable for posting as a code review comment
+pub fn format_comparison_markdown(comparison: &SchemaComparison, path_a: &str, path_b: &str) -> String {
+    let mut output = format!("## Schema diff: `{}` → `{}`\n\n", path_a, path_b);
+
+    if comparison.detailed_diffs.is_empty() {
+        output.push_str("No differences found between the schemas.\n");
+        return output;
+    }
+
+    output.push_str(&format!(
+        "**{} change(s)**: {} table(s) added, {} removed, {} changed.\n\n",
+        comparison.detailed_diffs.len(),
+        comparison.added_tables.len(),
+        comparison.removed_tables.len(),
+        comparison.changed_tables.len()
+    ));
+
+    output.push_str("| Severity | Change | Table | Field | Description |\n");
+    output.push_str("|----------|--------|-------|-------|-------------|\n");
+    for diff in &comparison.detailed_diffs {
+        let field = diff
+            .field_name
+            .as_deref()
+            .map(|f| format!("`{}`", escape_markdown_cell(f)))
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "| {} | {} | `{}` | {} | {} |\n",
+            diff.severity(),
+            serde_json::to_value(&diff.diff_type)
+                .ok()
+                .and_then(|v| v.as_str().map(String::from))
+                .unwrap_or_default(),
+            escape_markdown_cell(&diff.table_name),
+            field,
+            escape_markdown_cell(&diff.description)
+        ));
+    }
+
+    output
+}
+
+/// Escapes characters that would break a Markdown table cell
+fn escape_markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
 /// Helper function to get a symbol for each diff type
 fn get_diff_symbol(diff_type: &DiffType) -> &'static str {
     match diff_type {
@@ -313,10 +868,14 @@ fn get_diff_symbol(diff_type: &DiffType) -> &'static str {
         DiffType::ColumnAdded => "++",
         DiffType::ColumnRemoved => "--",
         DiffType::ColumnChanged => "~~",
+        DiffType::ColumnRenamed => "=>",
+        DiffType::ColumnTypeChanged => "~t",
+        DiffType::ColumnNullabilityChanged => "~n",
         DiffType::IndexAdded => "+i",
         DiffType::IndexRemoved => "-i",
         DiffType::ForeignKeyAdded => "+f",
         DiffType::ForeignKeyRemoved => "-f",
+        DiffType::ForeignKeyChanged => "~f",
     }
 }
 
@@ -476,24 +1035,36 @@ mod tests {
                     table_name: "new_table".to_string(),
                     field_name: None,
                     description: "Test add".to_string(),
+                    new_name: None,
+                    confidence: None,
+                    compatibility: None,
                 },
                 SchemaDiff {
                     diff_type: DiffType::ColumnAdded,
                     table_name: "modified_table".to_string(),
                     field_name: Some("new_column".to_string()),
                     description: "Test column change".to_string(),
+                    new_name: None,
+                    confidence: None,
+                    compatibility: None,
                 },
                 SchemaDiff {
                     diff_type: DiffType::ColumnRemoved,
                     table_name: "modified_table".to_string(),
                     field_name: Some("old_column".to_string()),
                     description: "Test column removal".to_string(),
+                    new_name: None,
+                    confidence: None,
+                    compatibility: None,
                 },
                 SchemaDiff {
                     diff_type: DiffType::TableRemoved,
                     table_name: "old_table".to_string(),
                     field_name: None,
                     description: "Test table removal".to_string(),
+                    new_name: None,
+                    confidence: None,
+                    compatibility: None,
                 },
             ],
         };
@@ -548,14 +1119,186 @@ mod tests {
             },
         ];
 
-        let diffs = compare_columns("test_table", &cols_a, &cols_b);
+        let diffs = compare_columns("test_table", &cols_a, &cols_b, &RenameOptions::default());
         assert!(!diffs.is_empty());
 
-        // Should have column added (email) and column changed (id)
-        let added_count = diffs.iter().filter(|d| d.diff_type == DiffType::ColumnAdded).count();
-        let changed_count = diffs.iter().filter(|d| d.diff_type == DiffType::ColumnChanged).count();
-        assert_eq!(added_count, 1);
-        assert_eq!(changed_count, 1);
+        // name -> email matches on type and position, but the names are unrelated
+        assert!(!diffs.iter().any(|d| d.diff_type == DiffType::ColumnRenamed));
+        let added: Vec<_> = diffs.iter().filter(|d| d.diff_type == DiffType::ColumnAdded).collect();
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].field_name.as_deref(), Some("email"));
+        let removed: Vec<_> = diffs.iter().filter(|d| d.diff_type == DiffType::ColumnRemoved).collect();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].field_name.as_deref(), Some("name"));
+
+        // id became NOT NULL
+        let nullability: Vec<_> = diffs
+            .iter()
+            .filter(|d| d.diff_type == DiffType::ColumnNullabilityChanged)
+            .collect();
+        assert_eq!(nullability.len(), 1);
+        assert_eq!(nullability[0].field_name.as_deref(), Some("id"));
+        assert_eq!(nullability[0].compatibility, Some(DataCompatibility::NeedsReview));
+    }
+
+    fn column(name: &str, type_name: &str) -> Column {
+        Column {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            notnull: false,
+            pk: false,
+            dflt_value: None,
+        }
+    }
+
+    #[test]
+    fn test_compare_columns_unrelated_columns_are_not_renames() {
+        let cols_a = vec![column("id", "INTEGER"), column("price", "REAL")];
+        let cols_b = vec![column("id", "INTEGER"), column("notes", "TEXT"), column("price_cents", "INTEGER")];
+
+        let diffs = compare_columns("items", &cols_a, &cols_b, &RenameOptions::default());
+        assert!(diffs.iter().any(|d| d.diff_type == DiffType::ColumnRemoved));
+        assert_eq!(diffs.iter().filter(|d| d.diff_type == DiffType::ColumnAdded).count(), 2);
+        assert!(!diffs.iter().any(|d| d.diff_type == DiffType::ColumnRenamed));
+    }
+
+    #[test]
+    fn test_compare_columns_same_shape_with_unrelated_name_is_not_rename() {
+        let cols_a = vec![column("id", "INTEGER"), column("foo", "TEXT")];
+        let cols_b = vec![column("id", "INTEGER"), column("bar", "TEXT")];
+
+        let diffs = compare_columns("items", &cols_a, &cols_b, &RenameOptions::default());
+        assert!(!diffs.iter().any(|d| d.diff_type == DiffType::ColumnRenamed));
+        assert!(diffs.iter().any(|d| d.diff_type == DiffType::ColumnRemoved && d.field_name.as_deref() == Some("foo")));
+        assert!(diffs.iter().any(|d| d.diff_type == DiffType::ColumnAdded && d.field_name.as_deref() == Some("bar")));
+
+        // A shared word still pairs names that differ a lot in length
+        let cols_b = vec![column("id", "INTEGER"), column("foo_text", "TEXT")];
+        let diffs = compare_columns("items", &cols_a, &cols_b, &RenameOptions::default());
+        assert!(diffs.iter().any(|d| d.diff_type == DiffType::ColumnRenamed && d.new_name.as_deref() == Some("foo_text")));
+    }
+
+    #[test]
+    fn test_compare_columns_with_rename_decisions() {
+        let cols_a = vec![column("id", "INTEGER"), column("user_name", "TEXT"), column("note", "TEXT")];
+        let cols_b = vec![column("id", "INTEGER"), column("username", "TEXT"), column("remark", "TEXT")];
+        let renamed = |options: &RenameOptions| -> Vec<(String, String, f64)> {
+            compare_columns("users", &cols_a, &cols_b, options)
+                .into_iter()
+                .filter(|d| d.diff_type == DiffType::ColumnRenamed)
+                .map(|d| (d.field_name.unwrap(), d.new_name.unwrap(), d.confidence.unwrap()))
+                .collect()
+        };
+        let pair = |value: &str| RenamePair::parse(value).unwrap();
+
+        // note -> remark is too different to be inferred, so only user_name -> username is
+        let inferred = renamed(&RenameOptions::default());
+        assert_eq!(inferred.len(), 1);
+        assert_eq!(inferred[0].0, "user_name");
+
+        let accepted = RenameOptions { accepted: vec![pair("users.note=remark")], ..Default::default() };
+        let diffs = compare_columns("users", &cols_a, &cols_b, &accepted);
+        assert!(diffs.iter().any(|d| d.description == "Column 'note' was renamed to 'remark' (confirmed)"));
+        assert_eq!(renamed(&accepted).len(), 2);
+        // A pairing for another table does not apply
+        let other_table = RenameOptions { accepted: vec![pair("posts.note=remark")], ..Default::default() };
+        assert_eq!(renamed(&other_table).len(), 1);
+
+        let rejected = RenameOptions { rejected: vec![pair("user_name=username")], ..Default::default() };
+        assert!(renamed(&rejected).is_empty());
+        let removed = compare_columns("users", &cols_a, &cols_b, &rejected);
+        assert!(removed
+            .iter()
+            .any(|d| d.diff_type == DiffType::ColumnRemoved && d.field_name.as_deref() == Some("user_name")));
+
+        let disabled = RenameOptions { disabled: true, accepted: vec![pair("note=remark")], ..Default::default() };
+        assert_eq!(renamed(&disabled), vec![("note".to_string(), "remark".to_string(), 1.0)]);
+
+        assert!(RenamePair::parse("users.name").is_err());
+        assert!(RenamePair::parse(".name=full").is_err());
+    }
+
+    #[test]
+    fn test_format_comparison_suggests_rename_flags() {
+        let schema_a = Schema::from_ddl("CREATE TABLE users (id INTEGER PRIMARY KEY, user_name TEXT);").unwrap();
+        let schema_b = Schema::from_ddl("CREATE TABLE users (id INTEGER PRIMARY KEY, username TEXT);").unwrap();
+
+        let pair_of = |value: &str| RenamePair::parse(value).unwrap();
+        let output = format_comparison(&compare_schemas(&schema_a, &schema_b).unwrap(), "a", "b");
+        assert!(output.contains("--rename <pair>"), "{}", output);
+        assert!(output.contains("  users.user_name=username\n"), "{}", output);
+
+        let confirmed = RenameOptions { accepted: vec![pair_of("users.user_name=username")], ..Default::default() };
+        let output = format_comparison(&compare_schemas_with(&schema_a, &schema_b, &confirmed).unwrap(), "a", "b");
+        assert!(!output.contains("--rename <pair>"), "{}", output);
+    }
+
+    #[test]
+    fn test_compare_columns_prefers_most_similar_rename() {
+        let cols_a = vec![column("id", "INTEGER"), column("user_name", "TEXT")];
+        let cols_b = vec![column("id", "INTEGER"), column("bio", "TEXT"), column("username", "TEXT")];
+
+        let diffs = compare_columns("users", &cols_a, &cols_b, &RenameOptions::default());
+        let renamed: Vec<_> = diffs.iter().filter(|d| d.diff_type == DiffType::ColumnRenamed).collect();
+        assert_eq!(renamed.len(), 1);
+        assert_eq!(renamed[0].new_name.as_deref(), Some("username"));
+        assert!(renamed[0].confidence.unwrap() >= RENAME_CONFIDENCE_THRESHOLD);
+        assert!(renamed[0].description.contains("confidence"));
+
+        // The other added column is still reported as an addition
+        let added: Vec<_> = diffs.iter().filter(|d| d.diff_type == DiffType::ColumnAdded).collect();
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].field_name.as_deref(), Some("bio"));
+    }
+
+    #[test]
+    fn test_type_change_compatibility() {
+        assert_eq!(type_change_compatibility("INT", "BIGINT"), DataCompatibility::Safe);
+        assert_eq!(type_change_compatibility("VARCHAR(20)", "TEXT"), DataCompatibility::Safe);
+        assert_eq!(type_change_compatibility("INTEGER", "REAL"), DataCompatibility::Safe);
+        assert_eq!(type_change_compatibility("TEXT", "BLOB"), DataCompatibility::Safe);
+        assert_eq!(type_change_compatibility("TEXT", "INTEGER"), DataCompatibility::Lossy);
+        assert_eq!(type_change_compatibility("REAL", "INTEGER"), DataCompatibility::NeedsReview);
+        assert_eq!(type_change_compatibility("INTEGER", "TEXT"), DataCompatibility::NeedsReview);
+    }
+
+    #[test]
+    fn test_compare_foreign_keys() {
+        let schema = |ddl: &str| Schema::from_ddl(&format!("CREATE TABLE p (id INTEGER PRIMARY KEY, a, b, UNIQUE (a, b)); {}", ddl)).unwrap();
+        let a = schema("CREATE TABLE c (x, y, pid REFERENCES p(id), FOREIGN KEY (x, y) REFERENCES p(a, b));");
+        let b = schema(
+            "CREATE TABLE c (x, y, pid REFERENCES p(id) ON DELETE CASCADE, owner REFERENCES p);",
+        );
+
+        let diffs = compare_foreign_keys("c", &a.tables["c"].foreign_keys, &b.tables["c"].foreign_keys);
+        assert_eq!(diffs.len(), 3);
+
+        let added = diffs.iter().find(|d| d.diff_type == DiffType::ForeignKeyAdded).unwrap();
+        assert_eq!(added.description, "Foreign key owner → p(id) was added");
+        let removed = diffs.iter().find(|d| d.diff_type == DiffType::ForeignKeyRemoved).unwrap();
+        assert_eq!(removed.field_name.as_deref(), Some("x, y"));
+        assert_eq!(removed.description, "Foreign key (x, y) → p(a, b) was removed");
+        let changed = diffs.iter().find(|d| d.diff_type == DiffType::ForeignKeyChanged).unwrap();
+        assert_eq!(
+            changed.description,
+            "Foreign key changed from pid → p(id) to pid → p(id) ON DELETE CASCADE"
+        );
+        assert_eq!(changed.severity(), "warning");
+
+        assert!(compare_foreign_keys("c", &a.tables["c"].foreign_keys, &a.tables["c"].foreign_keys).is_empty());
+    }
+
+    #[test]
+    fn test_type_change_is_classified_with_severity() {
+        let cols_a = vec![column("zip", "TEXT")];
+        let cols_b = vec![column("zip", "INTEGER")];
+
+        let diffs = compare_columns("addresses", &cols_a, &cols_b, &RenameOptions::default());
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].diff_type, DiffType::ColumnTypeChanged);
+        assert_eq!(diffs[0].compatibility, Some(DataCompatibility::Lossy));
+        assert_eq!(diffs[0].severity(), "error");
+        assert!(diffs[0].description.contains("TEXT → INTEGER"));
     }
 
     #[test]
@@ -626,4 +1369,110 @@ mod tests {
         assert_eq!(comparison.removed_tables, vec!["posts"]);
         assert!(comparison.changed_tables.contains(&"users".to_string()));
     }
+
+    #[test]
+    fn test_render_comparison_json() {
+        let db1 = create_test_db(setup_test_schema_a);
+        let db2 = create_test_db(setup_test_schema_b);
+        let comparison = compare_databases(
+            db1.path().to_str().unwrap(),
+            db2.path().to_str().unwrap(),
+        )
+        .unwrap();
+
+        let output = render_comparison(&comparison, "a.db", "b.db", "json").unwrap();
+        let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(report["version"], DIFF_REPORT_VERSION);
+        assert_eq!(report["source"], "a.db");
+        assert_eq!(report["target"], "b.db");
+        assert_eq!(report["summary"]["added_tables"], 1);
+        assert_eq!(report["summary"]["removed_tables"], 1);
+
+        let results = report["results"].as_array().unwrap();
+        assert_eq!(results.len(), comparison.detailed_diffs.len());
+        assert_eq!(results[0]["diff_type"], "table_added");
+        assert_eq!(results[0]["table_name"], "orders");
+        assert_eq!(results[0]["severity"], "note");
+        assert!(results
+            .iter()
+            .any(|r| r["diff_type"] == "table_removed" && r["severity"] == "error"));
+    }
+
+    #[test]
+    fn test_render_comparison_is_deterministic() {
+        let db1 = create_test_db(setup_test_schema_a);
+        let db2 = create_test_db(setup_test_schema_b);
+        let path_a = db1.path().to_str().unwrap();
+        let path_b = db2.path().to_str().unwrap();
+
+        let first = render_comparison(&compare_databases(path_a, path_b).unwrap(), "a", "b", "json").unwrap();
+        let second = render_comparison(&compare_databases(path_a, path_b).unwrap(), "a", "b", "json").unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_render_comparison_markdown() {
+        let comparison = SchemaComparison {
+            added_tables: Vec::new(),
+            removed_tables: Vec::new(),
+            changed_tables: vec!["users".to_string()],
+            detailed_diffs: vec![SchemaDiff {
+                diff_type: DiffType::ColumnChanged,
+                table_name: "users".to_string(),
+                field_name: Some("email".to_string()),
+                description: "type TEXT | VARCHAR".to_string(),
+                new_name: None,
+                confidence: None,
+                compatibility: None,
+            }],
+        };
+
+        let output = render_comparison(&comparison, "a.db", "b.db", "markdown").unwrap();
+        assert!(output.contains("## Schema diff: `a.db` → `b.db`"));
+        assert!(output.contains("| Severity | Change | Table | Field | Description |"));
+        assert!(output.contains("| warning | column_changed | `users` | `email` | type TEXT \\| VARCHAR |"));
+
+        let empty = SchemaComparison {
+            added_tables: Vec::new(),
+            removed_tables: Vec::new(),
+            changed_tables: Vec::new(),
+            detailed_diffs: Vec::new(),
+        };
+        let output = render_comparison(&empty, "a.db", "b.db", "md").unwrap();
+        assert!(output.contains("No differences found"));
+    }
+
+    #[test]
+    fn test_render_comparison_unsupported_format() {
+        let comparison = SchemaComparison {
+            added_tables: Vec::new(),
+            removed_tables: Vec::new(),
+            changed_tables: Vec::new(),
+            detailed_diffs: Vec::new(),
+        };
+
+        match render_comparison(&comparison, "a.db", "b.db", "xml") {
+            Err(TuiqlError::Ui(msg)) => assert!(msg.contains("Unsupported diff format")),
+            other => panic!("Expected UI error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_compare_snapshot_with_virtual_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT);
+             CREATE VIRTUAL TABLE docs USING fts5(title, body);",
+        )
+        .unwrap();
+
+        // Replaying a snapshot recreates the shadow tables of docs, which must not show up as changes
+        let snapshot = Schema::from_ddl(&crate::core::db::schema::schema_ddl(&conn).unwrap()).unwrap();
+        assert!(!snapshot.tables.contains_key("docs_data"));
+        let catalog = crate::catalog::CatalogCache::new().get(&conn).unwrap();
+        let comparison = compare_schemas(&snapshot, &catalog.schema).unwrap();
+        assert!(comparison.detailed_diffs.is_empty(), "{:?}", comparison.detailed_diffs);
+        assert!(comparison.removed_tables.is_empty() && comparison.added_tables.is_empty());
+    }
 }
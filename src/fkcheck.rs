@@ -0,0 +1,380 @@
+//! Foreign Key Check Module
+//!
+//! Runs `PRAGMA foreign_key_check` and groups the violations by child table,
+//! foreign key and parent table. Each group carries a sample of the orphaned
+//! rows for the results grid, and can generate DELETE or `UPDATE ... SET NULL`
+//! repair SQL. Repair SQL is only printed for review, never executed.
+
+use crate::core::db::schema::{quote_identifier, ForeignKey, Schema};
+use crate::core::{Result, TuiqlError};
+use crate::db;
+use crate::results_grid::ResultsGrid;
+use rusqlite::Connection;
+use std::collections::BTreeMap;
+
+/// Number of orphaned rows shown per violation group
+pub const SAMPLE_ROWS: usize = 5;
+
+/// How to repair orphaned rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairAction {
+    /// Delete the orphaned child rows
+    Delete,
+    /// Set the child's foreign key columns to NULL
+    SetNull,
+}
+
+impl RepairAction {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "delete" => Ok(RepairAction::Delete),
+            "null" | "set-null" | "setnull" => Ok(RepairAction::SetNull),
+            other => Err(TuiqlError::Command(format!(
+                "Invalid repair action '{}'. Expected one of: delete, null",
+                other
+            ))),
+        }
+    }
+}
+
+/// Violations of one foreign key constraint
+#[derive(Debug, Clone)]
+pub struct ViolationGroup {
+    /// Child table holding the orphaned rows
+    pub table: String,
+    /// Parent table the rows fail to reference
+    pub parent: String,
+    /// Constraint id from `PRAGMA foreign_key_list`
+    pub fk_id: i64,
+    /// The violated constraint, if it could be resolved from the schema
+    pub foreign_key: Option<ForeignKey>,
+    /// Whether the parent table exists at all
+    pub parent_exists: bool,
+    /// Number of orphaned rows
+    pub count: usize,
+    /// Up to `SAMPLE_ROWS` orphaned rows
+    pub sample: ResultsGrid,
+}
+
+impl ViolationGroup {
+    /// WHERE clause selecting the orphaned rows of this group
+    fn orphan_condition(&self) -> Result<String> {
+        let fk = self.foreign_key.as_ref().ok_or_else(|| {
+            TuiqlError::Schema(format!(
+                "Foreign key #{} of table '{}' could not be resolved",
+                self.fk_id, self.table
+            ))
+        })?;
+        let child = quote_identifier(&self.table);
+
+        // A row with any NULL key column is never checked, so it is not an orphan
+        let mut conditions: Vec<String> = fk
+            .from_columns
+            .iter()
+            .map(|c| format!("{}.{} IS NOT NULL", child, quote_identifier(c)))
+            .collect();
+        if self.parent_exists {
+            let parent = quote_identifier(&self.parent);
+            let joins: Vec<String> = fk
+                .from_columns
+                .iter()
+                .zip(&fk.to_columns)
+                .map(|(from, to)| {
+                    format!(
+                        "{}.{} = {}.{}",
+                        parent,
+                        quote_identifier(to),
+                        child,
+                        quote_identifier(from)
+                    )
+                })
+                .collect();
+            conditions.push(format!(
+                "NOT EXISTS (SELECT 1 FROM {} WHERE {})",
+                parent,
+                joins.join(" AND ")
+            ));
+        }
+        Ok(conditions.join("\n  AND "))
+    }
+}
+
+/// Runs `PRAGMA foreign_key_check` for one table (or all tables) and groups the violations.
+pub fn check_foreign_keys(conn: &Connection, table: Option<&str>) -> Result<Vec<ViolationGroup>> {
+    let sql = match table {
+        Some(table) => format!("PRAGMA foreign_key_check({})", quote_identifier(table)),
+        None => "PRAGMA foreign_key_check".to_string(),
+    };
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?, // child table
+            row.get::<_, String>(2)?, // parent table
+            row.get::<_, i64>(3)?,    // fk id
+        ))
+    })?;
+
+    let mut counts: BTreeMap<(String, i64, String), usize> = BTreeMap::new();
+    for row in rows {
+        let (child, parent, fk_id) = row?;
+        *counts.entry((child, fk_id, parent)).or_insert(0) += 1;
+    }
+    if counts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let schema = Schema::from_connection(conn)?;
+    let mut groups = Vec::with_capacity(counts.len());
+    for ((child, fk_id, parent), count) in counts {
+        let foreign_key = schema
+            .tables
+            .get(&child)
+            .and_then(|t| t.foreign_keys.iter().find(|fk| fk.id == fk_id))
+            .cloned();
+        let parent_exists = schema.tables.keys().any(|t| t.eq_ignore_ascii_case(&parent));
+        let mut group = ViolationGroup {
+            table: child,
+            parent,
+            fk_id,
+            foreign_key,
+            parent_exists,
+            count,
+            sample: ResultsGrid::new(),
+        };
+        group.sample = sample_orphans(conn, &group)?;
+        groups.push(group);
+    }
+    Ok(groups)
+}
+
+/// Loads up to `SAMPLE_ROWS` orphaned rows of a group into a results grid
+fn sample_orphans(conn: &Connection, group: &ViolationGroup) -> Result<ResultsGrid> {
+    let mut grid = ResultsGrid::new();
+    let Ok(condition) = group.orphan_condition() else {
+        return Ok(grid);
+    };
+    let sql = format!(
+        "SELECT * FROM {} WHERE {} LIMIT {}",
+        quote_identifier(&group.table),
+        condition,
+        SAMPLE_ROWS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    grid.set_headers(stmt.column_names().iter().map(|c| c.to_string()).collect());
+    let column_count = stmt.column_count();
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let mut values = Vec::with_capacity(column_count);
+        for i in 0..column_count {
+            values.push(db::format_value(row.get_ref(i)?));
+        }
+        grid.add_row(values);
+    }
+    Ok(grid)
+}
+
+/// Generates repair SQL for one violation group, for the user to review and run.
+pub fn repair_sql(group: &ViolationGroup, action: RepairAction) -> Result<String> {
+    let condition = group.orphan_condition()?;
+    let fk = group.foreign_key.as_ref().expect("orphan_condition resolved the foreign key");
+    let child = quote_identifier(&group.table);
+    let mut sql = format!(
+        "-- {} orphaned row(s) in {} ({} → {})\n",
+        group.count,
+        group.table,
+        fk.from_columns.join(", "),
+        group.parent
+    );
+
+    match action {
+        RepairAction::Delete => {
+            sql.push_str(&format!("DELETE FROM {}\nWHERE {};\n", child, condition));
+        }
+        RepairAction::SetNull => {
+            let assignments: Vec<String> = fk
+                .from_columns
+                .iter()
+                .map(|c| format!("{} = NULL", quote_identifier(c)))
+                .collect();
+            sql.push_str(&format!(
+                "UPDATE {}\nSET {}\nWHERE {};\n",
+                child,
+                assignments.join(", "),
+                condition
+            ));
+        }
+    }
+    Ok(sql)
+}
+
+/// Formats violation groups with their sample rows as a report.
+pub fn format_report(groups: &[ViolationGroup]) -> String {
+    if groups.is_empty() {
+        return "✅ No foreign key violations found.\n".to_string();
+    }
+
+    let total: usize = groups.iter().map(|g| g.count).sum();
+    let mut output = format!(
+        "❌ {} foreign key violation(s) in {} group(s):\n",
+        total,
+        groups.len()
+    );
+    for (i, group) in groups.iter().enumerate() {
+        let constraint = match &group.foreign_key {
+            Some(fk) => fk.describe(),
+            None => format!("fk #{} → {}", group.fk_id, group.parent),
+        };
+        output.push_str(&format!(
+            "\n{}. {}: {} ({} orphaned row(s))\n",
+            i + 1,
+            group.table,
+            constraint,
+            group.count
+        ));
+        if !group.parent_exists {
+            output.push_str(&format!("   ⚠️  Parent table '{}' does not exist\n", group.parent));
+        }
+        if !group.sample.rows.is_empty() {
+            if group.count > group.sample.rows.len() {
+                output.push_str(&format!("   Sample of {} row(s):\n", group.sample.rows.len()));
+            }
+            for line in group.sample.render().lines() {
+                output.push_str(&format!("   {}\n", line));
+            }
+        }
+    }
+    output.push_str("\n💡 Generate repair SQL for review with ':fkcheck [table] --repair delete|null'\n");
+    output
+}
+
+/// Executes `:fkcheck [table] [--repair delete|null]`.
+pub fn execute_fkcheck_command(command: &str) -> Result<()> {
+    let mut table = None;
+    let mut repair = None;
+
+    let mut args = command.split_whitespace();
+    while let Some(arg) = args.next() {
+        match arg {
+            "--repair" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| TuiqlError::Command("--repair requires delete or null".to_string()))?;
+                repair = Some(RepairAction::parse(value)?);
+            }
+            _ if arg.starts_with("--") => {
+                return Err(TuiqlError::Command(format!("Unknown option '{}'", arg)));
+            }
+            _ if table.is_none() => table = Some(arg),
+            _ => return Err(TuiqlError::Command(format!("Unexpected argument '{}'", arg))),
+        }
+    }
+
+    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::App("No database connection found".to_string()))?;
+    let state_guard = state_cell
+        .lock()
+        .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+    let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+
+    let groups = check_foreign_keys(conn, table)?;
+    match repair {
+        None => print!("{}", format_report(&groups)),
+        Some(_) if groups.is_empty() => println!("✅ No foreign key violations found; nothing to repair."),
+        Some(action) => {
+            println!("-- Review this SQL before running it; it has NOT been executed.");
+            println!("BEGIN;");
+            for group in &groups {
+                match repair_sql(group, action) {
+                    Ok(sql) => println!("{}", sql),
+                    Err(e) => println!("-- Skipped {} (fk #{}): {}\n", group.table, group.fk_id, e),
+                }
+            }
+            println!("COMMIT;");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orphan_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "PRAGMA foreign_keys = OFF;
+             CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+             CREATE TABLE regions (code TEXT, zone TEXT, PRIMARY KEY (code, zone));
+             CREATE TABLE orders (
+                 id INTEGER PRIMARY KEY,
+                 user_id INTEGER REFERENCES users(id),
+                 code TEXT,
+                 zone TEXT,
+                 FOREIGN KEY (code, zone) REFERENCES regions(code, zone)
+             );
+             INSERT INTO users VALUES (1, 'ann');
+             INSERT INTO regions VALUES ('eu', 'west');
+             INSERT INTO orders VALUES (1, 1, 'eu', 'west'), (2, 7, 'eu', 'west'),
+                                       (3, 8, 'us', 'east'), (4, NULL, NULL, 'east'), (5, 9, 'eu', 'west');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_check_groups_violations() {
+        let conn = orphan_db();
+        let groups = check_foreign_keys(&conn, None).unwrap();
+        assert_eq!(groups.len(), 2);
+
+        let users = groups.iter().find(|g| g.parent == "users").unwrap();
+        assert_eq!(users.table, "orders");
+        assert_eq!(users.count, 3);
+        assert_eq!(users.sample.rows.len(), 3);
+        assert_eq!(users.sample.headers, vec!["id", "user_id", "code", "zone"]);
+        assert_eq!(users.sample.rows[0].cells[0].content, "2");
+
+        let regions = groups.iter().find(|g| g.parent == "regions").unwrap();
+        assert_eq!(regions.count, 1);
+        assert_eq!(regions.foreign_key.as_ref().unwrap().from_columns, vec!["code", "zone"]);
+        assert_eq!(regions.sample.rows[0].cells[0].content, "3");
+
+        assert!(check_foreign_keys(&conn, Some("users")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_repair_sql_removes_exactly_the_orphans() {
+        let conn = orphan_db();
+        let groups = check_foreign_keys(&conn, Some("orders")).unwrap();
+        let users = groups.iter().find(|g| g.parent == "users").unwrap();
+
+        let set_null = repair_sql(users, RepairAction::SetNull).unwrap();
+        assert!(set_null.contains("UPDATE \"orders\"\nSET \"user_id\" = NULL\nWHERE"));
+        let regions = groups.iter().find(|g| g.parent == "regions").unwrap();
+        let delete = repair_sql(regions, RepairAction::Delete).unwrap();
+        assert!(delete.starts_with("-- 1 orphaned row(s) in orders (code, zone → regions)\nDELETE FROM \"orders\""));
+
+        conn.execute_batch(&set_null).unwrap();
+        conn.execute_batch(&delete).unwrap();
+        assert!(check_foreign_keys(&conn, None).unwrap().is_empty());
+        let remaining: i64 = conn.query_row("SELECT COUNT(*) FROM orders", [], |r| r.get(0)).unwrap();
+        assert_eq!(remaining, 4);
+    }
+
+    #[test]
+    fn test_format_report() {
+        let conn = orphan_db();
+        let report = format_report(&check_foreign_keys(&conn, None).unwrap());
+        assert!(report.starts_with("❌ 4 foreign key violation(s) in 2 group(s):\n"));
+        assert!(report.contains("orders: user_id → users(id) (3 orphaned row(s))"));
+        assert!(report.contains("orders: (code, zone) → regions(code, zone) (1 orphaned row(s))"));
+        assert!(report.contains("--repair delete|null"));
+        assert_eq!(format_report(&[]), "✅ No foreign key violations found.\n");
+    }
+
+    #[test]
+    fn test_repair_action_parse() {
+        assert_eq!(RepairAction::parse("DELETE").unwrap(), RepairAction::Delete);
+        assert_eq!(RepairAction::parse("null").unwrap(), RepairAction::SetNull);
+        assert!(RepairAction::parse("truncate").is_err());
+    }
+}
@@ -0,0 +1,595 @@
+//! Database Health Module
+//!
+//! Runs a set of read-only checks against the connected database — integrity or
+//! quick check, foreign key check, freelist and page counts, WAL size, the
+//! auto_vacuum mode and the freshness of `sqlite_stat1` — and turns the findings
+//! into a summary report with suggested fixes. The report can be exported as
+//! JSON for device diagnostics.
+
+use crate::core::db::schema::quote_identifier;
+use crate::core::{Result, TuiqlError};
+use crate::{db, fkcheck};
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Version of the JSON health report layout
+pub const HEALTH_REPORT_VERSION: u32 = 1;
+
+/// Maximum number of integrity problems collected
+const INTEGRITY_MESSAGE_LIMIT: usize = 100;
+
+/// Fraction of free pages above which VACUUM is suggested
+const FREELIST_VACUUM_RATIO: f64 = 0.2;
+
+/// Relative row-count drift above which `sqlite_stat1` is considered stale
+const STAT_DRIFT_RATIO: f64 = 0.25;
+
+/// Absolute row-count drift below which small tables are not reported as stale
+const STAT_DRIFT_MIN_ROWS: i64 = 100;
+
+/// Result of `PRAGMA integrity_check` or `PRAGMA quick_check`
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityResult {
+    /// "integrity_check" or "quick_check"
+    pub mode: String,
+    pub ok: bool,
+    /// Problems reported by SQLite, empty when ok
+    pub messages: Vec<String>,
+}
+
+/// Size of the write-ahead log and the checkpoint threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct WalStatus {
+    pub size_bytes: u64,
+    /// Valid frames in the WAL, from the WAL index (`-shm` file)
+    pub frames: u64,
+    /// Frames already copied into the database by checkpoints; the rest wait
+    /// for the next checkpoint or are still needed by an open reader
+    pub checkpointed: u64,
+    /// `PRAGMA wal_autocheckpoint`, in pages
+    pub autocheckpoint: i64,
+}
+
+/// Whether the planner statistics exist and still match the data
+#[derive(Debug, Clone, Serialize)]
+pub struct StatisticsStatus {
+    /// Whether `sqlite_stat1` exists
+    pub present: bool,
+    /// Tables that were never analyzed
+    pub missing_tables: Vec<String>,
+    /// Tables whose row count drifted from the `sqlite_stat1` estimate
+    pub stale_tables: Vec<String>,
+}
+
+/// An actionable suggestion derived from the checks
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion {
+    /// "error", "warning" or "note"
+    pub severity: String,
+    pub message: String,
+    /// SQL that addresses the finding, if there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sql: Option<String>,
+}
+
+impl Suggestion {
+    fn new(severity: &str, message: String, sql: Option<&str>) -> Self {
+        Suggestion {
+            severity: severity.to_string(),
+            message,
+            sql: sql.map(str::to_string),
+        }
+    }
+}
+
+/// Summary of all health checks
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub version: u32,
+    pub database: String,
+    pub integrity: IntegrityResult,
+    pub foreign_key_violations: usize,
+    pub foreign_key_groups: usize,
+    pub page_size: i64,
+    pub page_count: i64,
+    pub freelist_count: i64,
+    pub journal_mode: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wal: Option<WalStatus>,
+    pub auto_vacuum: String,
+    pub statistics: StatisticsStatus,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl HealthReport {
+    /// Fraction of the file taken up by free pages
+    pub fn freelist_ratio(&self) -> f64 {
+        if self.page_count == 0 {
+            0.0
+        } else {
+            self.freelist_count as f64 / self.page_count as f64
+        }
+    }
+
+    /// Whether any check found an error-level problem
+    pub fn has_errors(&self) -> bool {
+        self.suggestions.iter().any(|s| s.severity == "error")
+    }
+}
+
+/// Reads the valid and checkpointed frame counts from the WAL index of `database`.
+///
+/// The WAL file is not truncated by ordinary checkpoints, so its size says nothing
+/// about the live frames. The WAL index header records them instead: `mxFrame` at
+/// offset 16 and the checkpoint's `nBackfill` at offset 96, in native byte order
+/// (see "The WAL-Index Format" in the SQLite file format documentation). Reading
+/// it leaves the database untouched, unlike running a checkpoint.
+fn read_wal_index(database: &str) -> Option<(u64, u64)> {
+    let mut header = [0u8; 100];
+    std::fs::File::open(format!("{}-shm", database))
+        .and_then(|mut file| std::io::Read::read_exact(&mut file, &mut header))
+        .ok()?;
+    // isInit is zero until a connection has built the index
+    if header[12] == 0 {
+        return None;
+    }
+    let read_u32 = |offset: usize| u64::from(u32::from_ne_bytes(header[offset..offset + 4].try_into().unwrap()));
+    Some((read_u32(16), read_u32(96)))
+}
+
+fn pragma_i64(conn: &Connection, pragma: &str) -> Result<i64> {
+    Ok(conn.query_row(&format!("PRAGMA {}", pragma), [], |row| row.get(0))?)
+}
+
+/// Runs all health checks on the main database of `conn`.
+pub fn run_health_check(conn: &Connection, quick: bool) -> Result<HealthReport> {
+    let mode = if quick { "quick_check" } else { "integrity_check" };
+    let mut stmt = conn.prepare(&format!("PRAGMA {}({})", mode, INTEGRITY_MESSAGE_LIMIT))?;
+    let messages: Vec<String> = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let ok = messages.len() == 1 && messages[0] == "ok";
+    let integrity = IntegrityResult {
+        mode: mode.to_string(),
+        ok,
+        messages: if ok { Vec::new() } else { messages },
+    };
+
+    let fk_groups = fkcheck::check_foreign_keys(conn, None)?;
+    let foreign_key_violations = fk_groups.iter().map(|g| g.count).sum();
+
+    let page_size = pragma_i64(conn, "page_size")?;
+    let page_count = pragma_i64(conn, "page_count")?;
+    let freelist_count = pragma_i64(conn, "freelist_count")?;
+    let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0))?;
+    let auto_vacuum = match pragma_i64(conn, "auto_vacuum")? {
+        1 => "full",
+        2 => "incremental",
+        _ => "none",
+    }
+    .to_string();
+
+    let database: String = conn
+        .query_row("SELECT file FROM pragma_database_list WHERE name = 'main'", [], |row| row.get(0))
+        .unwrap_or_default();
+    let wal = if journal_mode.eq_ignore_ascii_case("wal") && !database.is_empty() {
+        let size_bytes = std::fs::metadata(format!("{}-wal", database)).map(|m| m.len()).unwrap_or(0);
+        let (frames, checkpointed) = read_wal_index(&database).unwrap_or((0, 0));
+        Some(WalStatus {
+            size_bytes,
+            frames,
+            checkpointed,
+            autocheckpoint: pragma_i64(conn, "wal_autocheckpoint")?,
+        })
+    } else {
+        None
+    };
+
+    let statistics = statistics_status(conn)?;
+
+    let mut report = HealthReport {
+        version: HEALTH_REPORT_VERSION,
+        database: if database.is_empty() { ":memory:".to_string() } else { database },
+        integrity,
+        foreign_key_violations,
+        foreign_key_groups: fk_groups.len(),
+        page_size,
+        page_count,
+        freelist_count,
+        journal_mode,
+        wal,
+        auto_vacuum,
+        statistics,
+        suggestions: Vec::new(),
+    };
+    report.suggestions = suggestions(&report);
+    Ok(report)
+}
+
+/// Compares `sqlite_stat1` row estimates against the current table sizes
+fn statistics_status(conn: &Connection) -> Result<StatisticsStatus> {
+    let present = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'sqlite_stat1'",
+            [],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table'
+           AND name NOT LIKE 'sqlite_%'
+           AND sql NOT LIKE 'CREATE VIRTUAL TABLE%'
+           AND name NOT IN (SELECT name FROM pragma_table_list WHERE type = 'shadow')
+         ORDER BY name",
+    )?;
+    let tables: Vec<String> = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+
+    if !present {
+        return Ok(StatisticsStatus {
+            present,
+            missing_tables: tables,
+            stale_tables: Vec::new(),
+        });
+    }
+
+    let mut estimates: HashMap<String, i64> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT tbl, stat FROM sqlite_stat1")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (table, stat) = row?;
+        if let Some(rows) = stat.split_whitespace().next().and_then(|n| n.parse::<i64>().ok()) {
+            let entry = estimates.entry(table.to_lowercase()).or_insert(0);
+            *entry = (*entry).max(rows);
+        }
+    }
+
+    let mut missing_tables = Vec::new();
+    let mut stale_tables = Vec::new();
+    for table in tables {
+        match estimates.get(&table.to_lowercase()) {
+            None => missing_tables.push(table),
+            Some(&estimate) => {
+                let actual: i64 = conn.query_row(
+                    &format!("SELECT COUNT(*) FROM {}", quote_identifier(&table)),
+                    [],
+                    |row| row.get(0),
+                )?;
+                let drift = (actual - estimate).abs();
+                if drift > STAT_DRIFT_MIN_ROWS && drift as f64 > estimate.max(1) as f64 * STAT_DRIFT_RATIO {
+                    stale_tables.push(table);
+                }
+            }
+        }
+    }
+
+    Ok(StatisticsStatus {
+        present,
+        missing_tables,
+        stale_tables,
+    })
+}
+
+/// Derives actionable suggestions from the check results
+fn suggestions(report: &HealthReport) -> Vec<Suggestion> {
+    let mut suggestions = Vec::new();
+
+    if !report.integrity.ok {
+        suggestions.push(Suggestion::new(
+            "error",
+            format!(
+                "{} reported {} problem(s); back up the file and recover it with the sqlite3 .recover command",
+                report.integrity.mode,
+                report.integrity.messages.len()
+            ),
+            None,
+        ));
+    }
+    if report.foreign_key_violations > 0 {
+        suggestions.push(Suggestion::new(
+            "error",
+            format!(
+                "{} foreign key violation(s) in {} group(s); inspect them with :fkcheck",
+                report.foreign_key_violations, report.foreign_key_groups
+            ),
+            None,
+        ));
+    }
+    if report.freelist_count > 0 && report.freelist_ratio() >= FREELIST_VACUUM_RATIO {
+        let (message, sql) = if report.auto_vacuum == "incremental" {
+            ("run an incremental vacuum to release them", "PRAGMA incremental_vacuum;")
+        } else {
+            ("VACUUM to shrink the file", "VACUUM;")
+        };
+        suggestions.push(Suggestion::new(
+            "warning",
+            format!(
+                "{:.0}% of pages ({} of {}) are free; {}",
+                report.freelist_ratio() * 100.0,
+                report.freelist_count,
+                report.page_count,
+                message
+            ),
+            Some(sql),
+        ));
+    }
+    if let Some(wal) = &report.wal {
+        let pending = wal.frames.saturating_sub(wal.checkpointed);
+        if wal.autocheckpoint > 0 && pending as i64 > wal.autocheckpoint * 4 {
+            suggestions.push(Suggestion::new(
+                "warning",
+                format!(
+                    "WAL holds {} frames that have not been checkpointed, far above the autocheckpoint threshold of {}; a reader may be blocking checkpoints",
+                    pending, wal.autocheckpoint
+                ),
+                Some("PRAGMA wal_checkpoint(TRUNCATE);"),
+            ));
+        }
+    }
+    if !report.statistics.present && !report.statistics.missing_tables.is_empty() {
+        suggestions.push(Suggestion::new(
+            "note",
+            "sqlite_stat1 does not exist, so the planner guesses table sizes; run ANALYZE".to_string(),
+            Some("ANALYZE;"),
+        ));
+    } else if !report.statistics.stale_tables.is_empty() || !report.statistics.missing_tables.is_empty() {
+        let mut tables = report.statistics.stale_tables.clone();
+        tables.extend(report.statistics.missing_tables.iter().cloned());
+        tables.sort();
+        let sql = tables
+            .iter()
+            .map(|t| format!("ANALYZE {};", quote_identifier(t)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        suggestions.push(Suggestion::new(
+            "note",
+            format!("Planner statistics are stale or missing for: {}", tables.join(", ")),
+            Some(&sql),
+        ));
+    }
+    suggestions
+}
+
+/// Formats a health report for the terminal.
+pub fn format_report(report: &HealthReport) -> String {
+    let mark = |ok: bool| if ok { "✅" } else { "❌" };
+    let mut output = format!("🩺 Health report for {}\n", report.database);
+    output.push_str(&"=".repeat(60));
+    output.push('\n');
+
+    output.push_str(&format!(
+        "{} {}: {}\n",
+        mark(report.integrity.ok),
+        report.integrity.mode,
+        if report.integrity.ok { "ok".to_string() } else { format!("{} problem(s)", report.integrity.messages.len()) }
+    ));
+    for message in &report.integrity.messages {
+        output.push_str(&format!("     {}\n", message));
+    }
+    output.push_str(&format!(
+        "{} foreign_key_check: {} violation(s)\n",
+        mark(report.foreign_key_violations == 0),
+        report.foreign_key_violations
+    ));
+    output.push_str(&format!(
+        "📄 Pages: {} × {} bytes, {} free ({:.1}%)\n",
+        report.page_count,
+        report.page_size,
+        report.freelist_count,
+        report.freelist_ratio() * 100.0
+    ));
+    output.push_str(&format!("📓 Journal mode: {}\n", report.journal_mode));
+    if let Some(wal) = &report.wal {
+        output.push_str(&format!(
+            "   WAL: {} bytes, {} frame(s), {} checkpointed, autocheckpoint every {} pages\n",
+            wal.size_bytes, wal.frames, wal.checkpointed, wal.autocheckpoint
+        ));
+    }
+    output.push_str(&format!("🧹 auto_vacuum: {}\n", report.auto_vacuum));
+    let stats = &report.statistics;
+    let stats_line = if !stats.present {
+        "sqlite_stat1 missing".to_string()
+    } else if stats.stale_tables.is_empty() && stats.missing_tables.is_empty() {
+        "sqlite_stat1 up to date".to_string()
+    } else {
+        format!(
+            "sqlite_stat1 stale for {} table(s), missing for {}",
+            stats.stale_tables.len(),
+            stats.missing_tables.len()
+        )
+    };
+    output.push_str(&format!("📊 Statistics: {}\n", stats_line));
+
+    if report.suggestions.is_empty() {
+        output.push_str("\n✅ No problems found.\n");
+    } else {
+        output.push_str("\n💡 Suggestions:\n");
+        for suggestion in &report.suggestions {
+            let icon = match suggestion.severity.as_str() {
+                "error" => "❌",
+                "warning" => "⚠️ ",
+                _ => "ℹ️ ",
+            };
+            output.push_str(&format!("  {} {}\n", icon, suggestion.message));
+            if let Some(sql) = &suggestion.sql {
+                output.push_str(&format!("     {}\n", sql));
+            }
+        }
+    }
+    output
+}
+
+/// Executes `:health [--quick] [--json [file]]`.
+pub fn execute_health_command(command: &str) -> Result<()> {
+    let mut quick = false;
+    let mut json = false;
+    let mut output_file = None;
+
+    let mut args = command.split_whitespace().peekable();
+    while let Some(arg) = args.next() {
+        match arg {
+            "--quick" => quick = true,
+            "--json" => {
+                json = true;
+                if let Some(file) = args.next_if(|next| !next.starts_with("--")) {
+                    output_file = Some(file);
+                }
+            }
+            other => return Err(TuiqlError::Command(format!("Unknown option '{}'", other))),
+        }
+    }
+
+    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::App("No database connection found".to_string()))?;
+    let state_guard = state_cell
+        .lock()
+        .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+    let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+
+    let report = run_health_check(conn, quick)?;
+    if !json {
+        print!("{}", format_report(&report));
+        return Ok(());
+    }
+
+    let report_json = serde_json::to_string_pretty(&report)?;
+    match output_file {
+        Some(path) => {
+            std::fs::write(path, report_json)?;
+            println!("✅ Health report written to {}", path);
+        }
+        None => println!("{}", report_json),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_healthy_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+             CREATE INDEX idx_users_name ON users(name);
+             INSERT INTO users (name) VALUES ('a'), ('b');
+             ANALYZE;",
+        )
+        .unwrap();
+
+        let report = run_health_check(&conn, true).unwrap();
+        assert!(report.integrity.ok);
+        assert_eq!(report.integrity.mode, "quick_check");
+        assert_eq!(report.foreign_key_violations, 0);
+        assert!(report.statistics.present);
+        assert!(report.statistics.stale_tables.is_empty());
+        assert!(report.wal.is_none());
+        assert!(report.suggestions.is_empty(), "{:?}", report.suggestions);
+        assert!(format_report(&report).contains("✅ No problems found."));
+    }
+
+    #[test]
+    fn test_problems_produce_suggestions() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "PRAGMA foreign_keys = OFF;
+             CREATE TABLE users (id INTEGER PRIMARY KEY);
+             CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users(id));
+             INSERT INTO posts VALUES (1, 9999);
+             INSERT INTO users (id) VALUES (1), (2);
+             ANALYZE;",
+        )
+        .unwrap();
+        // The table grows well past its analyzed size
+        conn.execute_batch(
+            "WITH RECURSIVE n(i) AS (SELECT 3 UNION ALL SELECT i + 1 FROM n WHERE i < 500)
+             INSERT INTO users (id) SELECT i FROM n;
+             CREATE TABLE tags (id INTEGER PRIMARY KEY);",
+        )
+        .unwrap();
+
+        let report = run_health_check(&conn, false).unwrap();
+        assert_eq!(report.integrity.mode, "integrity_check");
+        assert_eq!(report.foreign_key_violations, 1);
+        assert_eq!(report.statistics.stale_tables, vec!["users"]);
+        assert_eq!(report.statistics.missing_tables, vec!["tags"]);
+        assert!(report.has_errors());
+
+        let messages: Vec<&str> = report.suggestions.iter().map(|s| s.message.as_str()).collect();
+        assert!(messages.iter().any(|m| m.contains(":fkcheck")));
+        let analyze = report.suggestions.iter().find(|s| s.sql.as_deref().is_some_and(|q| q.starts_with("ANALYZE"))).unwrap();
+        assert_eq!(analyze.sql.as_deref(), Some("ANALYZE \"tags\"; ANALYZE \"users\";"));
+    }
+
+    #[test]
+    fn test_missing_statistics_and_freelist() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("health.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE blobs (id INTEGER PRIMARY KEY, data BLOB);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200)
+             INSERT INTO blobs (data) SELECT zeroblob(4000) FROM n;
+             PRAGMA wal_checkpoint(TRUNCATE);
+             DELETE FROM blobs;
+             PRAGMA wal_checkpoint(TRUNCATE);",
+        )
+        .unwrap();
+
+        let report = run_health_check(&conn, true).unwrap();
+        assert!(!report.statistics.present);
+        assert_eq!(report.journal_mode, "wal");
+        assert_eq!(report.wal.as_ref().map(|wal| wal.frames), Some(0));
+        assert!(report.freelist_ratio() > 0.5);
+        assert!(report.suggestions.iter().any(|s| s.sql.as_deref() == Some("VACUUM;")));
+        assert!(report.suggestions.iter().any(|s| s.sql.as_deref() == Some("ANALYZE;")));
+
+        let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+        assert_eq!(json["version"], HEALTH_REPORT_VERSION);
+        assert_eq!(json["auto_vacuum"], "none");
+        assert_eq!(json["statistics"]["present"], false);
+        assert!(json["wal"]["frames"].is_u64());
+    }
+
+    #[test]
+    fn test_wal_blocked_by_reader() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("health.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA wal_autocheckpoint = 1;
+             CREATE TABLE blobs (id INTEGER PRIMARY KEY, data BLOB);",
+        )
+        .unwrap();
+        // A reader whose snapshot predates the inserts keeps their frames in the WAL
+        let reader = Connection::open(&path).unwrap();
+        reader.execute_batch("BEGIN; SELECT COUNT(*) FROM blobs;").unwrap();
+        conn.execute_batch(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 50)
+             INSERT INTO blobs (data) SELECT zeroblob(4000) FROM n;",
+        )
+        .unwrap();
+
+        let wal = run_health_check(&conn, true).unwrap().wal.unwrap();
+        assert!(wal.frames > wal.checkpointed + 4, "{:?}", wal);
+        let report = run_health_check(&conn, true).unwrap();
+        assert!(report
+            .suggestions
+            .iter()
+            .any(|s| s.sql.as_deref() == Some("PRAGMA wal_checkpoint(TRUNCATE);")));
+
+        // The health check itself does not checkpoint
+        assert_eq!(run_health_check(&conn, true).unwrap().wal.unwrap().checkpointed, wal.checkpointed);
+
+        // Once the reader is done, the next commit's autocheckpoint catches up
+        reader.execute_batch("COMMIT;").unwrap();
+        conn.execute_batch("INSERT INTO blobs (data) VALUES (NULL);").unwrap();
+        let wal = run_health_check(&conn, true).unwrap().wal.unwrap();
+        assert_eq!(wal.frames, wal.checkpointed);
+    }
+}
@@ -0,0 +1,770 @@
+//! Index Advisor Module
+//!
+//! Proposes indexes for a query the way SQLite's `.expert` command does: the
+//! schema is replayed into a scratch in-memory database, candidate indexes are
+//! derived from the query's equality, range and ORDER BY constraints, and the
+//! planner is asked which of them it would actually use. Nothing touches the
+//! real database until the user chooses to create the proposed indexes.
+
+use crate::core::db::schema::{quote_identifier, schema_ddl};
+use crate::core::{Result, TuiqlError};
+use crate::db;
+use crate::plan::{query_plan, PlanNode};
+use rusqlite::{Connection, OptionalExtension};
+use sqlparser::ast::{
+    BinaryOperator, Expr, FunctionArg, FunctionArgExpr, JoinConstraint, JoinOperator, Query, Select, SetExpr,
+    Statement, TableFactor, TableWithJoins, Value,
+};
+use sqlparser::dialect::SQLiteDialect;
+use sqlparser::parser::Parser;
+use std::collections::HashMap;
+
+/// An index the advisor proposes
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexCandidate {
+    pub name: String,
+    pub table: String,
+    pub columns: Vec<String>,
+}
+
+impl IndexCandidate {
+    /// The statement that creates the index
+    pub fn create_sql(&self) -> String {
+        format!(
+            "CREATE INDEX {} ON {}({})",
+            quote_identifier(&self.name),
+            quote_identifier(&self.table),
+            self.columns.iter().map(|c| quote_identifier(c)).collect::<Vec<_>>().join(", ")
+        )
+    }
+}
+
+/// Result of advising on one query
+#[derive(Debug, Clone)]
+pub struct Advice {
+    pub query: String,
+    /// Plan against the current schema
+    pub before: Vec<PlanNode>,
+    /// Plan with the proposed indexes in place
+    pub after: Vec<PlanNode>,
+    /// Candidates the planner chose to use, in plan order
+    pub indexes: Vec<IndexCandidate>,
+}
+
+impl Advice {
+    pub fn full_scans_before(&self) -> usize {
+        self.before.iter().filter(|n| n.is_full_scan()).count()
+    }
+
+    pub fn full_scans_after(&self) -> usize {
+        self.after.iter().filter(|n| n.is_full_scan()).count()
+    }
+}
+
+/// How a column is constrained by the query
+#[derive(Debug, Default)]
+pub(crate) struct TableUsage {
+    /// Columns compared for equality with a value
+    pub(crate) equality: Vec<String>,
+    /// Columns compared for equality with a column of another table
+    pub(crate) join: Vec<String>,
+    pub(crate) range: Vec<String>,
+    pub(crate) order_by: Vec<String>,
+    /// Columns matched with a LIKE pattern that starts with `%`
+    pub(crate) leading_wildcard: Vec<String>,
+    /// Columns compared only inside a function call, as (function, column)
+    pub(crate) wrapped: Vec<(String, String)>,
+}
+
+/// Kind of comparison a column takes part in
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Constraint {
+    Equality,
+    Range,
+    LeadingWildcard,
+}
+
+fn push_unique(columns: &mut Vec<String>, column: String) {
+    if !columns.iter().any(|c| c.eq_ignore_ascii_case(&column)) {
+        columns.push(column);
+    }
+}
+
+/// Tables visible in one SELECT, keyed by alias (or name) in lowercase
+struct Scope<'a> {
+    tables: Vec<(String, String)>,
+    columns: &'a HashMap<String, Vec<String>>,
+}
+
+impl Scope<'_> {
+    /// Resolves a column reference to its table and the column's declared name
+    fn resolve(&self, expr: &Expr) -> Option<(String, String)> {
+        let (qualifier, column) = match expr {
+            Expr::Identifier(ident) => (None, ident.value.as_str()),
+            Expr::CompoundIdentifier(parts) if parts.len() >= 2 => {
+                (Some(parts[parts.len() - 2].value.to_lowercase()), parts[parts.len() - 1].value.as_str())
+            }
+            Expr::Nested(inner) => return self.resolve(inner),
+            _ => return None,
+        };
+
+        let mut matches = self
+            .tables
+            .iter()
+            .filter(|(alias, _)| qualifier.as_ref().is_none_or(|q| q == alias))
+            .filter_map(|(_, table)| {
+                let declared = self.columns.get(table)?.iter().find(|c| c.eq_ignore_ascii_case(column))?;
+                Some((table.clone(), declared.clone()))
+            });
+        let first = matches.next()?;
+        // An unqualified name that exists in several tables is ambiguous
+        if matches.next().is_some() {
+            None
+        } else {
+            Some(first)
+        }
+    }
+}
+
+/// Walks a query and records the constraints on every table column
+struct Collector<'a> {
+    conn: &'a Connection,
+    columns: HashMap<String, Vec<String>>,
+    usage: HashMap<String, TableUsage>,
+}
+
+impl Collector<'_> {
+    /// Canonical table name and its columns, loaded on first use; views and CTEs yield None
+    fn table(&mut self, name: &str) -> Result<Option<String>> {
+        let canonical: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?1 COLLATE NOCASE",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(table) = &canonical {
+            if !self.columns.contains_key(table) {
+                let mut stmt = self.conn.prepare("SELECT name FROM pragma_table_info(?1)")?;
+                let columns = stmt.query_map([table], |row| row.get(0))?.collect::<rusqlite::Result<Vec<String>>>()?;
+                self.columns.insert(table.clone(), columns);
+            }
+        }
+        Ok(canonical)
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<()> {
+        match statement {
+            Statement::Query(query) => self.query(query),
+            Statement::Update { table, selection, .. } => {
+                let mut scope = Vec::new();
+                let mut predicates = Vec::new();
+                self.table_with_joins(table, &mut scope, &mut predicates)?;
+                predicates.extend(selection.iter());
+                self.predicates(&scope, &predicates, &[])
+            }
+            Statement::Delete { table_name, selection } => {
+                let mut scope = Vec::new();
+                if let Some(name) = table_name.0.last() {
+                    if let Some(table) = self.table(&name.value)? {
+                        scope.push((name.value.to_lowercase(), table));
+                    }
+                }
+                self.predicates(&scope, &selection.iter().collect::<Vec<_>>(), &[])
+            }
+            _ => Err(TuiqlError::Query(
+                "Index advice is only available for SELECT, UPDATE and DELETE statements".to_string(),
+            )),
+        }
+    }
+
+    fn query(&mut self, query: &Query) -> Result<()> {
+        if let Some(with) = &query.with {
+            for cte in &with.cte_tables {
+                self.query(&cte.query)?;
+            }
+        }
+        let order_by: Vec<&Expr> = query.order_by.iter().map(|o| &o.expr).collect();
+        self.set_expr(&query.body, &order_by)
+    }
+
+    fn set_expr(&mut self, body: &SetExpr, order_by: &[&Expr]) -> Result<()> {
+        match body {
+            SetExpr::Select(select) => self.select(select, order_by),
+            SetExpr::Query(query) => self.query(query),
+            SetExpr::SetOperation { left, right, .. } => {
+                self.set_expr(left, &[])?;
+                self.set_expr(right, &[])
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn select(&mut self, select: &Select, order_by: &[&Expr]) -> Result<()> {
+        let mut scope = Vec::new();
+        let mut predicates = Vec::new();
+        for item in &select.from {
+            self.table_with_joins(item, &mut scope, &mut predicates)?;
+        }
+        predicates.extend(select.selection.iter());
+        self.predicates(&scope, &predicates, order_by)
+    }
+
+    fn table_with_joins<'q>(
+        &mut self,
+        item: &'q TableWithJoins,
+        scope: &mut Vec<(String, String)>,
+        predicates: &mut Vec<&'q Expr>,
+    ) -> Result<()> {
+        self.relation(&item.relation, scope)?;
+        for join in &item.joins {
+            self.relation(&join.relation, scope)?;
+            let constraint = match &join.join_operator {
+                JoinOperator::Inner(c)
+                | JoinOperator::LeftOuter(c)
+                | JoinOperator::RightOuter(c)
+                | JoinOperator::FullOuter(c) => c,
+                _ => continue,
+            };
+            match constraint {
+                JoinConstraint::On(expr) => predicates.push(expr),
+                JoinConstraint::Using(columns) => {
+                    // The joined table is looked up by the USING columns
+                    if let Some((_, table)) = scope.last().cloned() {
+                        for column in columns {
+                            let declared = self
+                                .columns
+                                .get(&table)
+                                .and_then(|cols| cols.iter().find(|c| c.eq_ignore_ascii_case(&column.value)).cloned());
+                            if let Some(declared) = declared {
+                                push_unique(&mut self.usage.entry(table.clone()).or_default().join, declared);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn relation(&mut self, relation: &TableFactor, scope: &mut Vec<(String, String)>) -> Result<()> {
+        match relation {
+            TableFactor::Table { name, alias, .. } => {
+                if let Some(last) = name.0.last() {
+                    if let Some(table) = self.table(&last.value)? {
+                        let key = alias.as_ref().map(|a| &a.name).unwrap_or(last).value.to_lowercase();
+                        scope.push((key, table));
+                    }
+                }
+                Ok(())
+            }
+            TableFactor::Derived { subquery, .. } => self.query(subquery),
+            TableFactor::NestedJoin(inner) => {
+                let mut predicates = Vec::new();
+                self.table_with_joins(inner, scope, &mut predicates)?;
+                let scope_copy = scope.clone();
+                self.predicates(&scope_copy, &predicates, &[])
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn predicates(&mut self, tables: &[(String, String)], predicates: &[&Expr], order_by: &[&Expr]) -> Result<()> {
+        let columns = std::mem::take(&mut self.columns);
+        let scope = Scope { tables: tables.to_vec(), columns: &columns };
+        let mut subqueries = Vec::new();
+        for predicate in predicates {
+            conjunct(predicate, &scope, &mut self.usage, &mut subqueries);
+        }
+
+        // An index can only deliver the ORDER BY if every term is a column of the same table
+        let resolved: Option<Vec<(String, String)>> = order_by.iter().map(|e| scope.resolve(e)).collect();
+        if let Some(resolved) = resolved.filter(|r| !r.is_empty()) {
+            if resolved.iter().all(|(table, _)| table == &resolved[0].0) {
+                let usage = self.usage.entry(resolved[0].0.clone()).or_default();
+                for (_, column) in resolved {
+                    push_unique(&mut usage.order_by, column);
+                }
+            }
+        }
+        self.columns = columns;
+
+        for subquery in subqueries {
+            self.query(subquery)?;
+        }
+        Ok(())
+    }
+}
+
+/// Records the constraints of one AND-ed term of a WHERE or ON clause
+fn conjunct<'q>(
+    expr: &'q Expr,
+    scope: &Scope,
+    usage: &mut HashMap<String, TableUsage>,
+    subqueries: &mut Vec<&'q Query>,
+) {
+    let mut record = |column: &Expr, other: Option<&Expr>, constraint: Constraint| {
+        if let Some((table, name)) = scope.resolve(column) {
+            let other_table = other.and_then(|o| scope.resolve(o)).map(|(t, _)| t);
+            // "a.x = a.y" compares two columns of the same row and cannot use an index
+            if other_table.as_ref() == Some(&table) {
+                return;
+            }
+            let entry = usage.entry(table).or_default();
+            match (constraint, other_table.is_some()) {
+                (Constraint::Equality, false) => push_unique(&mut entry.equality, name),
+                (Constraint::Equality, true) => push_unique(&mut entry.join, name),
+                (Constraint::Range, _) => push_unique(&mut entry.range, name),
+                (Constraint::LeadingWildcard, _) => push_unique(&mut entry.leading_wildcard, name),
+            }
+        } else if let Some((function, (table, name))) =
+            wrapped_column(column).and_then(|(function, inner)| Some((function, scope.resolve(inner)?)))
+        {
+            // "lower(email) = ?" cannot use an index on email
+            let wrapped = &mut usage.entry(table).or_default().wrapped;
+            if !wrapped.iter().any(|(f, c)| f.eq_ignore_ascii_case(&function) && c == &name) {
+                wrapped.push((function, name));
+            }
+        }
+    };
+
+    match expr {
+        Expr::Nested(inner) => conjunct(inner, scope, usage, subqueries),
+        Expr::BinaryOp { left, op: BinaryOperator::And, right } => {
+            conjunct(left, scope, usage, subqueries);
+            conjunct(right, scope, usage, subqueries);
+        }
+        Expr::BinaryOp { left, op: BinaryOperator::Like | BinaryOperator::ILike, right } => {
+            if matches!(right.as_ref(), Expr::Value(Value::SingleQuotedString(pattern)) if pattern.starts_with('%')) {
+                record(left, None, Constraint::LeadingWildcard);
+            }
+        }
+        Expr::BinaryOp { left, op, right } => {
+            let constraint = match op {
+                BinaryOperator::Eq => Constraint::Equality,
+                BinaryOperator::Lt | BinaryOperator::LtEq | BinaryOperator::Gt | BinaryOperator::GtEq => Constraint::Range,
+                _ => return,
+            };
+            record(left, Some(right), constraint);
+            record(right, Some(left), constraint);
+        }
+        Expr::Between { expr, negated: false, .. } => record(expr, None, Constraint::Range),
+        Expr::InList { expr, negated: false, .. } => record(expr, None, Constraint::Equality),
+        Expr::InSubquery { expr, subquery, negated: false } => {
+            record(expr, None, Constraint::Equality);
+            subqueries.push(subquery);
+        }
+        Expr::IsNull(expr) => record(expr, None, Constraint::Equality),
+        Expr::Exists(subquery) => subqueries.push(subquery),
+        _ => {}
+    }
+}
+
+/// The function name and column argument of a call like `lower(email)`
+fn wrapped_column(expr: &Expr) -> Option<(String, &Expr)> {
+    match expr {
+        Expr::Function(function) => match function.args.first() {
+            Some(FunctionArg::Unnamed(FunctionArgExpr::Expr(inner))) => Some((function.name.to_string(), inner)),
+            _ => None,
+        },
+        Expr::Nested(inner) => wrapped_column(inner),
+        _ => None,
+    }
+}
+
+/// Derives candidate column lists per table: the equality columns followed by
+/// the join columns, one range column or the ORDER BY columns, as sqlite3expert does
+fn candidate_columns(usage: &TableUsage) -> Vec<Vec<String>> {
+    let mut candidates: Vec<Vec<String>> = Vec::new();
+    let mut add = |columns: Vec<String>| {
+        if !columns.is_empty() && !candidates.contains(&columns) {
+            candidates.push(columns);
+        }
+    };
+
+    let mut with_range = usage.equality.clone();
+    if let Some(range) = usage.range.iter().find(|c| !usage.equality.contains(c)) {
+        with_range.push(range.clone());
+    }
+    add(with_range);
+
+    if !usage.join.is_empty() {
+        // Used when the table is the inner loop of the join
+        let mut with_join = usage.equality.clone();
+        for column in &usage.join {
+            push_unique(&mut with_join, column.clone());
+        }
+        add(with_join);
+    }
+
+    if !usage.order_by.is_empty() {
+        let mut with_order = usage.equality.clone();
+        for column in &usage.order_by {
+            push_unique(&mut with_order, column.clone());
+        }
+        add(with_order);
+    }
+
+    // Single-column indexes on the other range columns, in case one is more selective
+    for range in usage.range.iter().skip(1) {
+        add(vec![range.clone()]);
+    }
+    candidates
+}
+
+/// Column lists of the indexes that already exist on a table
+fn existing_indexes(conn: &Connection, table: &str) -> Result<Vec<Vec<String>>> {
+    let mut stmt = conn.prepare(
+        "SELECT il.name, ii.name FROM pragma_index_list(?1) il, pragma_index_info(il.name) ii ORDER BY il.name, ii.seqno",
+    )?;
+    let mut indexes: Vec<(String, Vec<String>)> = Vec::new();
+    let rows = stmt.query_map([table], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)))?;
+    for row in rows {
+        let (index, column) = row?;
+        match indexes.last_mut() {
+            Some((name, columns)) if *name == index => columns.extend(column),
+            _ => indexes.push((index, column.into_iter().collect())),
+        }
+    }
+    Ok(indexes.into_iter().map(|(_, columns)| columns).collect())
+}
+
+fn index_name(conn: &Connection, table: &str, columns: &[String]) -> Result<String> {
+    let base: String = std::iter::once(table)
+        .chain(columns.iter().map(String::as_str))
+        .map(|part| part.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("_");
+    let base = format!("idx_{}", base);
+
+    let mut name = base.clone();
+    let mut suffix = 1;
+    while conn
+        .query_row("SELECT 1 FROM sqlite_master WHERE name = ?1 COLLATE NOCASE", [&name], |_| Ok(()))
+        .optional()?
+        .is_some()
+    {
+        suffix += 1;
+        name = format!("{}_{}", base, suffix);
+    }
+    Ok(name)
+}
+
+/// Replays the schema of `conn` into a scratch database, with its statistics
+fn scratch_copy(conn: &Connection) -> Result<Connection> {
+    let scratch = Connection::open_in_memory()?;
+    scratch.execute_batch(&schema_ddl(conn)?)?;
+
+    let has_stat1 = conn
+        .query_row("SELECT 1 FROM sqlite_master WHERE name = 'sqlite_stat1'", [], |_| Ok(()))
+        .optional()?
+        .is_some();
+    if has_stat1 {
+        // ANALYZE on the empty copy creates sqlite_stat1, which is then filled from the real database
+        scratch.execute_batch("ANALYZE; DELETE FROM sqlite_stat1;")?;
+        let mut stmt = conn.prepare("SELECT tbl, idx, stat FROM sqlite_stat1")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, String>(2)?))
+        })?;
+        for row in rows {
+            let (tbl, idx, stat) = row?;
+            scratch.execute("INSERT INTO sqlite_stat1 (tbl, idx, stat) VALUES (?1, ?2, ?3)", (tbl, idx, stat))?;
+        }
+        scratch.execute_batch("ANALYZE sqlite_schema")?;
+    }
+    Ok(scratch)
+}
+
+fn parse_single_statement(query: &str) -> Result<Statement> {
+    let mut statements = Parser::parse_sql(&SQLiteDialect {}, query)
+        .map_err(|e| TuiqlError::Query(format!("Unable to parse query for index advice: {}", e)))?;
+    match statements.len() {
+        1 => Ok(statements.remove(0)),
+        _ => Err(TuiqlError::Query("Index advice expects exactly one statement".to_string())),
+    }
+}
+
+/// Constraints per table, sorted by table name
+fn statement_usage(conn: &Connection, statement: &Statement) -> Result<Vec<(String, TableUsage)>> {
+    let mut collector = Collector {
+        conn,
+        columns: HashMap::new(),
+        usage: HashMap::new(),
+    };
+    collector.statement(statement)?;
+    let mut usage: Vec<(String, TableUsage)> = collector.usage.into_iter().collect();
+    usage.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(usage)
+}
+
+/// How `query` constrains the columns of each table it reads, sorted by table name
+pub(crate) fn column_usage(conn: &Connection, query: &str) -> Result<Vec<(String, TableUsage)>> {
+    statement_usage(conn, &parse_single_statement(query.trim().trim_end_matches(';'))?)
+}
+
+/// Proposes indexes for `query` against the schema of `conn`.
+pub fn advise(conn: &Connection, query: &str) -> Result<Advice> {
+    let query = query.trim().trim_end_matches(';').to_string();
+    let statement = parse_single_statement(&query)?;
+
+    let scratch = scratch_copy(conn)?;
+    let before = query_plan(&scratch, &query)?;
+    let usage = statement_usage(&scratch, &statement)?;
+
+    let mut candidates = Vec::new();
+    for (table, usage) in &usage {
+        let existing = existing_indexes(&scratch, table)?;
+        for columns in candidate_columns(usage) {
+            // An existing index that starts with the same columns serves the query just as well
+            if existing.iter().any(|e| e.len() >= columns.len() && e[..columns.len()] == columns[..]) {
+                continue;
+            }
+            let candidate = IndexCandidate {
+                name: index_name(&scratch, table, &columns)?,
+                table: table.clone(),
+                columns,
+            };
+            scratch.execute_batch(&candidate.create_sql())?;
+            candidates.push(candidate);
+        }
+    }
+
+    let after = query_plan(&scratch, &query)?;
+    let mut indexes = Vec::new();
+    for node in &after {
+        for candidate in &candidates {
+            let used = node
+                .details
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .windows(2)
+                .any(|w| w[0] == "INDEX" && w[1] == candidate.name);
+            if used && !indexes.contains(candidate) {
+                indexes.push(candidate.clone());
+            }
+        }
+    }
+
+    Ok(Advice { query, before, after, indexes })
+}
+
+fn render_nodes(nodes: &[PlanNode], output: &mut String) {
+    if nodes.is_empty() {
+        output.push_str("  (no plan)\n");
+    }
+    for node in nodes {
+        let icon = if node.is_full_scan() { "⚠️ " } else { node.get_icon() };
+        output.push_str(&format!("  {}{} {}\n", "  ".repeat(node.level as usize), icon, node.details));
+    }
+}
+
+/// Formats the advice: plans before and after, and the numbered index proposals
+pub fn format_advice(advice: &Advice) -> String {
+    let mut output = String::from("🧭 Index advice\n");
+    output.push_str(&"=".repeat(60));
+    output.push('\n');
+    output.push_str(&format!("{}\n\n", advice.query));
+
+    output.push_str("Current plan:\n");
+    render_nodes(&advice.before, &mut output);
+
+    if advice.indexes.is_empty() {
+        output.push_str("\n✅ No new index would improve this query\n");
+        return output;
+    }
+
+    output.push_str("\nPlan with proposed indexes:\n");
+    render_nodes(&advice.after, &mut output);
+
+    output.push_str("\nProposed indexes:\n");
+    for (i, index) in advice.indexes.iter().enumerate() {
+        output.push_str(&format!("  {}. {};\n", i + 1, index.create_sql()));
+    }
+
+    let (before, after) = (advice.full_scans_before(), advice.full_scans_after());
+    if before > after {
+        output.push_str(&format!("\n💡 Full table scans: {} → {}\n", before, after));
+    }
+    output
+}
+
+/// Parses a selection of proposals such as "1 3", "1,2" or "all" into zero-based indexes
+pub fn parse_selection(input: &str, count: usize) -> Result<Vec<usize>> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("all") {
+        return Ok((0..count).collect());
+    }
+    let mut selected = Vec::new();
+    for part in input.split(|c: char| c == ',' || c.is_whitespace()).filter(|p| !p.is_empty()) {
+        let number: usize = part
+            .parse()
+            .map_err(|_| TuiqlError::Command(format!("Invalid index number '{}'", part)))?;
+        if number == 0 || number > count {
+            return Err(TuiqlError::Command(format!("No proposed index numbered {}", number)));
+        }
+        if !selected.contains(&(number - 1)) {
+            selected.push(number - 1);
+        }
+    }
+    Ok(selected)
+}
+
+/// Creates the selected indexes on the real database, all or none.
+///
+/// A savepoint is used so an open user transaction is neither required nor
+/// disturbed: on failure only the indexes created here are rolled back.
+pub fn create_indexes(conn: &Connection, indexes: &[&IndexCandidate]) -> Result<()> {
+    conn.execute_batch("SAVEPOINT tuiql_advise")?;
+    let created = indexes.iter().try_for_each(|index| conn.execute_batch(&index.create_sql()));
+    match created {
+        Ok(()) => conn.execute_batch("RELEASE tuiql_advise")?,
+        Err(e) => {
+            conn.execute_batch("ROLLBACK TO tuiql_advise; RELEASE tuiql_advise")?;
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
+/// Executes `:advise <query>` and returns the advice so the caller can offer to apply it.
+pub fn execute_advise_command(query: &str) -> Result<Advice> {
+    if query.trim().is_empty() {
+        return Err(TuiqlError::Command("Usage: :advise <query>".to_string()));
+    }
+
+    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::App("No database connection found".to_string()))?;
+    let state_guard = state_cell
+        .lock()
+        .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+    let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+
+    let advice = advise(conn, query)?;
+    print!("{}", format_advice(&advice));
+    Ok(advice)
+}
+
+/// Creates the proposals of `advice` picked by `selection` on the current connection.
+pub fn apply_advice(advice: &Advice, selection: &str) -> Result<Vec<String>> {
+    let chosen: Vec<&IndexCandidate> = parse_selection(selection, advice.indexes.len())?
+        .into_iter()
+        .map(|i| &advice.indexes[i])
+        .collect();
+    if chosen.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::App("No database connection found".to_string()))?;
+    let state_guard = state_cell
+        .lock()
+        .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+    let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+
+    create_indexes(conn, &chosen)?;
+    Ok(chosen.into_iter().map(|i| i.name.clone()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, country TEXT, created_at TEXT);
+             CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER, status TEXT, total REAL);
+             CREATE INDEX idx_orders_status ON orders(status);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_advise_equality_and_range() {
+        let conn = setup();
+        let advice = advise(&conn, "SELECT * FROM users WHERE country = 'NL' AND created_at > '2024-01-01';").unwrap();
+
+        assert_eq!(advice.full_scans_before(), 1);
+        assert_eq!(advice.full_scans_after(), 0);
+        assert_eq!(advice.indexes.len(), 1);
+        assert_eq!(advice.indexes[0].table, "users");
+        assert_eq!(advice.indexes[0].columns, vec!["country", "created_at"]);
+        assert_eq!(
+            advice.indexes[0].create_sql(),
+            "CREATE INDEX \"idx_users_country_created_at\" ON \"users\"(\"country\", \"created_at\")"
+        );
+
+        // The real database is untouched
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE type = 'index'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_advise_join_and_order_by() {
+        let conn = setup();
+        let advice = advise(
+            &conn,
+            "SELECT u.email, o.total FROM users u JOIN orders o ON o.user_id = u.id WHERE u.email = ?",
+        )
+        .unwrap();
+        let proposed: Vec<(&str, Vec<String>)> =
+            advice.indexes.iter().map(|i| (i.table.as_str(), i.columns.clone())).collect();
+        assert!(proposed.contains(&("users", vec!["email".to_string()])));
+        assert!(proposed.contains(&("orders", vec!["user_id".to_string()])));
+
+        let advice = advise(&conn, "SELECT * FROM users ORDER BY created_at").unwrap();
+        assert_eq!(advice.indexes[0].columns, vec!["created_at"]);
+        assert!(format_advice(&advice).contains("Proposed indexes:"));
+
+        // Already served by an existing index
+        let advice = advise(&conn, "SELECT * FROM orders WHERE status = 'open'").unwrap();
+        assert!(advice.indexes.is_empty());
+        assert!(format_advice(&advice).contains("No new index would improve this query"));
+    }
+
+    #[test]
+    fn test_selection_and_create() {
+        let conn = setup();
+        assert_eq!(parse_selection("all", 3).unwrap(), vec![0, 1, 2]);
+        assert_eq!(parse_selection("2, 1 2", 3).unwrap(), vec![1, 0]);
+        assert!(parse_selection("", 3).unwrap().is_empty());
+        assert!(parse_selection("4", 3).is_err());
+        assert!(parse_selection("x", 3).is_err());
+
+        let advice = advise(&conn, "DELETE FROM orders WHERE user_id = 7").unwrap();
+        assert_eq!(advice.indexes.len(), 1);
+        create_indexes(&conn, &[&advice.indexes[0]]).unwrap();
+        let plan = query_plan(&conn, "DELETE FROM orders WHERE user_id = 7").unwrap();
+        assert!(plan.iter().any(|n| n.details.contains("idx_orders_user_id")));
+
+        assert!(advise(&conn, "CREATE TABLE t (x)").is_err());
+        assert!(advise(&conn, "SELEC nonsense").is_err());
+    }
+
+    #[test]
+    fn test_create_indexes_keeps_user_transaction() {
+        let conn = setup();
+        conn.execute_batch("BEGIN; INSERT INTO users (email, country) VALUES ('a', 'NL'), ('b', 'NL');").unwrap();
+
+        let good = IndexCandidate { name: "idx_users_country".to_string(), table: "users".to_string(), columns: vec!["country".to_string()] };
+        let bad = IndexCandidate { name: "idx_bad".to_string(), table: "missing".to_string(), columns: vec!["id".to_string()] };
+        assert!(create_indexes(&conn, &[&good, &bad]).is_err());
+
+        // The failed call rolled back its own index but not the user's rows
+        assert!(!conn.is_autocommit());
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM users WHERE email IN ('a', 'b')", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 2);
+        let indexes: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'idx_users_country'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(indexes, 0);
+
+        create_indexes(&conn, &[&good]).unwrap();
+        conn.execute_batch("COMMIT").unwrap();
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM users WHERE email IN ('a', 'b')", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 2);
+    }
+}
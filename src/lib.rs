@@ -3,21 +3,30 @@ pub mod core;
 pub mod config;
 
 // Feature-specific modules
+pub mod analyze;
+pub mod bench;
 pub mod catalog;
 pub mod command_palette;
 pub mod db;
 pub mod diff;
+pub mod fkcheck;
 pub mod fts5;
+pub mod health;
+pub mod index_advisor;
 pub mod json1;
 pub mod json_viewer;
+pub mod migrate;
 pub mod plan;
 pub mod plugins;
+pub mod profile;
 pub mod query_editor;
 pub mod record_inspector;
 pub mod repl;
 pub mod results_grid;
 pub mod schema_map;
+pub mod schema_lint;
 pub mod schema_navigator;
+pub mod space;
 pub mod sql;
 pub mod sql_completer;
 pub mod storage;
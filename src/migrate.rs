@@ -0,0 +1,802 @@
+/*
+ * Migration Runner Module
+ *
+ * This module applies versioned schema migrations from a directory of
+ * numbered SQL files, e.g.:
+ *
+ *   migrations/0001_create_users.up.sql
+ *   migrations/0001_create_users.down.sql
+ *   migrations/0002_add_email.up.sql
+ *
+ * Features:
+ * - Progress tracked in a `_tuiql_migrations` table or in `PRAGMA user_version`
+ * - `status`, `up [n]`, `down [n]` and `redo` actions
+ * - Each step runs in its own transaction and is rolled back on failure
+ * - Checksums detect migration files edited after they were applied
+ */
+
+use crate::core::db::schema::ddl_fingerprint;
+use crate::core::{Result, TuiqlError};
+use crate::db;
+use regex::Regex;
+use rusqlite::{params, Connection};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// Name of the bookkeeping table used by `Tracking::Table`
+pub const MIGRATIONS_TABLE: &str = "_tuiql_migrations";
+
+/// Directory searched for migrations when `--dir` is not given
+pub const DEFAULT_MIGRATIONS_DIR: &str = "migrations";
+
+/// How applied migrations are recorded in the database
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tracking {
+    /// One row per applied migration in `_tuiql_migrations`, including its checksum
+    Table,
+    /// The highest applied version is stored in `PRAGMA user_version`
+    UserVersion,
+}
+
+impl Tracking {
+    /// Parses a tracking mode name as accepted by `--tracking`
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "table" => Ok(Tracking::Table),
+            "user_version" | "user-version" => Ok(Tracking::UserVersion),
+            _ => Err(TuiqlError::Command(format!(
+                "Unknown migration tracking mode: '{}'. Supported modes: table, user_version",
+                name
+            ))),
+        }
+    }
+}
+
+/// A migration loaded from disk
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub version: u32,
+    pub name: String,
+    pub up_sql: String,
+    pub down_sql: Option<String>,
+    /// Checksum of the up script, see `checksum`
+    pub checksum: String,
+}
+
+/// State of a single migration relative to the database
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the file on disk no longer matches the recorded checksum
+    Modified,
+    /// Recorded as applied, but no file exists for it
+    Missing,
+}
+
+/// Status line for one migration, as shown by `:migrate status`
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: u32,
+    pub name: String,
+    pub state: MigrationState,
+    /// Checksum of the file on disk, if there is one
+    pub checksum: Option<String>,
+    /// Checksum recorded when the migration was applied (table tracking only)
+    pub applied_checksum: Option<String>,
+    pub applied_at: Option<String>,
+}
+
+/// A migration recorded as applied in the database
+#[derive(Debug, Clone)]
+struct AppliedMigration {
+    version: u32,
+    name: String,
+    checksum: Option<String>,
+    applied_at: Option<String>,
+}
+
+/// Computes the checksum stored for a migration script
+///
+/// Uses 64-bit FNV-1a over the script with line endings normalized, so a
+/// checkout with CRLF line endings does not count as a modification.
+pub fn checksum(sql: &str) -> String {
+    ddl_fingerprint(&sql.replace("\r\n", "\n"))
+}
+
+/// Loads all migrations from `dir`, sorted by version
+///
+/// Files that do not match `NNNN_name.up.sql` or `NNNN_name.down.sql` are ignored.
+/// Every version must have an up script, and all files for a version must share a name.
+pub fn load_migrations(dir: &Path) -> Result<Vec<Migration>> {
+    let pattern = Regex::new(r"^(\d+)_(.+)\.(up|down)\.sql$").expect("valid migration file pattern");
+    let mut scripts: BTreeMap<u32, (String, Option<String>, Option<String>)> = BTreeMap::new();
+
+    let entries = fs::read_dir(dir).map_err(|e| {
+        TuiqlError::Command(format!(
+            "Cannot read migrations directory '{}': {}",
+            dir.display(),
+            e
+        ))
+    })?;
+
+    for entry in entries {
+        let path = entry?.path();
+        let file_name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        let captures = match pattern.captures(&file_name) {
+            Some(captures) => captures,
+            None => continue,
+        };
+
+        let version: u32 = captures[1].parse().map_err(|_| {
+            TuiqlError::Command(format!("Migration version in '{}' is out of range", file_name))
+        })?;
+        let name = captures[2].to_string();
+        let sql = fs::read_to_string(&path)?;
+
+        let entry = scripts.entry(version).or_insert_with(|| (name.clone(), None, None));
+        if entry.0 != name {
+            return Err(TuiqlError::Command(format!(
+                "Migration version {:04} is used by both '{}' and '{}'",
+                version, entry.0, name
+            )));
+        }
+        let slot = if &captures[3] == "up" { &mut entry.1 } else { &mut entry.2 };
+        if slot.is_some() {
+            return Err(TuiqlError::Command(format!("Duplicate migration file '{}'", file_name)));
+        }
+        *slot = Some(sql);
+    }
+
+    scripts
+        .into_iter()
+        .map(|(version, (name, up_sql, down_sql))| {
+            let up_sql = up_sql.ok_or_else(|| {
+                TuiqlError::Command(format!(
+                    "Migration {:04}_{} has no .up.sql file",
+                    version, name
+                ))
+            })?;
+            Ok(Migration {
+                version,
+                name,
+                checksum: checksum(&up_sql),
+                up_sql,
+                down_sql,
+            })
+        })
+        .collect()
+}
+
+/// Creates the `_tuiql_migrations` table if it does not exist yet
+fn ensure_migrations_table(conn: &Connection) -> Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        MIGRATIONS_TABLE
+    ))?;
+    Ok(())
+}
+
+/// Reads the current `PRAGMA user_version`
+fn user_version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+/// Returns the migrations recorded as applied, sorted by version
+fn applied_migrations(
+    conn: &Connection,
+    migrations: &[Migration],
+    tracking: Tracking,
+) -> Result<Vec<AppliedMigration>> {
+    match tracking {
+        Tracking::Table => {
+            let exists: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+                params![MIGRATIONS_TABLE],
+                |row| row.get(0),
+            )?;
+            if !exists {
+                return Ok(Vec::new());
+            }
+
+            let mut stmt = conn.prepare(&format!(
+                "SELECT version, name, checksum, applied_at FROM {} ORDER BY version",
+                MIGRATIONS_TABLE
+            ))?;
+            let rows = stmt.query_map([], |row| {
+                Ok(AppliedMigration {
+                    version: row.get(0)?,
+                    name: row.get(1)?,
+                    checksum: row.get(2)?,
+                    applied_at: row.get(3)?,
+                })
+            })?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        }
+        Tracking::UserVersion => {
+            // user_version only records a high-water mark, so every known
+            // migration at or below it counts as applied
+            let current = user_version(conn)?;
+            Ok(migrations
+                .iter()
+                .filter(|m| m.version <= current)
+                .map(|m| AppliedMigration {
+                    version: m.version,
+                    name: m.name.clone(),
+                    checksum: None,
+                    applied_at: None,
+                })
+                .collect())
+        }
+    }
+}
+
+/// Lists every known migration, applied or not, with its state and checksums
+pub fn status(conn: &Connection, migrations: &[Migration], tracking: Tracking) -> Result<Vec<MigrationStatus>> {
+    let applied: BTreeMap<u32, AppliedMigration> = applied_migrations(conn, migrations, tracking)?
+        .into_iter()
+        .map(|a| (a.version, a))
+        .collect();
+    let files: BTreeMap<u32, &Migration> = migrations.iter().map(|m| (m.version, m)).collect();
+    let versions: BTreeSet<u32> = applied.keys().chain(files.keys()).copied().collect();
+
+    Ok(versions
+        .into_iter()
+        .map(|version| {
+            let file = files.get(&version);
+            let record = applied.get(&version);
+            let state = match (file, record) {
+                (Some(_), None) => MigrationState::Pending,
+                (None, _) => MigrationState::Missing,
+                (Some(file), Some(record)) => match &record.checksum {
+                    Some(recorded) if *recorded != file.checksum => MigrationState::Modified,
+                    _ => MigrationState::Applied,
+                },
+            };
+            MigrationStatus {
+                version,
+                name: file
+                    .map(|m| m.name.clone())
+                    .or_else(|| record.map(|r| r.name.clone()))
+                    .unwrap_or_default(),
+                state,
+                checksum: file.map(|m| m.checksum.clone()),
+                applied_checksum: record.and_then(|r| r.checksum.clone()),
+                applied_at: record.and_then(|r| r.applied_at.clone()),
+            }
+        })
+        .collect())
+}
+
+/// Applies up to `count` pending migrations (all of them when `None`), oldest first
+///
+/// Each migration runs in its own transaction together with its bookkeeping, so a
+/// failing script leaves the database at the last successfully applied version.
+/// Returns the versions that were applied.
+pub fn up(conn: &Connection, migrations: &[Migration], tracking: Tracking, count: Option<usize>) -> Result<Vec<u32>> {
+    if tracking == Tracking::Table {
+        ensure_migrations_table(conn)?;
+    }
+    let applied: BTreeSet<u32> = applied_migrations(conn, migrations, tracking)?
+        .iter()
+        .map(|a| a.version)
+        .collect();
+
+    let pending: Vec<&Migration> = migrations
+        .iter()
+        .filter(|m| !applied.contains(&m.version))
+        .take(count.unwrap_or(usize::MAX))
+        .collect();
+
+    if tracking == Tracking::UserVersion && !pending.is_empty() {
+        // Every file at or below user_version counts as applied, which is only sound
+        // when user_version was set by one of these migrations
+        let current = user_version(conn)?;
+        if current > 0 && !migrations.iter().any(|m| m.version == current) {
+            return Err(TuiqlError::Command(format!(
+                "user_version {} does not match any migration file, so migrations at or below it cannot be told apart from applied ones; use --tracking table",
+                current
+            )));
+        }
+    }
+
+    let mut done = Vec::new();
+    for migration in pending {
+        apply(conn, migration, tracking)?;
+        done.push(migration.version);
+    }
+
+    Ok(done)
+}
+
+/// Runs one migration's up script and records it, in a single transaction
+fn apply(conn: &Connection, migration: &Migration, tracking: Tracking) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(&migration.up_sql).map_err(|e| {
+        TuiqlError::Transaction(format!(
+            "Migration {:04}_{} failed and was rolled back: {}",
+            migration.version, migration.name, e
+        ))
+    })?;
+    match tracking {
+        Tracking::Table => {
+            tx.execute(
+                &format!(
+                    "INSERT INTO {} (version, name, checksum) VALUES (?1, ?2, ?3)",
+                    MIGRATIONS_TABLE
+                ),
+                params![migration.version, migration.name, migration.checksum],
+            )?;
+        }
+        Tracking::UserVersion => {
+            tx.execute_batch(&format!("PRAGMA user_version = {}", migration.version))?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Reverts up to `count` applied migrations (one when `None`), newest first
+///
+/// Every migration being reverted needs a `.down.sql` file. Returns the versions
+/// that were reverted.
+pub fn down(conn: &Connection, migrations: &[Migration], tracking: Tracking, count: Option<usize>) -> Result<Vec<u32>> {
+    let applied = applied_migrations(conn, migrations, tracking)?;
+    let files: BTreeMap<u32, &Migration> = migrations.iter().map(|m| (m.version, m)).collect();
+
+    let mut done = Vec::new();
+    for record in applied.iter().rev().take(count.unwrap_or(1)) {
+        let migration = files.get(&record.version).ok_or_else(|| {
+            TuiqlError::Command(format!(
+                "Cannot revert {:04}_{}: its migration files are missing",
+                record.version, record.name
+            ))
+        })?;
+        let down_sql = migration.down_sql.as_ref().ok_or_else(|| {
+            TuiqlError::Command(format!(
+                "Cannot revert {:04}_{}: no .down.sql file",
+                migration.version, migration.name
+            ))
+        })?;
+
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(down_sql).map_err(|e| {
+            TuiqlError::Transaction(format!(
+                "Reverting migration {:04}_{} failed and was rolled back: {}",
+                migration.version, migration.name, e
+            ))
+        })?;
+        match tracking {
+            Tracking::Table => {
+                tx.execute(
+                    &format!("DELETE FROM {} WHERE version = ?1", MIGRATIONS_TABLE),
+                    params![migration.version],
+                )?;
+            }
+            Tracking::UserVersion => {
+                let previous = migrations
+                    .iter()
+                    .map(|m| m.version)
+                    .filter(|v| *v < migration.version)
+                    .max()
+                    .unwrap_or(0);
+                tx.execute_batch(&format!("PRAGMA user_version = {}", previous))?;
+            }
+        }
+        tx.commit()?;
+        done.push(migration.version);
+    }
+
+    Ok(done)
+}
+
+/// Reverts the newest applied migration and applies that same migration again
+///
+/// Older pending migrations are left pending.
+pub fn redo(conn: &Connection, migrations: &[Migration], tracking: Tracking) -> Result<Option<u32>> {
+    let reverted = down(conn, migrations, tracking, Some(1))?;
+    let Some(&version) = reverted.first() else {
+        return Ok(None);
+    };
+    // down only reverts migrations that have files
+    if let Some(migration) = migrations.iter().find(|m| m.version == version) {
+        apply(conn, migration, tracking)?;
+    }
+    Ok(Some(version))
+}
+
+/// Formats migration status lines for display in the REPL
+pub fn format_status(statuses: &[MigrationStatus], tracking: Tracking) -> String {
+    let tracking_name = match tracking {
+        Tracking::Table => MIGRATIONS_TABLE,
+        Tracking::UserVersion => "PRAGMA user_version",
+    };
+    let mut output = format!("📦 Migrations (tracked in {})\n", tracking_name);
+
+    if statuses.is_empty() {
+        output.push_str("  No migrations found.\n");
+        return output;
+    }
+
+    for status in statuses {
+        let (icon, label) = match status.state {
+            MigrationState::Applied => ("✅", "applied"),
+            MigrationState::Pending => ("⏳", "pending"),
+            MigrationState::Modified => ("⚠️ ", "modified"),
+            MigrationState::Missing => ("❓", "missing"),
+        };
+        let mut line = format!(
+            "  {} {:04} {:<30} {:<9} {}",
+            icon,
+            status.version,
+            status.name,
+            label,
+            status.checksum.as_deref().unwrap_or("-")
+        );
+        if status.state == MigrationState::Modified {
+            if let Some(recorded) = &status.applied_checksum {
+                line.push_str(&format!(" (applied as {})", recorded));
+            }
+        }
+        if let Some(applied_at) = &status.applied_at {
+            line.push_str(&format!(" at {}", applied_at));
+        }
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+
+    let count = |state: MigrationState| statuses.iter().filter(|s| s.state == state).count();
+    output.push_str(&format!(
+        "\n{} applied, {} pending",
+        count(MigrationState::Applied) + count(MigrationState::Modified),
+        count(MigrationState::Pending)
+    ));
+    if count(MigrationState::Modified) > 0 {
+        output.push_str(&format!(", {} modified since applied", count(MigrationState::Modified)));
+    }
+    if count(MigrationState::Missing) > 0 {
+        output.push_str(&format!(", {} missing on disk", count(MigrationState::Missing)));
+    }
+    output.push('\n');
+    output
+}
+
+/// Help text for the `:migrate` command
+pub fn migrate_help() -> String {
+    "📦 Migration Runner\n\n\
+          Applies numbered NNNN_name.up.sql / NNNN_name.down.sql files in version order.\n\n\
+          🔧 COMMAND SYNTAX:\n\
+          • :migrate status - Show applied and pending migrations with checksums\n\
+          • :migrate up [n] - Apply the next n pending migrations (default: all)\n\
+          • :migrate down [n] - Revert the last n applied migrations (default: 1)\n\
+          • :migrate redo - Revert and re-apply the last migration\n\
+          • :migrate help - Show this help text\n\n\
+          ⚙️ OPTIONS:\n\
+          • --dir <path> - Migrations directory (default: ./migrations)\n\
+          • --tracking table|user_version - Record progress in _tuiql_migrations (default) or PRAGMA user_version\n\n\
+          💡 Each step runs in its own transaction and is rolled back if it fails.".to_string()
+}
+
+/// Executes a `:migrate` command against the current database connection
+///
+/// # Arguments
+///
+/// * `command` - Arguments after `:migrate`, e.g. "up 2 --dir db/migrations"
+pub fn execute_migrate_command(command: &str) -> Result<()> {
+    let mut positional = Vec::new();
+    let mut dir = DEFAULT_MIGRATIONS_DIR.to_string();
+    let mut tracking = Tracking::Table;
+
+    let mut args = command.split_whitespace();
+    while let Some(arg) = args.next() {
+        match arg {
+            "--dir" => {
+                dir = args
+                    .next()
+                    .ok_or_else(|| TuiqlError::Command("--dir requires a path".to_string()))?
+                    .to_string();
+            }
+            "--tracking" => {
+                let mode = args
+                    .next()
+                    .ok_or_else(|| TuiqlError::Command("--tracking requires a mode".to_string()))?;
+                tracking = Tracking::parse(mode)?;
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let action = positional.first().copied().unwrap_or("status");
+    if action == "help" {
+        println!("{}", migrate_help());
+        return Ok(());
+    }
+    let count = match positional.get(1) {
+        Some(n) => Some(n.parse::<usize>().map_err(|_| {
+            TuiqlError::Command(format!("Invalid migration count: '{}'", n))
+        })?),
+        None => None,
+    };
+
+    let migrations = load_migrations(Path::new(&dir))?;
+
+    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::App("No database connection found".to_string()))?;
+    let state_guard = state_cell
+        .lock()
+        .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+    let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+
+    match action {
+        "status" => {}
+        "up" => {
+            let applied = up(conn, &migrations, tracking, count)?;
+            if applied.is_empty() {
+                println!("✅ Database is up to date");
+            }
+            for version in applied {
+                println!("⬆️  Applied migration {:04}", version);
+            }
+        }
+        "down" => {
+            let reverted = down(conn, &migrations, tracking, count)?;
+            if reverted.is_empty() {
+                println!("ℹ️  No applied migrations to revert");
+            }
+            for version in reverted {
+                println!("⬇️  Reverted migration {:04}", version);
+            }
+        }
+        "redo" => match redo(conn, &migrations, tracking)? {
+            Some(version) => println!("🔁 Re-applied migration {:04}", version),
+            None => println!("ℹ️  No applied migrations to redo"),
+        },
+        other => {
+            return Err(TuiqlError::Command(format!(
+                "Unknown migrate action: '{}'. Use status, up [n], down [n] or redo",
+                other
+            )));
+        }
+    }
+
+    println!("{}", format_status(&status(conn, &migrations, tracking)?, tracking));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_migrations(files: &[(&str, &str)]) -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, sql) in files {
+            fs::write(dir.path().join(name), sql).unwrap();
+        }
+        dir
+    }
+
+    fn sample_migrations() -> TempDir {
+        write_migrations(&[
+            ("0001_create_users.up.sql", "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);"),
+            ("0001_create_users.down.sql", "DROP TABLE users;"),
+            (
+                "0002_add_email.up.sql",
+                "ALTER TABLE users ADD COLUMN email TEXT;\nCREATE INDEX idx_users_email ON users(email);",
+            ),
+            ("0002_add_email.down.sql", "DROP INDEX idx_users_email;\nALTER TABLE users DROP COLUMN email;"),
+            ("0003_create_posts.up.sql", "CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users(id));"),
+            ("0003_create_posts.down.sql", "DROP TABLE posts;"),
+            ("README.md", "not a migration"),
+        ])
+    }
+
+    fn table_exists(conn: &Connection, name: &str) -> bool {
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            params![name],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    fn states(conn: &Connection, migrations: &[Migration], tracking: Tracking) -> Vec<MigrationState> {
+        status(conn, migrations, tracking).unwrap().iter().map(|s| s.state).collect()
+    }
+
+    #[test]
+    fn test_load_migrations() {
+        let dir = sample_migrations();
+        let migrations = load_migrations(dir.path()).unwrap();
+
+        assert_eq!(migrations.len(), 3);
+        assert_eq!(migrations[0].version, 1);
+        assert_eq!(migrations[0].name, "create_users");
+        assert!(migrations[0].down_sql.is_some());
+        assert_eq!(migrations[2].name, "create_posts");
+        assert_eq!(migrations[0].checksum, checksum(&migrations[0].up_sql));
+    }
+
+    #[test]
+    fn test_load_migrations_requires_up_script() {
+        let dir = write_migrations(&[("0001_orphan.down.sql", "DROP TABLE x;")]);
+        assert!(matches!(load_migrations(dir.path()), Err(TuiqlError::Command(_))));
+    }
+
+    #[test]
+    fn test_load_migrations_rejects_conflicting_names() {
+        let dir = write_migrations(&[
+            ("0001_one.up.sql", "SELECT 1;"),
+            ("0001_two.up.sql", "SELECT 2;"),
+        ]);
+        match load_migrations(dir.path()) {
+            Err(TuiqlError::Command(msg)) => assert!(msg.contains("0001")),
+            other => panic!("Expected command error, got {:?}", other.map(|m| m.len())),
+        }
+    }
+
+    #[test]
+    fn test_checksum_ignores_line_endings() {
+        assert_eq!(checksum("SELECT 1;\nSELECT 2;"), checksum("SELECT 1;\r\nSELECT 2;"));
+        assert_ne!(checksum("SELECT 1;"), checksum("SELECT 2;"));
+        assert_eq!(checksum("").len(), 16);
+    }
+
+    #[test]
+    fn test_up_down_and_redo_with_table_tracking() {
+        let dir = sample_migrations();
+        let migrations = load_migrations(dir.path()).unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+
+        assert_eq!(
+            states(&conn, &migrations, Tracking::Table),
+            vec![MigrationState::Pending; 3]
+        );
+
+        assert_eq!(up(&conn, &migrations, Tracking::Table, Some(2)).unwrap(), vec![1, 2]);
+        assert_eq!(
+            states(&conn, &migrations, Tracking::Table),
+            vec![MigrationState::Applied, MigrationState::Applied, MigrationState::Pending]
+        );
+
+        assert_eq!(up(&conn, &migrations, Tracking::Table, None).unwrap(), vec![3]);
+        assert!(table_exists(&conn, "posts"));
+        assert!(up(&conn, &migrations, Tracking::Table, None).unwrap().is_empty());
+
+        assert_eq!(down(&conn, &migrations, Tracking::Table, None).unwrap(), vec![3]);
+        assert!(!table_exists(&conn, "posts"));
+
+        assert_eq!(redo(&conn, &migrations, Tracking::Table).unwrap(), Some(2));
+        assert_eq!(
+            states(&conn, &migrations, Tracking::Table),
+            vec![MigrationState::Applied, MigrationState::Applied, MigrationState::Pending]
+        );
+
+        assert_eq!(down(&conn, &migrations, Tracking::Table, Some(5)).unwrap(), vec![2, 1]);
+        assert!(!table_exists(&conn, "users"));
+    }
+
+    #[test]
+    fn test_redo_reapplies_the_reverted_migration() {
+        let dir = sample_migrations();
+        let migrations = load_migrations(dir.path()).unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+
+        // 0002 is pending below the newest applied migration, e.g. after merging a branch
+        let without_second: Vec<Migration> = migrations.iter().filter(|m| m.version != 2).cloned().collect();
+        assert_eq!(up(&conn, &without_second, Tracking::Table, None).unwrap(), vec![1, 3]);
+
+        assert_eq!(redo(&conn, &migrations, Tracking::Table).unwrap(), Some(3));
+        assert!(table_exists(&conn, "posts"));
+        assert_eq!(
+            states(&conn, &migrations, Tracking::Table),
+            vec![MigrationState::Applied, MigrationState::Pending, MigrationState::Applied]
+        );
+    }
+
+    #[test]
+    fn test_user_version_tracking() {
+        let dir = sample_migrations();
+        let migrations = load_migrations(dir.path()).unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+
+        up(&conn, &migrations, Tracking::UserVersion, None).unwrap();
+        assert_eq!(user_version(&conn).unwrap(), 3);
+        assert!(!table_exists(&conn, MIGRATIONS_TABLE));
+
+        down(&conn, &migrations, Tracking::UserVersion, Some(2)).unwrap();
+        assert_eq!(user_version(&conn).unwrap(), 1);
+        assert_eq!(
+            states(&conn, &migrations, Tracking::UserVersion),
+            vec![MigrationState::Applied, MigrationState::Pending, MigrationState::Pending]
+        );
+    }
+
+    #[test]
+    fn test_user_version_without_matching_migration() {
+        let dir = sample_migrations();
+        let migrations = load_migrations(dir.path()).unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT); PRAGMA user_version = 1;").unwrap();
+        let gapped: Vec<Migration> = migrations.iter().filter(|m| m.version != 1).cloned().collect();
+
+        // user_version 1 was not set by any of these files, so 0002 may or may not have run
+        let err = up(&conn, &gapped, Tracking::UserVersion, None).unwrap_err();
+        assert!(err.to_string().contains("user_version 1 does not match any migration file"));
+        assert_eq!(user_version(&conn).unwrap(), 1);
+        assert!(!table_exists(&conn, "posts"));
+
+        // With the matching file present the pending migrations apply
+        assert_eq!(up(&conn, &migrations, Tracking::UserVersion, None).unwrap(), vec![2, 3]);
+        assert_eq!(user_version(&conn).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_failed_migration_is_rolled_back() {
+        let dir = write_migrations(&[
+            ("0001_good.up.sql", "CREATE TABLE a (id INTEGER);"),
+            ("0002_bad.up.sql", "CREATE TABLE b (id INTEGER);\nINSERT INTO missing_table VALUES (1);"),
+        ]);
+        let migrations = load_migrations(dir.path()).unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+
+        let result = up(&conn, &migrations, Tracking::Table, None);
+        match result {
+            Err(TuiqlError::Transaction(msg)) => assert!(msg.contains("0002_bad")),
+            other => panic!("Expected transaction error, got {:?}", other),
+        }
+
+        assert!(table_exists(&conn, "a"));
+        assert!(!table_exists(&conn, "b"));
+        assert_eq!(
+            states(&conn, &migrations, Tracking::Table),
+            vec![MigrationState::Applied, MigrationState::Pending]
+        );
+    }
+
+    #[test]
+    fn test_status_detects_modified_and_missing_migrations() {
+        let dir = sample_migrations();
+        let conn = Connection::open_in_memory().unwrap();
+        up(&conn, &load_migrations(dir.path()).unwrap(), Tracking::Table, Some(2)).unwrap();
+
+        fs::write(dir.path().join("0001_create_users.up.sql"), "CREATE TABLE users (id INTEGER);").unwrap();
+        fs::remove_file(dir.path().join("0002_add_email.up.sql")).unwrap();
+        fs::remove_file(dir.path().join("0002_add_email.down.sql")).unwrap();
+        let migrations = load_migrations(dir.path()).unwrap();
+
+        let statuses = status(&conn, &migrations, Tracking::Table).unwrap();
+        assert_eq!(statuses[0].state, MigrationState::Modified);
+        assert_ne!(statuses[0].checksum, statuses[0].applied_checksum);
+        assert_eq!(statuses[1].state, MigrationState::Missing);
+        assert_eq!(statuses[1].name, "add_email");
+        assert_eq!(statuses[2].state, MigrationState::Pending);
+
+        let output = format_status(&statuses, Tracking::Table);
+        assert!(output.contains("0001 create_users"));
+        assert!(output.contains("modified"));
+        assert!(output.contains("1 modified since applied"));
+        assert!(output.contains("1 missing on disk"));
+
+        // A missing migration cannot be reverted
+        assert!(matches!(
+            down(&conn, &migrations, Tracking::Table, None),
+            Err(TuiqlError::Command(_))
+        ));
+    }
+
+    #[test]
+    fn test_tracking_parse() {
+        assert_eq!(Tracking::parse("table").unwrap(), Tracking::Table);
+        assert_eq!(Tracking::parse("user_version").unwrap(), Tracking::UserVersion);
+        assert!(Tracking::parse("file").is_err());
+    }
+}
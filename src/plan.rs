@@ -4,19 +4,79 @@
 // visual representation of the plan. It highlights index usage and optimizes
 // the visualization for better comprehension of query execution.
 
+use crate::analyze;
+use crate::catalog;
+use crate::core::db::schema::{self, Schema};
 use crate::core::{Result, TuiqlError};
 use crate::db;
-
-#[derive(Debug, Clone)]
+use crate::index_advisor;
+use crate::storage::{HistoryEntry, Storage};
+use rusqlite::Connection;
+#[cfg(feature = "scanstatus")]
+use rusqlite::ffi;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "scanstatus")]
+use std::ffi::{CStr, CString};
+#[cfg(feature = "scanstatus")]
+use std::os::raw::{c_char, c_int, c_void};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// Ratio between estimated and actual rows per loop at which a loop is highlighted
+pub const ESTIMATE_DIVERGENCE_WARN: f64 = 10.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlanNode {
     pub id: i32,
     pub parent: i32,
     pub details: String,
     pub selectid: i32,
     pub level: i32,
+    /// Runtime statistics, present when the plan was collected by running the query
+    pub stats: Option<ScanStats>,
+}
+
+/// Runtime statistics of one plan loop, from `sqlite3_stmt_scanstatus_v2`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScanStats {
+    /// Number of times the loop ran
+    pub loops: i64,
+    /// Rows visited across all runs of the loop
+    pub rows_visited: i64,
+    /// Planner estimate of rows output per run of the loop
+    pub estimated_rows: f64,
+}
+
+impl ScanStats {
+    /// Actual rows visited per run of the loop
+    pub fn actual_rows(&self) -> f64 {
+        if self.loops == 0 {
+            0.0
+        } else {
+            self.rows_visited as f64 / self.loops as f64
+        }
+    }
+
+    /// Factor by which the estimate and the actual rows per loop differ, at least 1
+    pub fn estimate_divergence(&self) -> f64 {
+        let actual = self.actual_rows().max(1.0);
+        let estimated = self.estimated_rows.max(1.0);
+        actual.max(estimated) / actual.min(estimated)
+    }
 }
 
-#[derive(Debug)]
+/// Plan of a query that was executed with scan status collection
+#[derive(Debug, Clone)]
+pub struct ScanStatusRun {
+    /// Plan elements as reported by scanstatus, with levels assigned
+    pub nodes: Vec<PlanNode>,
+    pub rows_returned: u64,
+    pub execution_time_ms: u128,
+    /// Statement counters, collected when per-loop statistics are not available
+    pub counters: Option<db::StatementStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct PlanStats {
     pub table_row_counts: std::collections::HashMap<String, i64>,
     pub execution_time_ms: Option<u128>,
@@ -41,6 +101,7 @@ impl PlanNode {
             details,
             selectid,
             level: 0, // Will be set when building the tree
+            stats: None,
         }
     }
 
@@ -49,8 +110,29 @@ impl PlanNode {
     }
 
     pub fn is_full_scan(&self) -> bool {
-        self.details.to_uppercase().contains("SCAN TABLE") &&
-        !self.details.to_uppercase().contains("USING INDEX")
+        let details = self.details.to_uppercase();
+        // SQLite 3.36 dropped the TABLE keyword: "SCAN TABLE users" became "SCAN users"
+        let scans_table = details.contains("SCAN TABLE")
+            || (details.starts_with("SCAN ") && !details.starts_with("SCAN CONSTANT ROW") && !details.starts_with("SCAN ("));
+        scans_table && !details.contains("USING INDEX")
+    }
+
+    /// Index the loop uses: an index name, `INTEGER PRIMARY KEY`, `PRIMARY KEY`
+    /// or `AUTOMATIC INDEX` for an index SQLite builds for the statement
+    pub fn index_used(&self) -> Option<String> {
+        let upper = self.details.to_ascii_uppercase();
+        let start = upper.find(" USING ")? + " USING ".len();
+        let using = &upper[start..];
+        if using.starts_with("INTEGER PRIMARY KEY") {
+            Some("INTEGER PRIMARY KEY".to_string())
+        } else if using.starts_with("PRIMARY KEY") {
+            Some("PRIMARY KEY".to_string())
+        } else if using.starts_with("AUTOMATIC ") {
+            Some("AUTOMATIC INDEX".to_string())
+        } else {
+            let name_start = start + using.find("INDEX ")? + "INDEX ".len();
+            self.details[name_start..].split_whitespace().next().map(str::to_string)
+        }
     }
 
     pub fn get_icon(&self) -> &'static str {
@@ -65,10 +147,12 @@ impl PlanNode {
 
     pub fn get_table_name(&self) -> Option<String> {
         if let Some(table_part) = self.details.split(" FROM ").nth(1)
-            .or_else(|| self.details.split("TABLE ").nth(1)) {
+            .or_else(|| self.details.split("TABLE ").nth(1))
+            .or_else(|| self.details.strip_prefix("SCAN ").or_else(|| self.details.strip_prefix("SEARCH "))
+                .filter(|rest| !rest.starts_with("CONSTANT ROW") && !rest.starts_with('('))) {
             // Extract table name from various formats like "FROM users", "SCAN TABLE users"
             let table_name = table_part.split_whitespace().next()?
-                .split('.').last()? // Handle schema.table format
+                .split('.').next_back()? // Handle schema.table format
                 .trim_matches(|c| !char::is_alphanumeric(c) && c != '_')
                 .to_string();
             if !table_name.is_empty() { Some(table_name) } else { None }
@@ -337,6 +421,190 @@ pub fn render_plan(plan: &str) -> Result<String> {
     Ok(visualization)
 }
 
+/// Executes `query` and collects per-loop statistics with `sqlite3_stmt_scanstatus_v2`.
+///
+/// The query runs to completion inside a savepoint that is rolled back afterwards,
+/// so statements that write are measured without keeping their changes. Rows are
+/// added to `rows_produced` as they are returned. Builds without the `scanstatus`
+/// feature fall back to the EXPLAIN QUERY PLAN tree and the statement counters.
+pub fn run_with_scan_stats(conn: &Connection, query: &str, rows_produced: &AtomicU64) -> Result<ScanStatusRun> {
+    let query = query.trim().trim_end_matches(';');
+
+    conn.execute_batch("SAVEPOINT tuiql_scanstatus")?;
+    let result = step_with_scan_stats(conn, query, rows_produced);
+    // An interrupted write may already have rolled back the savepoint, so report the run's error first
+    let rollback = conn.execute_batch("ROLLBACK TO tuiql_scanstatus; RELEASE tuiql_scanstatus");
+    let run = result?;
+    rollback?;
+    Ok(run)
+}
+
+#[cfg(not(feature = "scanstatus"))]
+fn step_with_scan_stats(conn: &Connection, query: &str, rows_produced: &AtomicU64) -> Result<ScanStatusRun> {
+    let nodes = query_plan(conn, query)?;
+    let mut stmt = conn.prepare(query)?;
+
+    let start_time = Instant::now();
+    let mut rows_returned = 0;
+    let mut rows = stmt.raw_query();
+    while rows.next()?.is_some() {
+        rows_returned += 1;
+        rows_produced.fetch_add(1, Ordering::Relaxed);
+    }
+    drop(rows);
+    let execution_time_ms = start_time.elapsed().as_millis();
+
+    Ok(ScanStatusRun {
+        nodes,
+        rows_returned,
+        execution_time_ms,
+        counters: Some(db::StatementStats::from_statement(&stmt)),
+    })
+}
+
+#[cfg(feature = "scanstatus")]
+fn step_with_scan_stats(conn: &Connection, query: &str, rows_produced: &AtomicU64) -> Result<ScanStatusRun> {
+    let sql = CString::new(query).map_err(|_| TuiqlError::Query("Query contains a NUL byte".to_string()))?;
+    // SAFETY: the statement is prepared on this connection's handle and finalized before returning
+    let mut run = unsafe { read_scan_status(conn.handle(), &sql, rows_produced) }?;
+    run.nodes = build_plan_tree(run.nodes);
+    Ok(run)
+}
+
+#[cfg(feature = "scanstatus")]
+unsafe fn error_message(db: *mut ffi::sqlite3) -> String {
+    CStr::from_ptr(ffi::sqlite3_errmsg(db)).to_string_lossy().into_owned()
+}
+
+#[cfg(feature = "scanstatus")]
+unsafe fn read_scan_status(db: *mut ffi::sqlite3, sql: &CStr, rows_produced: &AtomicU64) -> Result<ScanStatusRun> {
+    let mut stmt: *mut ffi::sqlite3_stmt = std::ptr::null_mut();
+    if ffi::sqlite3_prepare_v2(db, sql.as_ptr(), -1, &mut stmt, std::ptr::null_mut()) != ffi::SQLITE_OK {
+        return Err(TuiqlError::Query(error_message(db)));
+    }
+    if stmt.is_null() {
+        return Err(TuiqlError::Query("Nothing to execute".to_string()));
+    }
+
+    let start_time = Instant::now();
+    let mut rows_returned = 0;
+    let rc = loop {
+        match ffi::sqlite3_step(stmt) {
+            ffi::SQLITE_ROW => {
+                rows_returned += 1;
+                rows_produced.fetch_add(1, Ordering::Relaxed);
+            }
+            rc => break rc,
+        }
+    };
+    let execution_time_ms = start_time.elapsed().as_millis();
+    if rc != ffi::SQLITE_DONE {
+        let message = error_message(db);
+        ffi::sqlite3_finalize(stmt);
+        return Err(TuiqlError::Query(message));
+    }
+
+    let mut nodes = Vec::new();
+    for idx in 0.. {
+        let status = |op: c_int, out: *mut c_void| {
+            ffi::sqlite3_stmt_scanstatus_v2(stmt, idx, op, ffi::SQLITE_SCANSTAT_COMPLEX, out)
+        };
+        let mut id: c_int = 0;
+        if status(ffi::SQLITE_SCANSTAT_SELECTID, &mut id as *mut c_int as *mut c_void) != 0 {
+            break;
+        }
+        let mut parent: c_int = 0;
+        let mut loops: ffi::sqlite3_int64 = -1;
+        let mut rows_visited: ffi::sqlite3_int64 = -1;
+        let mut estimated_rows: f64 = 0.0;
+        let mut explain: *const c_char = std::ptr::null();
+        status(ffi::SQLITE_SCANSTAT_PARENTID, &mut parent as *mut c_int as *mut c_void);
+        status(ffi::SQLITE_SCANSTAT_NLOOP, &mut loops as *mut ffi::sqlite3_int64 as *mut c_void);
+        status(ffi::SQLITE_SCANSTAT_NVISIT, &mut rows_visited as *mut ffi::sqlite3_int64 as *mut c_void);
+        status(ffi::SQLITE_SCANSTAT_EST, &mut estimated_rows as *mut f64 as *mut c_void);
+        status(ffi::SQLITE_SCANSTAT_EXPLAIN, &mut explain as *mut *const c_char as *mut c_void);
+
+        let details = if explain.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(explain).to_string_lossy().into_owned()
+        };
+        let mut node = PlanNode::new(id, parent, details);
+        // Elements that are not loops, such as temp b-trees, report no loop count
+        if loops >= 0 {
+            node.stats = Some(ScanStats {
+                loops,
+                rows_visited,
+                estimated_rows,
+            });
+        }
+        nodes.push(node);
+    }
+    ffi::sqlite3_finalize(stmt);
+
+    Ok(ScanStatusRun {
+        nodes,
+        rows_returned,
+        execution_time_ms,
+        counters: None,
+    })
+}
+
+/// Renders a plan tree annotated with real loops, rows visited and estimated vs actual rows.
+///
+/// Loops whose estimate differs from the actual rows by `ESTIMATE_DIVERGENCE_WARN` or more are highlighted.
+pub fn render_plan_with_scan_stats(run: &ScanStatusRun) -> String {
+    let mut visualization = String::from("=== Query Plan with Runtime Statistics ===\n");
+    visualization.push_str(&format!(
+        "⏱️  Execution Time: {} ms, {} row(s) returned\n\n",
+        run.execution_time_ms, run.rows_returned
+    ));
+
+    let mut divergent = 0;
+    for node in &run.nodes {
+        let indent = "  ".repeat(node.level as usize);
+        visualization.push_str(&format!("{}{} {}\n", indent, node.get_icon(), node.details));
+        let Some(stats) = node.stats else {
+            continue;
+        };
+        visualization.push_str(&format!(
+            "{}   └─ 🔁 loops {} · 👣 rows visited {} · 🔢 est {:.1}/loop vs actual {:.1}/loop\n",
+            indent,
+            stats.loops,
+            stats.rows_visited,
+            stats.estimated_rows,
+            stats.actual_rows()
+        ));
+        if stats.loops > 0 && stats.estimate_divergence() >= ESTIMATE_DIVERGENCE_WARN {
+            divergent += 1;
+            visualization.push_str(&format!(
+                "{}   └─ ❗ Estimate off by {:.1}×\n",
+                indent,
+                stats.estimate_divergence()
+            ));
+        }
+    }
+
+    if run.nodes.is_empty() {
+        visualization.push_str("No plan data to display.\n");
+    }
+    if let Some(counters) = run.counters {
+        visualization.push_str(&format!("\n{}\n", counters.summary()));
+    }
+    if !cfg!(feature = "scanstatus") {
+        visualization.push_str("\nℹ️  Per-loop statistics need a build with `--features scanstatus`\n");
+    }
+    if divergent > 0 {
+        visualization.push_str(&format!(
+            "\n💡 {} loop(s) diverge from the planner's estimates; run ANALYZE so the planner sees current table sizes\n",
+            divergent
+        ));
+    }
+
+    visualization.push_str("\n=== End Query Plan ===");
+    visualization
+}
+
 /// Renders enhanced plan visualization with execution timing overlay
 pub fn render_plan_with_timing(plan: &str, execution_time_ms: u128) -> Result<String> {
     let nodes = parse_plan_output(plan)?;
@@ -381,8 +649,1040 @@ pub fn render_plan_with_timing(plan: &str, execution_time_ms: u128) -> Result<St
     Ok(visualization)
 }
 
+/// Opcodes that jump back to the start of a loop body through p2
+const LOOP_OPCODES: &[&str] = &["Next", "Prev", "VNext", "VPrev", "SorterNext"];
+
+/// Opcodes a backward `Goto` returns to when it closes a loop, as in the sqlite3 shell
+const LOOP_START_OPCODES: &[&str] = &["Yield", "SeekLT", "SeekGT", "RowSetRead", "Rewind"];
+
+/// Opcodes whose p1 is a cursor number
+const CURSOR_OPCODES: &[&str] = &[
+    "OpenRead", "OpenWrite", "ReopenIdx", "OpenDup", "OpenEphemeral", "OpenAutoindex", "SorterOpen", "OpenPseudo",
+    "Close", "Column", "Rowid", "RowData", "Rewind", "Last", "Next", "Prev", "SeekRowid", "NotExists", "SeekGE",
+    "SeekGT", "SeekLE", "SeekLT", "SeekScan", "SeekEnd", "SeekHit", "IdxGE", "IdxGT", "IdxLE", "IdxLT", "IdxRowid",
+    "IdxInsert", "IdxDelete", "DeferredSeek", "FinishSeek", "Found", "NotFound", "NoConflict", "IfNoHope",
+    "Insert", "Delete", "NullRow", "Count", "Sort", "SorterSort", "SorterNext", "SorterData", "SorterInsert", "SorterCompare",
+    "VOpen", "VFilter", "VColumn", "VNext", "VUpdate", "VRowid", "NewRowid", "ResetSorter", "IfEmpty",
+];
+
+/// One instruction of a prepared statement's bytecode program
+#[derive(Debug, Clone, PartialEq)]
+pub struct VmInstruction {
+    pub addr: i64,
+    pub opcode: String,
+    pub p1: i64,
+    pub p2: i64,
+    pub p3: i64,
+    pub p4: String,
+    pub p5: i64,
+    /// SQLite's comment when built with SQLITE_ENABLE_EXPLAIN_COMMENTS, otherwise a
+    /// synopsis derived from the opcode and its operands
+    pub comment: String,
+    /// Loop nesting depth, derived from backward jumps
+    pub indent: usize,
+}
+
+/// What a VDBE cursor is opened on
+#[derive(Debug, Clone, PartialEq)]
+pub struct VmCursor {
+    /// Cursor number (P1 of the Open* instruction); numbers can be reused by later opens
+    pub number: i64,
+    /// Address of the instruction that opened the cursor
+    pub opened_at: i64,
+    /// Table or index name, or a description for ephemeral cursors
+    pub target: String,
+    /// "table", "index", "ephemeral", "sorter", "pseudo", "virtual" or "unknown"
+    pub kind: String,
+    pub writable: bool,
+}
+
+/// Bytecode program of a statement, as reported by EXPLAIN
+#[derive(Debug, Clone)]
+pub struct VmProgram {
+    pub instructions: Vec<VmInstruction>,
+    /// Every cursor opened by the program, in address order
+    pub cursors: Vec<VmCursor>,
+}
+
+impl VmProgram {
+    /// The cursor `number` refers to at `addr`: the most recent one opened before it
+    pub fn cursor_at(&self, number: i64, addr: i64) -> Option<&VmCursor> {
+        latest_open(&self.cursors, number, addr)
+    }
+}
+
+fn latest_open(cursors: &[VmCursor], number: i64, addr: i64) -> Option<&VmCursor> {
+    cursors.iter().rev().find(|c| c.number == number && c.opened_at <= addr)
+}
+
+/// Computes loop indentation the way the sqlite3 shell does: the body of every
+/// backward `Next`-style jump, and of every backward `Goto` to a loop start, is indented
+fn indent_loops(instructions: &mut [VmInstruction]) {
+    for i in 0..instructions.len() {
+        let target = instructions[i].p2;
+        let Some(start) = instructions.iter().position(|ins| ins.addr == target) else { continue };
+        if start >= i {
+            continue;
+        }
+        let opcode = instructions[i].opcode.as_str();
+        let closes_loop = LOOP_OPCODES.contains(&opcode)
+            || (opcode == "Goto"
+                && (LOOP_START_OPCODES.contains(&instructions[start].opcode.as_str()) || instructions[i].p1 != 0));
+        if closes_loop {
+            for ins in &mut instructions[start..i] {
+                ins.indent += 1;
+            }
+        }
+    }
+}
+
+/// Maps the cursors opened by the program to tables and indexes through their root pages
+fn resolve_cursors(conn: &Connection, instructions: &[VmInstruction]) -> Result<Vec<VmCursor>> {
+    let mut databases = std::collections::HashMap::new();
+    let mut stmt = conn.prepare("SELECT seq, name FROM pragma_database_list")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (seq, name) = row?;
+        databases.insert(seq, name);
+    }
+
+    let mut cursors: Vec<VmCursor> = Vec::new();
+    for ins in instructions {
+        let (target, kind, writable) = match ins.opcode.as_str() {
+            "OpenRead" | "OpenWrite" | "ReopenIdx" => {
+                let schema_table = match databases.get(&ins.p3).map(String::as_str) {
+                    Some("temp") => "sqlite_temp_schema".to_string(),
+                    Some(name) => format!("{}.sqlite_schema", crate::core::db::schema::quote_identifier(name)),
+                    None => "sqlite_schema".to_string(),
+                };
+                let object: Option<(String, String)> = conn
+                    .query_row(
+                        &format!("SELECT name, type FROM {} WHERE rootpage = ?1", schema_table),
+                        [ins.p2],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .ok();
+                let (target, kind) = match object {
+                    Some((name, kind)) => (name, kind),
+                    // sqlite_schema itself lives on page 1
+                    None if ins.p2 == 1 => ("sqlite_schema".to_string(), "table".to_string()),
+                    None => (format!("root page {}", ins.p2), "unknown".to_string()),
+                };
+                (target, kind, ins.opcode == "OpenWrite")
+            }
+            "OpenDup" => match latest_open(&cursors, ins.p2, ins.addr) {
+                Some(original) => (original.target.clone(), original.kind.clone(), original.writable),
+                None => continue,
+            },
+            "OpenAutoindex" => ("automatic index".to_string(), "ephemeral".to_string(), true),
+            "OpenEphemeral" => ("ephemeral table".to_string(), "ephemeral".to_string(), true),
+            "SorterOpen" => ("sorter".to_string(), "sorter".to_string(), true),
+            "OpenPseudo" => (format!("pseudo-table on r[{}]", ins.p2), "pseudo".to_string(), false),
+            "VOpen" => (ins.p4.clone(), "virtual".to_string(), false),
+            _ => continue,
+        };
+        cursors.push(VmCursor { number: ins.p1, opened_at: ins.addr, target, kind, writable });
+    }
+    Ok(cursors)
+}
+
+/// Registers `start` to `start + count - 1`, as in SQLite's `r[P1@P2]` synopses
+fn register_range(start: i64, count: i64) -> String {
+    if count > 1 {
+        format!("r[{}..{}]", start, start + count - 1)
+    } else {
+        format!("r[{}]", start)
+    }
+}
+
+/// Synopsis of an instruction in the style of SQLite's EXPLAIN comments, for the
+/// common opcodes; others get an empty comment
+fn describe_instruction(ins: &VmInstruction) -> String {
+    let (p1, p2, p3) = (ins.p1, ins.p2, ins.p3);
+    match ins.opcode.as_str() {
+        "Init" => format!("Start at {}", p2),
+        "Integer" => format!("r[{}]={}", p2, p1),
+        "Int64" | "Real" => format!("r[{}]={}", p2, ins.p4),
+        "String8" => format!("r[{}]='{}'", p2, ins.p4),
+        "Null" if p3 > p2 => format!("r[{}..{}]=NULL", p2, p3),
+        "Null" => format!("r[{}]=NULL", p2),
+        "Variable" => format!("r[{}]=parameter({})", p2, p1),
+        "Copy" => format!("{}={}", register_range(p2, p3 + 1), register_range(p1, p3 + 1)),
+        "SCopy" => format!("r[{}]=r[{}]", p2, p1),
+        "Move" => format!("{}={}", register_range(p2, p3), register_range(p1, p3)),
+        "Column" => format!("r[{}]=cursor {} column {}", p3, p1, p2),
+        "Rowid" | "IdxRowid" => format!("r[{}]=rowid of cursor {}", p2, p1),
+        "NewRowid" => format!("r[{}]=new rowid of cursor {}", p2, p1),
+        "ResultRow" => format!("output={}", register_range(p1, p2)),
+        "MakeRecord" => format!("r[{}]=mkrec({})", p3, register_range(p1, p2)),
+        "Affinity" => format!("affinity({})", register_range(p1, p2)),
+        "OpenRead" | "OpenWrite" | "ReopenIdx" => format!("root={} iDb={}", p2, p3),
+        "Rewind" | "Last" | "SorterSort" | "Sort" => format!("if cursor {} is empty goto {}", p1, p2),
+        "Next" | "Prev" | "SorterNext" => format!("advance cursor {}; goto {} if more rows", p1, p2),
+        "SeekRowid" | "NotExists" => format!("intkey=r[{}]", p3),
+        "SeekGE" | "SeekGT" | "SeekLE" | "SeekLT" | "IdxGE" | "IdxGT" | "IdxLE" | "IdxLT" | "Found" | "NotFound" | "NoConflict" => {
+            format!("key={}", register_range(p3, ins.p4.parse().unwrap_or(1)))
+        }
+        "Insert" => format!("intkey=r[{}] data=r[{}]", p3, p2),
+        "IdxInsert" | "SorterInsert" => format!("key=r[{}]", p2),
+        "SorterData" => format!("r[{}]=data", p2),
+        "Eq" | "Ne" | "Lt" | "Le" | "Gt" | "Ge" => {
+            let operator = match ins.opcode.as_str() {
+                "Eq" => "==",
+                "Ne" => "!=",
+                "Lt" => "<",
+                "Le" => "<=",
+                "Gt" => ">",
+                _ => ">=",
+            };
+            format!("if r[{}]{}r[{}] goto {}", p3, operator, p1, p2)
+        }
+        "If" => format!("if r[{}] goto {}", p1, p2),
+        "IfNot" => format!("if !r[{}] goto {}", p1, p2),
+        "IsNull" => format!("if r[{}]==NULL goto {}", p1, p2),
+        "NotNull" => format!("if r[{}]!=NULL goto {}", p1, p2),
+        "IfPos" => format!("if r[{}]>0 then r[{}]-={}, goto {}", p1, p1, p3, p2),
+        "DecrJumpZero" => format!("if (--r[{}])==0 goto {}", p1, p2),
+        "Add" => format!("r[{}]=r[{}]+r[{}]", p3, p2, p1),
+        "Subtract" => format!("r[{}]=r[{}]-r[{}]", p3, p2, p1),
+        "Multiply" => format!("r[{}]=r[{}]*r[{}]", p3, p2, p1),
+        "Divide" => format!("r[{}]=r[{}]/r[{}]", p3, p2, p1),
+        "Concat" => format!("r[{}]=r[{}]||r[{}]", p3, p2, p1),
+        "Function" | "PureFunc" => format!("r[{}]={}", p3, ins.p4),
+        "AggStep" => format!("accum=r[{}] step({})", p3, ins.p4),
+        "AggFinal" => format!("accum=r[{}] N={}", p1, p2),
+        "Count" => format!("r[{}]=count()", p2),
+        "Gosub" => format!("r[{}]=return address; goto {}", p1, p2),
+        "Return" => format!("goto address in r[{}]", p1),
+        "Goto" => format!("goto {}", p2),
+        _ => String::new(),
+    }
+}
+
+/// Runs EXPLAIN on `query` and returns its bytecode with loops and cursors resolved
+pub fn explain_vm(conn: &Connection, query: &str) -> Result<VmProgram> {
+    let mut stmt = conn.prepare(&format!("EXPLAIN {}", query.trim().trim_end_matches(';')))?;
+    let parameters = std::iter::repeat_n(rusqlite::types::Null, stmt.parameter_count());
+    let mut instructions = stmt
+        .query_map(rusqlite::params_from_iter(parameters), |row| {
+            Ok(VmInstruction {
+                addr: row.get(0)?,
+                opcode: row.get(1)?,
+                p1: row.get(2)?,
+                p2: row.get(3)?,
+                p3: row.get(4)?,
+                p4: match row.get_ref(5)? {
+                    rusqlite::types::ValueRef::Null => String::new(),
+                    value => db::format_value(value),
+                },
+                p5: row.get(6)?,
+                comment: row.get::<_, Option<String>>(7)?.unwrap_or_default(),
+                indent: 0,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for ins in instructions.iter_mut().filter(|ins| ins.comment.is_empty()) {
+        ins.comment = describe_instruction(ins);
+    }
+    indent_loops(&mut instructions);
+    let cursors = resolve_cursors(conn, &instructions)?;
+    Ok(VmProgram { instructions, cursors })
+}
+
+/// Renders a bytecode program in the layout of the sqlite3 shell's EXPLAIN,
+/// with a cursor column and a legend of what every cursor is opened on
+pub fn render_vm_program(program: &VmProgram) -> String {
+    let opcode_width = program
+        .instructions
+        .iter()
+        .map(|ins| ins.opcode.len() + ins.indent * 2)
+        .max()
+        .unwrap_or(6)
+        .max(6);
+    let p4_width = program.instructions.iter().map(|ins| ins.p4.chars().count()).max().unwrap_or(2).clamp(2, 30);
+
+    let mut output = String::from("=== Bytecode Program ===\n\n");
+    output.push_str(&format!(
+        "{:<4}  {:<ow$}  {:>4}  {:>4}  {:>4}  {:<pw$}  {:>2}  {:<16}  comment\n",
+        "addr", "opcode", "p1", "p2", "p3", "p4", "p5", "cursor",
+        ow = opcode_width,
+        pw = p4_width
+    ));
+    output.push_str(&format!(
+        "{}  {}  ----  ----  ----  {}  --  {}  -------\n",
+        "-".repeat(4),
+        "-".repeat(opcode_width),
+        "-".repeat(p4_width),
+        "-".repeat(16)
+    ));
+
+    for ins in &program.instructions {
+        let cursor = if CURSOR_OPCODES.contains(&ins.opcode.as_str()) {
+            program.cursor_at(ins.p1, ins.addr).map(|c| format!("[{}] {}", ins.p1, c.target)).unwrap_or_default()
+        } else {
+            String::new()
+        };
+        let mut p4: String = ins.p4.chars().take(p4_width).collect();
+        if ins.p4.chars().count() > p4_width {
+            p4.pop();
+            p4.push('…');
+        }
+        output.push_str(
+            format!(
+                "{:<4}  {:<ow$}  {:>4}  {:>4}  {:>4}  {:<pw$}  {:>2}  {:<16}  {}",
+                ins.addr,
+                format!("{}{}", "  ".repeat(ins.indent), ins.opcode),
+                ins.p1,
+                ins.p2,
+                ins.p3,
+                p4,
+                ins.p5,
+                cursor,
+                ins.comment,
+                ow = opcode_width,
+                pw = p4_width
+            )
+            .trim_end(),
+        );
+        output.push('\n');
+    }
+
+    if !program.cursors.is_empty() {
+        output.push_str("\n🗂️  Cursors:\n");
+        for cursor in &program.cursors {
+            output.push_str(&format!(
+                "  [{}] {} ({}{}) at {}\n",
+                cursor.number,
+                cursor.target,
+                cursor.kind,
+                if cursor.writable && (cursor.kind == "table" || cursor.kind == "index") { ", write" } else { "" },
+                cursor.opened_at
+            ));
+        }
+    }
+
+    let loops = program.instructions.iter().filter(|ins| LOOP_OPCODES.contains(&ins.opcode.as_str())).count();
+    output.push_str(&format!("\n{} instruction(s), {} loop(s)\n", program.instructions.len(), loops));
+    output.push_str("\n=== End Bytecode Program ===");
+    output
+}
+
+/// Runs EXPLAIN for a query on the current connection and renders its bytecode
+pub fn explain_query_vm(query: &str) -> Result<String> {
+    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::App("No database connection found".to_string()))?;
+    let state_guard = state_cell
+        .lock()
+        .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+    let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+    Ok(render_vm_program(&explain_vm(conn, query)?))
+}
+
+/// Runs EXPLAIN QUERY PLAN, binding NULL to any parameters the query contains
+pub fn query_plan(conn: &Connection, query: &str) -> Result<Vec<PlanNode>> {
+    let mut stmt = conn.prepare(&format!("EXPLAIN QUERY PLAN {}", query.trim().trim_end_matches(';')))?;
+    let parameters = std::iter::repeat_n(rusqlite::types::Null, stmt.parameter_count());
+    let nodes = stmt
+        .query_map(rusqlite::params_from_iter(parameters), |row| {
+            Ok(PlanNode::new(row.get(0)?, row.get(1)?, row.get(3)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(build_plan_tree(nodes))
+}
+
+/// Normalizes a plan line so plans from different SQLite versions compare equal
+pub fn normalize_plan_detail(detail: &str) -> String {
+    let detail = detail.split_whitespace().collect::<Vec<_>>().join(" ");
+    for (old, new) in [("SCAN TABLE ", "SCAN "), ("SEARCH TABLE ", "SEARCH ")] {
+        if let Some(rest) = detail.strip_prefix(old) {
+            return format!("{}{}", new, rest);
+        }
+    }
+    detail
+}
+
+/// Normalizes query text for matching against history: whitespace is collapsed
+/// and a trailing semicolon dropped
+pub fn normalize_query_text(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ").trim_end_matches(';').trim_end().to_string()
+}
+
+/// Plans `query` on the current connection for recording in history; any failure yields None
+pub fn capture_current_plan(query: &str) -> Option<PlanSnapshot> {
+    let state_guard = db::DB_STATE.get()?.lock().ok()?;
+    let conn = state_guard.connection.as_ref()?;
+    PlanSnapshot::capture(conn, query).ok().flatten()
+}
+
+/// The plan stored with a history entry; entries whose plan no longer parses have none
+fn stored_plan(entry: &HistoryEntry) -> Option<PlanSnapshot> {
+    serde_json::from_str(entry.plan.as_deref()?).ok()
+}
+
+/// Counts of the plan steps that make a plan expensive, most significant first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PlanScore {
+    pub full_scans: usize,
+    pub automatic_indexes: usize,
+    pub temp_btrees: usize,
+}
+
+impl PlanScore {
+    /// Whether a plan with this score is worse than one with `other`
+    pub fn is_worse_than(&self, other: &PlanScore) -> bool {
+        self > other
+    }
+}
+
+/// Normalized EXPLAIN QUERY PLAN tree, as stored in history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanSnapshot {
+    /// One line per plan node, indented by two spaces per level
+    pub lines: Vec<String>,
+    /// `schema_version` the plan was made against; SQLite bumps it on every schema change
+    pub schema_version: i64,
+}
+
+impl PlanSnapshot {
+    pub fn from_nodes(nodes: &[PlanNode], schema_version: i64) -> Self {
+        // Top-level EXPLAIN QUERY PLAN rows have parent 0, not -1, so levels start at 1
+        let base = nodes.iter().map(|n| n.level).min().unwrap_or(0);
+        PlanSnapshot {
+            lines: nodes
+                .iter()
+                .map(|n| format!("{}{}", "  ".repeat((n.level - base) as usize), normalize_plan_detail(&n.details)))
+                .collect(),
+            schema_version,
+        }
+    }
+
+    /// Plans `query` on `conn`; statements other than queries and DML, such as DDL, yield None
+    pub fn capture(conn: &Connection, query: &str) -> Result<Option<Self>> {
+        // DDL has a plan too, for its internal updates of sqlite_schema, which is not worth recording
+        let keyword = query.split_whitespace().next().unwrap_or("").to_uppercase();
+        if !["SELECT", "WITH", "VALUES", "INSERT", "REPLACE", "UPDATE", "DELETE"].contains(&keyword.as_str()) {
+            return Ok(None);
+        }
+        let nodes = query_plan(conn, query)?;
+        if nodes.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(PlanSnapshot::from_nodes(&nodes, schema::schema_version(conn)?)))
+    }
+
+    pub fn score(&self) -> PlanScore {
+        let mut score = PlanScore::default();
+        for line in &self.lines {
+            let detail = line.trim_start();
+            if PlanNode::new(0, -1, detail.to_string()).is_full_scan() {
+                score.full_scans += 1;
+            }
+            if detail.contains("AUTOMATIC") {
+                score.automatic_indexes += 1;
+            }
+            if detail.starts_with("USE TEMP B-TREE") {
+                score.temp_btrees += 1;
+            }
+        }
+        score
+    }
+}
+
+/// One line of a plan diff
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanChange {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Diffs two plans line by line (longest common subsequence)
+pub fn diff_plans(old: &[String], new: &[String]) -> Vec<PlanChange> {
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut changes = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(PlanChange::Same(old[i].clone()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            changes.push(PlanChange::Removed(old[i].clone()));
+            i += 1;
+        } else {
+            changes.push(PlanChange::Added(new[j].clone()));
+            j += 1;
+        }
+    }
+    changes
+}
+
+/// Pairs removed and added plan lines that access the same table, e.g.
+/// "SEARCH users USING INDEX ..." replaced by "SCAN users"
+pub fn plan_replacements(changes: &[PlanChange]) -> Vec<(String, String, String)> {
+    let table_of = |line: &str| PlanNode::new(0, -1, line.trim_start().to_string()).get_table_name();
+    let mut replacements = Vec::new();
+    for change in changes {
+        if let PlanChange::Removed(old) = change {
+            let Some(table) = table_of(old) else { continue };
+            let replacement = changes.iter().find_map(|c| match c {
+                PlanChange::Added(new) if table_of(new).as_deref() == Some(table.as_str()) => Some(new),
+                _ => None,
+            });
+            if let Some(new) = replacement {
+                replacements.push((table, old.trim_start().to_string(), new.trim_start().to_string()));
+            }
+        }
+    }
+    replacements
+}
+
+fn describe_score_change(old: &PlanScore, new: &PlanScore) -> String {
+    let mut parts = Vec::new();
+    for (label, before, after) in [
+        ("full scans", old.full_scans, new.full_scans),
+        ("automatic indexes", old.automatic_indexes, new.automatic_indexes),
+        ("temp b-trees", old.temp_btrees, new.temp_btrees),
+    ] {
+        if before != after {
+            parts.push(format!("{} {} → {}", label, before, after));
+        }
+    }
+    parts.join(", ")
+}
+
+/// Renders the diff between a stored plan and the current one
+pub fn render_plan_comparison(stored: &PlanSnapshot, current: &PlanSnapshot) -> String {
+    let mut output = String::from("=== Plan Comparison ===\n\n");
+    if stored.schema_version != current.schema_version {
+        output.push_str("🧬 The schema changed since the stored plan was recorded\n\n");
+    }
+
+    let changes = diff_plans(&stored.lines, &current.lines);
+    if changes.iter().all(|c| matches!(c, PlanChange::Same(_))) {
+        output.push_str("✅ Plan unchanged:\n");
+    } else {
+        output.push_str("Stored plan (-) vs current plan (+):\n");
+    }
+    for change in &changes {
+        let (marker, line) = match change {
+            PlanChange::Same(line) => (' ', line),
+            PlanChange::Removed(line) => ('-', line),
+            PlanChange::Added(line) => ('+', line),
+        };
+        output.push_str(&format!("  {} {}\n", marker, line));
+    }
+
+    let replacements = plan_replacements(&changes);
+    if !replacements.is_empty() {
+        output.push('\n');
+        for (table, old, new) in &replacements {
+            output.push_str(&format!("🔁 {}: \"{}\" replaced \"{}\"\n", table, new, old));
+        }
+    }
+
+    let (old_score, new_score) = (stored.score(), current.score());
+    if new_score.is_worse_than(&old_score) {
+        output.push_str(&format!("\n⚠️  Plan got worse: {}\n", describe_score_change(&old_score, &new_score)));
+    } else if old_score.is_worse_than(&new_score) {
+        output.push_str(&format!("\n🏎️ Plan improved: {}\n", describe_score_change(&old_score, &new_score)));
+    }
+
+    output.push_str("\n=== End Plan Comparison ===");
+    output
+}
+
+/// A query from history whose plan got worse after a schema change
+#[derive(Debug, Clone)]
+pub struct PlanRegression {
+    pub query: String,
+    /// When the better plan was recorded
+    pub recorded_at: i64,
+    pub stored: PlanSnapshot,
+    pub current: PlanSnapshot,
+}
+
+/// Re-plans every query with a stored plan and reports those whose plan is now worse
+/// than the latest plan recorded under a different schema.
+///
+/// `entries` must be ordered newest first; queries that no longer prepare are skipped.
+pub fn find_plan_regressions(conn: &Connection, entries: &[HistoryEntry]) -> Result<Vec<PlanRegression>> {
+    let schema_version = schema::schema_version(conn)?;
+    let mut seen = std::collections::HashSet::new();
+    let mut regressions = Vec::new();
+
+    for entry in entries {
+        let query = normalize_query_text(&entry.query);
+        if !seen.insert(query.clone()) {
+            continue;
+        }
+        let Ok(Some(current)) = PlanSnapshot::capture(conn, &query) else { continue };
+        let previous = entries.iter().find_map(|e| match stored_plan(e) {
+            Some(plan) if plan.schema_version != schema_version && normalize_query_text(&e.query) == query => {
+                Some((e.timestamp, plan))
+            }
+            _ => None,
+        });
+        if let Some((recorded_at, stored)) = previous {
+            if current.score().is_worse_than(&stored.score()) {
+                regressions.push(PlanRegression {
+                    query,
+                    recorded_at,
+                    stored,
+                    current,
+                });
+            }
+        }
+    }
+    Ok(regressions)
+}
+
+/// Renders the list of plan regressions
+pub fn render_plan_regressions(regressions: &[PlanRegression]) -> String {
+    if regressions.is_empty() {
+        return "✅ No plan regressions found in history\n".to_string();
+    }
+
+    let mut output = format!("⚠️  {} quer{} with a worse plan since a schema change\n", regressions.len(), if regressions.len() == 1 { "y" } else { "ies" });
+    output.push_str(&"=".repeat(60));
+    output.push('\n');
+    for (i, regression) in regressions.iter().enumerate() {
+        let recorded = chrono::DateTime::from_timestamp(regression.recorded_at, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        output.push_str(&format!("{}. {}\n", i + 1, regression.query));
+        output.push_str(&format!(
+            "   {} (was recorded {})\n",
+            describe_score_change(&regression.stored.score(), &regression.current.score()),
+            recorded
+        ));
+        let changes = diff_plans(&regression.stored.lines, &regression.current.lines);
+        for (table, old, new) in plan_replacements(&changes) {
+            output.push_str(&format!("   🔁 {}: \"{}\" replaced \"{}\"\n", table, new, old));
+        }
+    }
+    output.push_str("\n💡 Use ':plan --compare <query>' for the full diff\n");
+    output
+}
+
+/// Version of the `:plan --format json` document
+pub const PLAN_EXPORT_VERSION: u32 = 1;
+
+/// One loop of an exported plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanExportNode {
+    pub id: i32,
+    /// Id of the parent node; 0 for top-level nodes
+    pub parent: i32,
+    pub detail: String,
+    pub table: Option<String>,
+    pub index: Option<String>,
+    pub full_scan: bool,
+    /// Estimated rows visited per run of the loop, from `sqlite_stat1`; `None` without statistics
+    pub estimated_cost: Option<f64>,
+}
+
+/// A query plan in a form external tools can consume
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanExport {
+    pub version: u32,
+    pub query: String,
+    pub nodes: Vec<PlanExportNode>,
+}
+
+/// Number of rows in a table as estimated by `sqlite_stat1`; exporting a plan never scans the table
+fn table_row_count(conn: &Connection, table: &str) -> Option<u64> {
+    let stats = analyze::read_stat1(conn, Some(table)).ok()?;
+    stats.first().and_then(|row| row.row_estimate())
+}
+
+/// Terms of the constraint list of a SEARCH, e.g. `a=?` and `b>?` for `(a=? AND b>?)`
+fn constraint_terms(detail: &str) -> impl Iterator<Item = &str> {
+    let constraints = detail.rsplit_once('(').map_or("", |(_, rest)| rest.trim_end_matches(')'));
+    constraints.split(" AND ").filter(|term| !term.is_empty())
+}
+
+/// Number of `column=?` terms in the constraint list of a SEARCH
+fn equality_constraints(detail: &str) -> usize {
+    constraint_terms(detail)
+        .filter(|term| term.contains('=') && !term.contains(">=") && !term.contains("<="))
+        .count()
+}
+
+/// Estimates the rows a loop visits per run
+fn estimate_cost(conn: &Connection, node: &PlanNode, table: &str) -> Option<f64> {
+    let searches = node.details.to_ascii_uppercase().starts_with("SEARCH ");
+    let equalities = equality_constraints(&node.details);
+    match node.index_used() {
+        _ if node.is_full_scan() => table_row_count(conn, table).map(|rows| rows as f64),
+        Some(index) if searches && equalities > 0 && index == "INTEGER PRIMARY KEY" => Some(1.0),
+        Some(index) if searches && equalities > 0 => {
+            // stat1 holds the row count, then the average rows per distinct key prefix
+            let stats = analyze::read_stat1(conn, Some(table)).ok()?;
+            let numbers = stats.iter().find(|row| row.idx.as_deref() == Some(index.as_str()))?.numbers();
+            numbers.get(equalities.min(numbers.len().saturating_sub(1))).filter(|_| numbers.len() > 1).map(|rows| *rows as f64)
+        }
+        // A SCAN through an index visits every row
+        Some(_) if !searches => table_row_count(conn, table).map(|rows| rows as f64),
+        _ => None,
+    }
+}
+
+/// Resolves the name a plan shows for a loop, which is the alias when the query
+/// gives the table one, to the table itself
+fn resolve_plan_table(conn: &Connection, query: &str, name: &str) -> Option<String> {
+    let table_named = |candidate: &str| -> Option<String> {
+        conn.query_row(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?1 COLLATE NOCASE",
+            [candidate],
+            |row| row.get(0),
+        )
+        .ok()
+    };
+    if let Some(table) = table_named(name) {
+        return Some(table);
+    }
+
+    // Look for "<table> <alias>" or "<table> AS <alias>" in the query
+    let tokens: Vec<&str> = query
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '(' | ')' | ';'))
+        .filter(|token| !token.is_empty())
+        .map(|token| token.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']')))
+        .collect();
+    tokens.iter().enumerate().skip(1).find_map(|(i, token)| {
+        if !token.eq_ignore_ascii_case(name) {
+            return None;
+        }
+        let before = if tokens[i - 1].eq_ignore_ascii_case("AS") { tokens.get(i.checked_sub(2)?)? } else { &tokens[i - 1] };
+        table_named(before.rsplit('.').next()?)
+    })
+}
+
+/// Collects the plan of `query` with the table, index and estimated cost of each loop
+pub fn export_plan(conn: &Connection, query: &str) -> Result<PlanExport> {
+    let nodes = query_plan(conn, query)?
+        .into_iter()
+        .map(|node| {
+            let table = node.get_table_name().map(|name| resolve_plan_table(conn, query, &name).unwrap_or(name));
+            PlanExportNode {
+                id: node.id,
+                parent: node.parent,
+                estimated_cost: table.as_deref().and_then(|table| estimate_cost(conn, &node, table)),
+                full_scan: node.is_full_scan(),
+                index: node.index_used(),
+                table,
+                detail: node.details,
+            }
+        })
+        .collect();
+    Ok(PlanExport {
+        version: PLAN_EXPORT_VERSION,
+        query: query.trim().trim_end_matches(';').to_string(),
+        nodes,
+    })
+}
+
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+/// Renders an exported plan as a Graphviz digraph rooted at the query
+pub fn plan_to_dot(plan: &PlanExport) -> String {
+    let mut out = String::from("digraph plan {\n    rankdir=TB;\n    node [shape=box];\n\n");
+    out.push_str(&format!("    n0 [label={}, shape=note];\n", dot_quote(&plan.query)));
+    for node in &plan.nodes {
+        let mut label = node.detail.clone();
+        if let Some(cost) = node.estimated_cost {
+            label.push_str(&format!("\n~{} rows", cost.round()));
+        }
+        let color = if node.full_scan {
+            ", color=red"
+        } else if node.index.is_some() {
+            ", color=darkgreen"
+        } else {
+            ""
+        };
+        out.push_str(&format!("    n{} [label={}{}];\n", node.id, dot_quote(&label), color));
+    }
+    if !plan.nodes.is_empty() {
+        out.push('\n');
+    }
+    for node in &plan.nodes {
+        // Nodes whose parent is missing from the plan hang off the root
+        let parent = if plan.nodes.iter().any(|other| other.id == node.parent) { node.parent } else { 0 };
+        out.push_str(&format!("    n{} -> n{};\n", parent, node.id));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Renders the plan of `query` as `json` or `dot`
+pub fn export_query_plan(conn: &Connection, query: &str, format: &str) -> Result<String> {
+    let plan = export_plan(conn, query)?;
+    match format.to_lowercase().as_str() {
+        "json" => Ok(serde_json::to_string_pretty(&plan)?),
+        "dot" | "graphviz" => Ok(plan_to_dot(&plan)),
+        _ => Err(TuiqlError::Command(format!(
+            "Unsupported plan format: '{}'. Supported formats: json, dot",
+            format
+        ))),
+    }
+}
+
+/// A plain-language hint about a plan loop, tied to the columns it concerns
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanHint {
+    pub table: String,
+    pub columns: Vec<String>,
+    pub message: String,
+}
+
+impl PlanHint {
+    /// `table.column` references to the columns the hint is about
+    pub fn column_links(&self) -> Vec<String> {
+        self.columns.iter().map(|column| format!("{}.{}", self.table, column)).collect()
+    }
+}
+
+fn backticked(columns: &[String]) -> String {
+    columns.iter().map(|column| format!("`{}`", column)).collect::<Vec<_>>().join(", ")
+}
+
+/// First index of `table` whose leading columns are `columns`
+fn index_with_prefix<'t>(table: &'t schema::Table, columns: &[String]) -> Option<&'t schema::Index> {
+    table.indexes.iter().find(|index| {
+        index.columns.len() >= columns.len()
+            && index.columns.iter().zip(columns).all(|(indexed, column)| indexed.eq_ignore_ascii_case(column))
+    })
+}
+
+/// Cross-references the plan of `query` with the schema's indexes and the
+/// query's constraints to explain why a loop scans, sorts or builds an index.
+pub fn plan_hints(conn: &Connection, schema: &Schema, query: &str, nodes: &[PlanNode]) -> Result<Vec<PlanHint>> {
+    // Statements the analyzer does not understand simply get no column-specific hints
+    let usage = index_advisor::column_usage(conn, query).unwrap_or_default();
+    let mut hints: Vec<PlanHint> = Vec::new();
+    let mut push = |table: &str, columns: Vec<String>, message: String| {
+        let hint = PlanHint { table: table.to_string(), columns, message };
+        if !hints.contains(&hint) {
+            hints.push(hint);
+        }
+    };
+
+    for node in nodes {
+        if node.details.to_ascii_uppercase().starts_with("USE TEMP B-TREE FOR ORDER BY") {
+            for (table, usage) in usage.iter().filter(|(_, usage)| !usage.order_by.is_empty()) {
+                let mut columns = usage.equality.clone();
+                columns.extend(usage.order_by.iter().filter(|c| !usage.equality.contains(c)).cloned());
+                let message = match schema.tables.get(table).and_then(|t| index_with_prefix(t, &columns)) {
+                    Some(index) => format!(
+                        "ORDER BY on {} uses a temp B-tree although index `{}` starts with ({}); run :analyze so the planner can weigh it",
+                        backticked(&usage.order_by),
+                        index.name,
+                        columns.join(", ")
+                    ),
+                    None => format!(
+                        "ORDER BY on {} uses a temp B-tree; an index on ({}) would avoid it",
+                        backticked(&usage.order_by),
+                        columns.join(", ")
+                    ),
+                };
+                push(table, usage.order_by.clone(), message);
+            }
+            continue;
+        }
+
+        let Some(name) = node.get_table_name() else { continue };
+        let table = resolve_plan_table(conn, query, &name).unwrap_or(name);
+
+        if node.index_used().as_deref() == Some("AUTOMATIC INDEX") {
+            let columns: Vec<String> = constraint_terms(&node.details)
+                .filter_map(|term| term.split(['=', '<', '>', ' ']).next())
+                .filter(|column| !column.is_empty())
+                .map(str::to_string)
+                .collect();
+            if !columns.is_empty() {
+                let message = format!(
+                    "SQLite builds an automatic index on {} of `{}` every time this query runs; a permanent index on {}({}) would avoid it",
+                    backticked(&columns),
+                    table,
+                    table,
+                    columns.join(", ")
+                );
+                push(&table, columns, message);
+            }
+            continue;
+        }
+
+        if !node.is_full_scan() {
+            continue;
+        }
+        let Some((_, usage)) = usage.iter().find(|(t, _)| t.eq_ignore_ascii_case(&table)) else { continue };
+        for column in &usage.leading_wildcard {
+            push(
+                &table,
+                vec![column.clone()],
+                format!("LIKE with leading % prevents index use on `{}`; a full-text index (:fts5) can search inside values", column),
+            );
+        }
+        for (function, column) in &usage.wrapped {
+            push(
+                &table,
+                vec![column.clone()],
+                format!(
+                    "Wrapping `{}` in {}() prevents index use on it; compare the bare column or index the expression {}({})",
+                    column, function, function, column
+                ),
+            );
+        }
+
+        let mut columns = usage.equality.clone();
+        if let Some(range) = usage.range.iter().find(|c| !columns.contains(c)) {
+            columns.push(range.clone());
+        }
+        if columns.is_empty() {
+            continue;
+        }
+        let message = match schema.tables.get(&table).and_then(|t| index_with_prefix(t, &columns[..1])) {
+            Some(index) => format!(
+                "`{}` is scanned although index `{}` starts with `{}`; the planner expects it to be unselective, run :analyze if that is wrong",
+                table, index.name, columns[0]
+            ),
+            None => format!(
+                "Filtering on {} scans all of `{}`; an index on ({}) would let SQLite search it instead",
+                backticked(&columns),
+                table,
+                columns.join(", ")
+            ),
+        };
+        push(&table, columns, message);
+    }
+    Ok(hints)
+}
+
+/// Renders plan hints with a link to each column they concern
+pub fn render_plan_hints(hints: &[PlanHint]) -> String {
+    let mut output = String::new();
+    if hints.is_empty() {
+        return output;
+    }
+    output.push_str("\n💡 Hints:\n");
+    for hint in hints {
+        output.push_str(&format!("  • {}\n", hint.message));
+        if !hint.columns.is_empty() {
+            output.push_str(&format!("    🔗 {}\n", hint.column_links().join(", ")));
+        }
+    }
+    output
+}
+
+/// Hints for `query` on the current connection; empty when none apply or the plan is unavailable
+fn current_plan_hints(query: &str) -> String {
+    // The catalog takes the connection lock itself, so it is fetched first
+    let Ok(catalog) = catalog::current_catalog() else { return String::new() };
+    let Some(state_cell) = db::DB_STATE.get() else { return String::new() };
+    let Ok(state_guard) = state_cell.lock() else { return String::new() };
+    let Some(conn) = state_guard.connection.as_ref() else { return String::new() };
+    query_plan(conn, query)
+        .and_then(|nodes| plan_hints(conn, &catalog.schema, query, &nodes))
+        .map(|hints| render_plan_hints(&hints))
+        .unwrap_or_default()
+}
+
+/// Executes `:plan <query>`, `:plan --compare <query>`, `:plan --format json|dot <query>` and `:plan regressions`
+pub fn execute_plan_command(command: &str, storage: &Storage) -> Result<String> {
+    let command = command.trim();
+    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::App("No database connection found".to_string()))?;
+
+    if command == "regressions" {
+        let state_guard = state_cell
+            .lock()
+            .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+        let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+        let database_path = state_guard.current_path.clone().unwrap_or_else(|| "main".to_string());
+        let entries = storage.get_history_with_plans(&database_path)?;
+        return Ok(render_plan_regressions(&find_plan_regressions(conn, &entries)?));
+    }
+
+    if let Some(rest) = command.strip_prefix("--format").filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+        let (format, query) = rest.trim().split_once(char::is_whitespace).unwrap_or((rest.trim(), ""));
+        if format.is_empty() || query.trim().is_empty() {
+            return Err(TuiqlError::Command("Usage: :plan --format json|dot <query>".to_string()));
+        }
+        let state_guard = state_cell
+            .lock()
+            .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+        let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+        return export_query_plan(conn, query.trim(), format);
+    }
+
+    let query = match command.strip_prefix("--compare") {
+        Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => rest.trim(),
+        _ => {
+            if let Some(option) = command.split_whitespace().next().filter(|arg| arg.starts_with("--")) {
+                return Err(TuiqlError::Command(format!("Unknown option '{}'", option)));
+            }
+            return explain_query(command);
+        }
+    };
+    if query.is_empty() {
+        return Err(TuiqlError::Command("Usage: :plan --compare <query>".to_string()));
+    }
+
+    let state_guard = state_cell
+        .lock()
+        .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+    let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+    let database_path = state_guard.current_path.clone().unwrap_or_else(|| "main".to_string());
+
+    let current = PlanSnapshot::capture(conn, query)?
+        .ok_or_else(|| TuiqlError::Query("The statement has no query plan".to_string()))?;
+    let normalized = normalize_query_text(query);
+    let stored = storage
+        .get_history_with_plans(&database_path)?
+        .into_iter()
+        .find(|e| normalize_query_text(&e.query) == normalized)
+        .and_then(|e| stored_plan(&e))
+        .ok_or_else(|| TuiqlError::Query("No stored plan for this query; run it first to record one".to_string()))?;
+    Ok(render_plan_comparison(&stored, &current))
+}
+
+/// Executes `:explain [--vm] <query>`: the query plan, or with `--vm` the bytecode program
+pub fn execute_explain_command(command: &str) -> Result<String> {
+    let command = command.trim();
+    let (vm, query) = match command.strip_prefix("--vm") {
+        Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => (true, rest.trim()),
+        _ => (false, command),
+    };
+    if let Some(option) = query.split_whitespace().next().filter(|arg| arg.starts_with("--")) {
+        return Err(TuiqlError::Command(format!("Unknown option '{}'", option)));
+    }
+    if query.is_empty() {
+        return Err(TuiqlError::Command("Usage: :explain [--vm] <query>".to_string()));
+    }
+
+    if vm {
+        explain_query_vm(query)
+    } else {
+        explain_query(query)
+    }
+}
+
 /// Executes EXPLAIN QUERY PLAN for a given SQL query and returns enhanced visualization
-pub fn explain_query_plan_enhanced(query: &str) -> Result<String> {
+///
+/// The query itself runs to gather runtime statistics; `cancellation_monitor` receives
+/// the connection's interrupt handle and `progress_reporter` the progress of that run.
+pub fn explain_query_plan_enhanced<F, P>(query: &str, cancellation_monitor: F, progress_reporter: P) -> Result<String>
+where
+    F: FnOnce(rusqlite::InterruptHandle) + Send + 'static,
+    P: FnMut(&db::QueryProgress) + Send + 'static,
+{
     let explain_query = format!("EXPLAIN QUERY PLAN {}", query.trim_end_matches(';'));
     let result = db::execute_query(&explain_query)?;
 
@@ -399,26 +1699,31 @@ pub fn explain_query_plan_enhanced(query: &str) -> Result<String> {
         }
     }
 
-    // Try to get actual query timing by running it (with LIMIT 1 for safety)
-    let time_query = if query.to_uppercase().contains("SELECT") &&
-                     !query.to_uppercase().contains("LIMIT") {
-        // Add LIMIT 1 to avoid timing large queries
-        format!("{} LIMIT 1", query)
-    } else {
-        query.to_string()
-    };
-
-    let start_time = std::time::Instant::now();
-    let _ = db::execute_query(&time_query);
-    let execution_time_ms = start_time.elapsed().as_millis();
+    // Run the query itself with scan status enabled for real timing and per-loop counts
+    let run = db::with_cancellable_connection(cancellation_monitor, progress_reporter, |conn, rows_produced| {
+        run_with_scan_stats(conn, query, rows_produced)
+    });
+    if let Err(e) = &run {
+        if db::is_interrupted(e) {
+            return Err(TuiqlError::Query("Query plan analysis cancelled by user (Ctrl+C)".to_string()));
+        }
+    }
 
     match render_plan_with_cost_overlay(&plan_output) {
         Ok(plan_text) => {
-            // Add timing information to the enhanced plan
-            let mut enhanced_output = String::new();
-            enhanced_output.push_str(&plan_text);
-            enhanced_output.insert_str(plan_text.find("\n\n").unwrap_or(plan_text.len()-1) + 1,
-            &format!("⏱️  Query execution time: {} ms\n", execution_time_ms));
+            let mut enhanced_output = plan_text;
+            match run {
+                Ok(run) => {
+                    enhanced_output.insert_str(
+                        enhanced_output.find("\n\n").unwrap_or(enhanced_output.len() - 1) + 1,
+                        &format!("⏱️  Query execution time: {} ms\n", run.execution_time_ms),
+                    );
+                    enhanced_output.push_str("\n\n");
+                    enhanced_output.push_str(&render_plan_with_scan_stats(&run));
+                }
+                Err(e) => enhanced_output.push_str(&format!("\n\n⚠️  Runtime statistics unavailable: {}", e)),
+            }
+            enhanced_output.push_str(&current_plan_hints(query));
             Ok(enhanced_output)
         }
         Err(_) => render_plan(&plan_output)
@@ -443,26 +1748,27 @@ pub fn explain_query_plan(query: &str) -> Result<String> {
         }
     }
 
-    render_plan(&plan_output)
+    let mut output = render_plan(&plan_output)?;
+    output.push_str(&current_plan_hints(query));
+    Ok(output)
 }
 
 /// Explains a query with enhanced visualization
-pub fn explain_query_enhanced(query: &str) -> Result<String> {
-    match explain_query_plan_enhanced(query) {
+pub fn explain_query_enhanced<F, P>(query: &str, cancellation_monitor: F, progress_reporter: P) -> Result<String>
+where
+    F: FnOnce(rusqlite::InterruptHandle) + Send + 'static,
+    P: FnMut(&db::QueryProgress) + Send + 'static,
+{
+    match explain_query_plan_enhanced(query, cancellation_monitor, progress_reporter) {
         Ok(plan) => Ok(plan),
+        Err(TuiqlError::Query(message)) if message.contains("cancelled by user") => Err(TuiqlError::Query(message)),
         Err(TuiqlError::Query(_) | TuiqlError::App(_)) => {
-            // Try a simpler approach - just run the query with EXPLAIN
-            let explain_stmt = format!("EXPLAIN {}", query.trim_end_matches(';'));
-            match db::execute_query(&explain_stmt) {
-                Ok(result) => {
-                    let mut output = String::from("=== EXPLAIN Output ===\n\n⚠️  Unable to provide enhanced plan analysis. Showing basic EXPLAIN output:\n\n");
-                    for row in &result.rows {
-                        output.push_str(&row.join(" | "));
-                        output.push('\n');
-                    }
-                    output.push_str("\n=== End EXPLAIN ===");
-                    Ok(output)
-                }
+            // Fall back to the statement's bytecode
+            match explain_query_vm(query) {
+                Ok(program) => Ok(format!(
+                    "⚠️  Unable to provide enhanced plan analysis. Showing the bytecode program instead:\n\n{}",
+                    program
+                )),
                 Err(_) => Ok("Unable to generate query plan. Ensure the query is valid and the database is connected.\n".to_string()),
             }
         }
@@ -475,18 +1781,9 @@ pub fn explain_query(query: &str) -> Result<String> {
     match explain_query_plan(query) {
         Ok(plan) => Ok(plan),
         Err(TuiqlError::Query(_) | TuiqlError::App(_)) => {
-            // Try a simpler approach - just run the query with EXPLAIN
-            let explain_stmt = format!("EXPLAIN {}", query.trim_end_matches(';'));
-            match db::execute_query(&explain_stmt) {
-                Ok(result) => {
-                    let mut output = String::from("=== EXPLAIN Output ===\n");
-                    for row in &result.rows {
-                        output.push_str(&row.join(" | "));
-                        output.push('\n');
-                    }
-                    output.push_str("=== End EXPLAIN ===");
-                    Ok(output)
-                }
+            // Fall back to the statement's bytecode
+            match explain_query_vm(query) {
+                Ok(program) => Ok(program),
                 Err(_) => Ok("Unable to generate query plan. Ensure the query is valid and the database is connected.\n".to_string()),
             }
         }
@@ -564,6 +1861,180 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_explain_vm() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT);
+             CREATE INDEX idx_users_email ON users(email);
+             CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER, title TEXT);",
+        )
+        .unwrap();
+
+        let program = explain_vm(
+            &conn,
+            "SELECT u.email, p.title FROM posts p JOIN users u ON u.id = p.user_id WHERE p.user_id > ? ORDER BY p.title",
+        )
+        .unwrap();
+        assert_eq!(program.instructions[0].opcode, "Init");
+
+        let targets: Vec<&str> = program.cursors.iter().map(|c| c.target.as_str()).collect();
+        assert!(targets.contains(&"users"));
+        assert!(targets.contains(&"posts"));
+        assert!(targets.contains(&"sorter"));
+
+        // Everything between Rewind and its Next is part of the loop body
+        let rewind = program.instructions.iter().position(|i| i.opcode == "Rewind").unwrap();
+        let next = program.instructions.iter().position(|i| i.opcode == "Next").unwrap();
+        assert!(program.instructions[rewind + 1..next].iter().all(|i| i.indent >= 1));
+        assert_eq!(program.instructions[next].indent, 0);
+
+        let rendered = render_vm_program(&program);
+        assert!(rendered.contains("=== Bytecode Program ==="));
+        assert!(rendered.contains("🗂️  Cursors:"));
+        assert!(rendered.contains("] posts"));
+        assert!(rendered.contains("  Column"));
+
+        // Comments are derived from the operands when SQLite does not provide them
+        let init = &program.instructions[0];
+        assert_eq!(init.comment, format!("Start at {}", init.p2));
+        let result_row = program.instructions.iter().find(|i| i.opcode == "ResultRow").unwrap();
+        assert_eq!(result_row.comment, format!("output=r[{}..{}]", result_row.p1, result_row.p1 + 1));
+        assert!(rendered.contains(&result_row.comment));
+
+        let program = explain_vm(&conn, "DELETE FROM users WHERE email = 'x'").unwrap();
+        assert!(program
+            .cursors
+            .iter()
+            .any(|c| c.target == "idx_users_email" && c.kind == "index" && c.writable));
+    }
+
+    #[test]
+    fn test_resolve_reused_cursors() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE users (id INTEGER PRIMARY KEY); CREATE TABLE posts (id INTEGER PRIMARY KEY);")
+            .unwrap();
+        let root = |name: &str| -> i64 {
+            conn.query_row("SELECT rootpage FROM sqlite_schema WHERE name = ?1", [name], |row| row.get(0)).unwrap()
+        };
+        let ins = |addr: i64, opcode: &str, p1: i64, p2: i64| VmInstruction {
+            addr,
+            opcode: opcode.to_string(),
+            p1,
+            p2,
+            p3: 0,
+            p4: String::new(),
+            p5: 0,
+            comment: String::new(),
+            indent: 0,
+        };
+        let instructions = vec![
+            ins(0, "OpenRead", 0, root("users")),
+            ins(1, "Rewind", 0, 3),
+            ins(2, "Close", 0, 0),
+            ins(3, "OpenRead", 0, root("posts")),
+            ins(4, "Rewind", 0, 5),
+        ];
+        let program = VmProgram { cursors: resolve_cursors(&conn, &instructions).unwrap(), instructions };
+
+        assert_eq!(program.cursors.len(), 2);
+        assert_eq!(program.cursor_at(0, 1).unwrap().target, "users");
+        assert_eq!(program.cursor_at(0, 4).unwrap().target, "posts");
+        let rendered = render_vm_program(&program);
+        assert!(rendered.contains("[0] users (table) at 0"));
+        assert!(rendered.contains("[0] posts (table) at 3"));
+    }
+
+    #[test]
+    fn test_plan_snapshot_comparison() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, name TEXT);
+             CREATE INDEX idx_users_email ON users(email);",
+        )
+        .unwrap();
+        let query = "SELECT * FROM users WHERE email = ?";
+        let with_index = PlanSnapshot::capture(&conn, query).unwrap().unwrap();
+        assert_eq!(with_index.lines, vec!["SEARCH users USING INDEX idx_users_email (email=?)"]);
+        assert!(PlanSnapshot::capture(&conn, "CREATE TABLE t (x)").unwrap().is_none());
+
+        conn.execute_batch("DROP INDEX idx_users_email").unwrap();
+        let without_index = PlanSnapshot::capture(&conn, query).unwrap().unwrap();
+        assert_ne!(with_index.schema_version, without_index.schema_version);
+        assert!(without_index.score().is_worse_than(&with_index.score()));
+
+        let changes = diff_plans(&with_index.lines, &without_index.lines);
+        assert_eq!(
+            changes,
+            vec![
+                PlanChange::Removed("SEARCH users USING INDEX idx_users_email (email=?)".to_string()),
+                PlanChange::Added("SCAN users".to_string()),
+            ]
+        );
+
+        let rendered = render_plan_comparison(&with_index, &without_index);
+        assert!(rendered.contains("🧬 The schema changed"));
+        assert!(rendered.contains("🔁 users: \"SCAN users\" replaced \"SEARCH users USING INDEX"));
+        assert!(rendered.contains("Plan got worse: full scans 0 → 1"));
+        assert!(render_plan_comparison(&with_index, &with_index).contains("✅ Plan unchanged"));
+
+        assert_eq!(normalize_plan_detail("SCAN TABLE  users"), "SCAN users");
+        assert_eq!(normalize_query_text("SELECT *\n  FROM users ;"), "SELECT * FROM users");
+    }
+
+    #[test]
+    fn test_find_plan_regressions() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT);
+             CREATE INDEX idx_users_email ON users(email);",
+        )
+        .unwrap();
+        let entry = |query: &str, timestamp: i64| {
+            let plan = PlanSnapshot::capture(&conn, query).unwrap();
+            let mut entry = HistoryEntry::new(query.to_string(), "test.db".to_string(), true, None, None)
+                .with_plan(plan.map(|plan| serde_json::to_string(&plan).unwrap()));
+            entry.timestamp = timestamp;
+            entry
+        };
+        let old_entries = [
+            entry("SELECT * FROM users WHERE email = 'a';", 2),
+            entry("SELECT * FROM users WHERE id = 1", 1),
+        ];
+        assert!(find_plan_regressions(&conn, &old_entries).unwrap().is_empty());
+
+        conn.execute_batch("DROP INDEX idx_users_email").unwrap();
+        // Running the query again under the new schema must not hide the regression
+        let mut entries = vec![entry("SELECT * FROM users WHERE email = 'a'", 3)];
+        entries.extend(old_entries);
+        let regressions = find_plan_regressions(&conn, &entries).unwrap();
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].query, "SELECT * FROM users WHERE email = 'a'");
+        assert_eq!(regressions[0].recorded_at, 2);
+
+        let rendered = render_plan_regressions(&regressions);
+        assert!(rendered.contains("1 query with a worse plan"));
+        assert!(rendered.contains("full scans 0 → 1"));
+        assert!(render_plan_regressions(&[]).contains("No plan regressions"));
+    }
+
+    #[test]
+    fn test_execute_explain_command_arguments() {
+        assert!(matches!(execute_explain_command(""), Err(TuiqlError::Command(_))));
+        assert!(matches!(execute_explain_command("--vm"), Err(TuiqlError::Command(_))));
+        assert!(matches!(execute_explain_command("--opcodes SELECT 1"), Err(TuiqlError::Command(_))));
+    }
+
+    #[test]
+    fn test_is_full_scan() {
+        assert!(PlanNode::new(0, -1, "SCAN TABLE users".to_string()).is_full_scan());
+        assert!(PlanNode::new(0, -1, "SCAN users".to_string()).is_full_scan());
+        assert!(!PlanNode::new(0, -1, "SEARCH users USING INDEX idx_users_email (email=?)".to_string()).is_full_scan());
+        assert!(!PlanNode::new(0, -1, "SCAN users USING INDEX idx_users_email".to_string()).is_full_scan());
+        assert!(!PlanNode::new(0, -1, "SCAN CONSTANT ROW".to_string()).is_full_scan());
+        assert!(!PlanNode::new(0, -1, "SCAN (subquery-1)".to_string()).is_full_scan());
+    }
+
     #[test]
     fn test_plan_node_table_extraction() {
         // Test with format that contains " FROM "
@@ -578,8 +2049,174 @@ mod tests {
         let complex_query_node = PlanNode::new(3, 2, "SELECT users.name FROM users u WHERE u.id > 10".to_string());
         assert_eq!(complex_query_node.get_table_name(), Some("users".to_string()));
 
+        // Test the format used since SQLite 3.36
+        let modern_scan_node = PlanNode::new(5, 0, "SEARCH posts USING INDEX idx_posts_user (user_id=?)".to_string());
+        assert_eq!(modern_scan_node.get_table_name(), Some("posts".to_string()));
+
         // Test node without table patterns
         let no_table_node = PlanNode::new(4, 3, "SCAN CONSTANT ROW".to_string());
         assert_eq!(no_table_node.get_table_name(), None);
     }
+
+    #[test]
+    fn test_index_used() {
+        let index_used = |detail: &str| PlanNode::new(2, 0, detail.to_string()).index_used();
+        assert_eq!(index_used("SEARCH posts USING INDEX idx_posts_user (user_id=?)"), Some("idx_posts_user".to_string()));
+        assert_eq!(index_used("SCAN users USING COVERING INDEX idx_users_email"), Some("idx_users_email".to_string()));
+        assert_eq!(index_used("SEARCH users USING INTEGER PRIMARY KEY (rowid=?)"), Some("INTEGER PRIMARY KEY".to_string()));
+        assert_eq!(index_used("SEARCH b USING AUTOMATIC COVERING INDEX (x=?)"), Some("AUTOMATIC INDEX".to_string()));
+        assert_eq!(index_used("SCAN users"), None);
+        assert_eq!(index_used("USE TEMP B-TREE FOR ORDER BY"), None);
+    }
+
+    #[test]
+    fn test_export_plan() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, country TEXT);
+             CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER, title TEXT);
+             CREATE INDEX idx_posts_user ON posts(user_id);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 50)
+             INSERT INTO users (email, country) SELECT 'u' || i, 'NL' FROM n;
+             ANALYZE;
+             DELETE FROM sqlite_stat1 WHERE idx = 'idx_posts_user';
+             INSERT INTO sqlite_stat1 VALUES ('posts', 'idx_posts_user', '1000 20');
+             ANALYZE sqlite_schema;",
+        )
+        .unwrap();
+
+        let plan = export_plan(&conn, "SELECT * FROM users u JOIN posts p ON p.user_id = u.id WHERE u.country = ?;").unwrap();
+        assert_eq!(plan.version, PLAN_EXPORT_VERSION);
+        assert!(!plan.query.ends_with(';'));
+        let scan = plan.nodes.iter().find(|node| node.full_scan).unwrap();
+        assert_eq!(scan.table.as_deref(), Some("users"));
+        assert_eq!(scan.estimated_cost, Some(50.0));
+        let search = plan.nodes.iter().find(|node| node.index.as_deref() == Some("idx_posts_user")).unwrap();
+        assert_eq!(search.estimated_cost, Some(20.0));
+        assert!(!search.full_scan);
+
+        let json = export_query_plan(&conn, "SELECT * FROM users WHERE id = 1", "json").unwrap();
+        let parsed: PlanExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.nodes[0].index.as_deref(), Some("INTEGER PRIMARY KEY"));
+        assert_eq!(parsed.nodes[0].estimated_cost, Some(1.0));
+
+        let dot = plan_to_dot(&plan);
+        assert!(dot.starts_with("digraph plan {"));
+        assert!(dot.contains(&format!("n0 -> n{};", scan.id)));
+        assert!(dot.contains("color=red"));
+        assert!(dot.contains("~20 rows"));
+
+        assert!(export_query_plan(&conn, "SELECT 1", "svg").is_err());
+
+        // Tables without statistics get no cost rather than a COUNT(*)
+        conn.execute_batch("CREATE TABLE fresh (id INTEGER PRIMARY KEY, note TEXT);").unwrap();
+        let plan = export_plan(&conn, "SELECT * FROM fresh WHERE note = 'x'").unwrap();
+        assert!(plan.nodes[0].full_scan);
+        assert_eq!(plan.nodes[0].estimated_cost, None);
+    }
+
+    #[test]
+    fn test_plan_hints() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, country TEXT);
+             CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER, title TEXT, created_at TEXT);
+             CREATE TABLE tags (post_id INTEGER, name TEXT);
+             CREATE INDEX idx_posts_user ON posts(user_id);",
+        )
+        .unwrap();
+        let schema = Schema::from_connection(&conn).unwrap();
+        let hints = |query: &str| plan_hints(&conn, &schema, query, &query_plan(&conn, query).unwrap()).unwrap();
+
+        let order = hints("SELECT * FROM posts WHERE user_id = ? ORDER BY created_at");
+        assert_eq!(order.len(), 1);
+        assert_eq!(
+            order[0].message,
+            "ORDER BY on `created_at` uses a temp B-tree; an index on (user_id, created_at) would avoid it"
+        );
+        assert_eq!(order[0].column_links(), vec!["posts.created_at"]);
+
+        let like = hints("SELECT * FROM users u WHERE u.email LIKE '%@example.com'");
+        assert!(like[0].message.starts_with("LIKE with leading % prevents index use on `email`"));
+        assert_eq!(like[0].column_links(), vec!["users.email"]);
+
+        let wrapped = hints("SELECT * FROM users WHERE lower(email) = ?");
+        assert!(wrapped[0].message.contains("Wrapping `email` in lower()"));
+
+        let missing = hints("SELECT * FROM users WHERE country = ? AND email > ?");
+        assert!(missing.iter().any(|hint| hint.message.contains("an index on (country, email)")));
+
+        let automatic = hints("SELECT * FROM users u JOIN tags t ON t.name = u.email");
+        assert!(automatic
+            .iter()
+            .any(|hint| hint.message.starts_with("SQLite builds an automatic index on `name` of `tags`")));
+
+        assert!(hints("SELECT * FROM posts WHERE user_id = ?").is_empty());
+        assert!(render_plan_hints(&[]).is_empty());
+        assert!(render_plan_hints(&like).contains("🔗 users.email"));
+    }
+
+    #[test]
+    fn test_run_with_scan_stats() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE events (id INTEGER PRIMARY KEY, kind INTEGER, payload TEXT);
+             CREATE INDEX idx_events_kind ON events(kind);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 1000)
+             INSERT INTO events (kind, payload) SELECT i % 2, 'x' FROM n;",
+        )
+        .unwrap();
+
+        let rows_produced = AtomicU64::new(0);
+        let run = run_with_scan_stats(&conn, "SELECT * FROM events WHERE kind = 1;", &rows_produced).unwrap();
+        assert_eq!(run.rows_returned, 500);
+        assert_eq!(rows_produced.load(Ordering::Relaxed), 500);
+        let search = run.nodes.iter().find(|n| n.details.contains("idx_events_kind")).unwrap();
+        let rendered = render_plan_with_scan_stats(&run);
+        assert!(rendered.contains("500 row(s) returned"));
+
+        if cfg!(feature = "scanstatus") {
+            let stats = search.stats.unwrap();
+            assert_eq!(stats.loops, 1);
+            assert_eq!(stats.rows_visited, 500);
+            assert_eq!(stats.actual_rows(), 500.0);
+            // Without ANALYZE the planner assumes about 10 rows per index key
+            assert!(stats.estimate_divergence() >= ESTIMATE_DIVERGENCE_WARN);
+            assert!(rendered.contains("rows visited 500"));
+            assert!(rendered.contains("❗ Estimate off by"));
+        } else {
+            assert!(search.stats.is_none());
+            assert!(run.counters.unwrap().vm_steps > 0);
+            assert!(rendered.contains("VM steps"));
+            assert!(rendered.contains("--features scanstatus"));
+        }
+    }
+
+    #[test]
+    fn test_run_with_scan_stats_rolls_back_writes() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1), (2), (3);").unwrap();
+
+        let rows_produced = AtomicU64::new(0);
+        let run = run_with_scan_stats(&conn, "DELETE FROM t WHERE x > 1", &rows_produced).unwrap();
+        if cfg!(feature = "scanstatus") {
+            assert!(run.nodes.iter().any(|n| n.stats.is_some_and(|s| s.rows_visited == 3)));
+        } else {
+            assert!(run.counters.unwrap().fullscan_steps > 0);
+        }
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 3);
+
+        assert!(run_with_scan_stats(&conn, "SELECT * FROM missing", &rows_produced).is_err());
+        assert!(conn.is_autocommit());
+    }
+
+    #[test]
+    fn test_scan_stats_divergence() {
+        let stats = ScanStats { loops: 4, rows_visited: 400, estimated_rows: 10.0 };
+        assert_eq!(stats.actual_rows(), 100.0);
+        assert_eq!(stats.estimate_divergence(), 10.0);
+        let idle = ScanStats { loops: 0, rows_visited: 0, estimated_rows: 0.5 };
+        assert_eq!(idle.estimate_divergence(), 1.0);
+    }
 }
@@ -1,13 +1,13 @@
-/// Plugin system for TUIQL
-///
-/// This module provides infrastructure for extending TUIQL with external plugins.
-/// Plugins are executable programs that communicate with TUIQL via JSON-RPC over stdio.
-///
-/// The plugin system supports:
-/// - Command plugins: Register palette commands
-/// - Panel plugins: Custom UI panels
-/// - Export plugins: Custom export formats
-/// - WASI plugins: Dynamic registration and execution
+//! Plugin system for TUIQL
+//!
+//! This module provides infrastructure for extending TUIQL with external plugins.
+//! Plugins are executable programs that communicate with TUIQL via JSON-RPC over stdio.
+//!
+//! The plugin system supports:
+//! - Command plugins: Register palette commands
+//! - Panel plugins: Custom UI panels
+//! - Export plugins: Custom export formats
+//! - WASI plugins: Dynamic registration and execution
 
 use crate::config::PluginSpec;
 use crate::core::{Result, TuiqlError};
@@ -308,7 +308,7 @@ impl PluginManager {
             override_name
         } else {
             // Extract name from URL: e.g., "gh:user/repo" -> "repo"
-            url.split('/').last()
+            url.split('/').next_back()
                 .unwrap_or("unknown_plugin")
                 .trim_end_matches(".git")
                 .to_string()
@@ -0,0 +1,492 @@
+//! Table Profiling Module
+//!
+//! Computes per-column statistics for a table — null percentage, distinct count,
+//! min/max, average length, top values, type-affinity mismatches and a histogram —
+//! over a bounded number of rows. Profiles are shown as a `ResultsGrid` and cached
+//! in `Storage` so reopening one does not rescan the table.
+
+use crate::core::db::schema::{quote_identifier, Affinity};
+use crate::core::{Result, TuiqlError};
+use crate::db;
+use crate::results_grid::{ResultsGrid, Viewport};
+use crate::storage::Storage;
+use rusqlite::{types::ValueRef, Connection};
+use serde::{Deserialize, Serialize};
+
+/// Rows scanned per table unless `--limit` or `--exact` is given
+pub const DEFAULT_ROW_LIMIT: usize = 100_000;
+
+/// Bars used to draw histograms, lowest to highest
+const HISTOGRAM_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Longest value shown in the grid before it is truncated
+const MAX_VALUE_WIDTH: usize = 24;
+
+/// How a profile is computed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileOptions {
+    /// Maximum rows scanned, `None` to scan the whole table
+    pub row_limit: Option<usize>,
+    /// Number of most frequent values kept per column
+    pub top_k: usize,
+    /// Number of histogram buckets for numeric columns
+    pub buckets: usize,
+}
+
+impl Default for ProfileOptions {
+    fn default() -> Self {
+        ProfileOptions {
+            row_limit: Some(DEFAULT_ROW_LIMIT),
+            top_k: 5,
+            buckets: 10,
+        }
+    }
+}
+
+/// Statistics of a single column
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ColumnProfile {
+    pub name: String,
+    pub declared_type: String,
+    pub nulls: u64,
+    pub distinct: u64,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    /// Average length in characters (bytes for blobs) of non-null values
+    pub avg_length: Option<f64>,
+    /// Most frequent non-null values with their counts
+    pub top_values: Vec<(String, u64)>,
+    /// Values whose storage class does not fit the column affinity
+    pub type_mismatches: u64,
+    /// Row counts of equal-width buckets between min and max; empty for non-numeric columns
+    pub histogram: Vec<u64>,
+}
+
+/// Statistics of all columns of a table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableProfile {
+    pub table: String,
+    pub options: ProfileOptions,
+    pub rows_scanned: u64,
+    /// Whether the table has more rows than were scanned, so the statistics describe a sample
+    pub sampled: bool,
+    /// Largest rowid when the profile was computed; `None` for WITHOUT ROWID tables
+    #[serde(default)]
+    pub max_rowid: Option<i64>,
+    pub columns: Vec<ColumnProfile>,
+}
+
+impl TableProfile {
+    /// Whether a cached profile still applies: same options, and no rows were
+    /// appended since, which a cheap `max(rowid)` lookup detects without a scan
+    pub fn is_current(&self, options: &ProfileOptions, max_rowid: Option<i64>) -> bool {
+        self.options == *options && self.max_rowid == max_rowid
+    }
+
+    /// Renders the profile as one grid row per column.
+    pub fn to_grid(&self) -> ResultsGrid {
+        let mut grid = ResultsGrid::new();
+        grid.set_headers(
+            ["column", "type", "null %", "distinct", "min", "max", "avg len", "mismatches", "top values", "histogram"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+        );
+        for column in &self.columns {
+            grid.add_row(vec![
+                column.name.clone(),
+                column.declared_type.clone(),
+                format!("{:.1}", percent(column.nulls, self.rows_scanned)),
+                column.distinct.to_string(),
+                column.min.as_deref().map(truncate).unwrap_or_else(|| "NULL".to_string()),
+                column.max.as_deref().map(truncate).unwrap_or_else(|| "NULL".to_string()),
+                column.avg_length.map(|l| format!("{:.1}", l)).unwrap_or_default(),
+                column.type_mismatches.to_string(),
+                column
+                    .top_values
+                    .iter()
+                    .map(|(value, count)| format!("{} ({})", truncate(value), count))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                sparkline(&column.histogram),
+            ]);
+        }
+        grid.viewport = Viewport::new(0, grid.rows.len());
+        grid
+    }
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+fn truncate(value: &str) -> String {
+    if value.chars().count() > MAX_VALUE_WIDTH {
+        format!("{}…", value.chars().take(MAX_VALUE_WIDTH - 1).collect::<String>())
+    } else {
+        value.to_string()
+    }
+}
+
+/// Draws bucket counts as a bar per bucket, scaled to the largest bucket.
+pub fn sparkline(counts: &[u64]) -> String {
+    let max = counts.iter().copied().max().unwrap_or(0);
+    if max == 0 {
+        return String::new();
+    }
+    counts
+        .iter()
+        .map(|&count| {
+            if count == 0 {
+                ' '
+            } else {
+                HISTOGRAM_BARS[((count * (HISTOGRAM_BARS.len() as u64 - 1)) / max) as usize]
+            }
+        })
+        .collect()
+}
+
+/// SQL condition matching values whose storage class does not fit the affinity
+fn mismatch_condition(affinity: Affinity, column: &str) -> Option<String> {
+    match affinity {
+        Affinity::Integer | Affinity::Real | Affinity::Numeric => {
+            Some(format!("typeof({}) IN ('text', 'blob')", column))
+        }
+        Affinity::Text => Some(format!("typeof({}) = 'blob'", column)),
+        Affinity::Blob => None,
+    }
+}
+
+/// Profiles every column of `table`, scanning at most `options.row_limit` rows.
+pub fn profile_table(conn: &Connection, table: &str, options: &ProfileOptions) -> Result<TableProfile> {
+    let mut stmt = conn.prepare("SELECT name, type FROM pragma_table_info(?1) ORDER BY cid")?;
+    let columns: Vec<(String, String)> = stmt
+        .query_map([table], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    if columns.is_empty() {
+        return Err(TuiqlError::Schema(format!("Table '{}' not found", table)));
+    }
+
+    let source = match options.row_limit {
+        Some(limit) => format!("(SELECT * FROM {} LIMIT {})", quote_identifier(table), limit),
+        None => quote_identifier(table),
+    };
+    // Counting one row past the limit tells whether the table was sampled without scanning it;
+    // only --exact counts every row
+    let counted: u64 = conn.query_row(
+        &format!(
+            "SELECT COUNT(*) FROM (SELECT 1 FROM {}{})",
+            quote_identifier(table),
+            options.row_limit.map(|limit| format!(" LIMIT {}", limit + 1)).unwrap_or_default()
+        ),
+        [],
+        |row| row.get(0),
+    )?;
+    let rows_scanned = options.row_limit.map_or(counted, |limit| counted.min(limit as u64));
+
+    let mut profiles = Vec::with_capacity(columns.len());
+    for (name, declared_type) in columns {
+        let column = quote_identifier(&name);
+        let mismatches = mismatch_condition(Affinity::of(&declared_type), &column)
+            .map(|condition| format!("SUM({})", condition))
+            .unwrap_or_else(|| "0".to_string());
+
+        let mut profile = conn.query_row(
+            &format!(
+                "SELECT COUNT(*) - COUNT({c}), COUNT(DISTINCT {c}), MIN({c}), MAX({c}),
+                        AVG(LENGTH({c})), COALESCE({m}, 0)
+                 FROM {s}",
+                c = column,
+                m = mismatches,
+                s = source
+            ),
+            [],
+            |row| {
+                let value = |i| -> rusqlite::Result<Option<String>> {
+                    let value = row.get_ref(i)?;
+                    Ok((value != ValueRef::Null).then(|| db::format_value(value)))
+                };
+                Ok(ColumnProfile {
+                    name: name.clone(),
+                    declared_type: declared_type.clone(),
+                    nulls: row.get(0)?,
+                    distinct: row.get(1)?,
+                    min: value(2)?,
+                    max: value(3)?,
+                    avg_length: row.get(4)?,
+                    type_mismatches: row.get(5)?,
+                    ..Default::default()
+                })
+            },
+        )?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {c}, COUNT(*) AS n FROM {s} WHERE {c} IS NOT NULL GROUP BY {c} ORDER BY n DESC, {c} LIMIT {k}",
+            c = column,
+            s = source,
+            k = options.top_k
+        ))?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            profile.top_values.push((db::format_value(row.get_ref(0)?), row.get(1)?));
+        }
+
+        profile.histogram = histogram(conn, &column, &source, options.buckets)?;
+        profiles.push(profile);
+    }
+
+    Ok(TableProfile {
+        table: table.to_string(),
+        options: options.clone(),
+        rows_scanned,
+        sampled: rows_scanned < counted,
+        max_rowid: max_rowid(conn, table)?,
+        columns: profiles,
+    })
+}
+
+/// Largest rowid of `table`, a b-tree lookup rather than a scan; `None` for WITHOUT ROWID tables
+fn max_rowid(conn: &Connection, table: &str) -> Result<Option<i64>> {
+    let without_rowid: bool = conn.query_row(
+        "SELECT wr FROM pragma_table_list WHERE schema = 'main' AND name = ?1 COLLATE NOCASE",
+        [table],
+        |row| row.get(0),
+    )?;
+    if without_rowid {
+        return Ok(None);
+    }
+    Ok(conn.query_row(&format!("SELECT max(rowid) FROM {}", quote_identifier(table)), [], |row| row.get(0))?)
+}
+
+/// Counts numeric values of a column in equal-width buckets between its min and max
+fn histogram(conn: &Connection, column: &str, source: &str, buckets: usize) -> Result<Vec<u64>> {
+    let numeric = format!("typeof({}) IN ('integer', 'real')", column);
+    let (low, high): (Option<f64>, Option<f64>) = conn.query_row(
+        &format!("SELECT MIN({c}), MAX({c}) FROM {s} WHERE {n}", c = column, s = source, n = numeric),
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let (Some(low), Some(high)) = (low, high) else {
+        return Ok(Vec::new());
+    };
+    if buckets == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut counts = vec![0u64; buckets];
+    if high <= low {
+        counts[0] = conn.query_row(&format!("SELECT COUNT(*) FROM {} WHERE {}", source, numeric), [], |row| row.get(0))?;
+        return Ok(counts);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT MIN(CAST(({c} - ?1) * ?3 / (?2 - ?1) AS INTEGER), ?3 - 1) AS bucket, COUNT(*)
+         FROM {s} WHERE {n} GROUP BY bucket",
+        c = column,
+        s = source,
+        n = numeric
+    ))?;
+    let mut rows = stmt.query(rusqlite::params![low, high, buckets as i64])?;
+    while let Some(row) = rows.next()? {
+        let bucket: i64 = row.get(0)?;
+        counts[bucket.clamp(0, buckets as i64 - 1) as usize] += row.get::<_, u64>(1)?;
+    }
+    Ok(counts)
+}
+
+/// Formats a profile grid with a summary line.
+pub fn format_profile(profile: &TableProfile) -> String {
+    let scope = if profile.sampled {
+        format!("first {} rows", profile.rows_scanned)
+    } else {
+        format!("{} rows", profile.rows_scanned)
+    };
+    let mut output = format!("📊 Profile of {} ({})\n", profile.table, scope);
+    output.push_str(&profile.to_grid().render());
+    if profile.sampled {
+        output.push_str("💡 Statistics describe a sample; use --exact to scan the whole table\n");
+    }
+    output
+}
+
+/// Executes `:profile <table> [--limit N] [--exact] [--top K] [--buckets N] [--refresh]`.
+///
+/// The cached profile is shown when one exists for the same options and the table's
+/// largest rowid is unchanged, unless `--refresh` is given. Profiling can be cancelled
+/// through `cancellation_monitor`, as in `db::with_cancellable_connection`.
+pub fn execute_profile_command<F>(command: &str, storage: &Storage, cancellation_monitor: F) -> Result<()>
+where
+    F: FnOnce(rusqlite::InterruptHandle) + Send + 'static,
+{
+    let mut options = ProfileOptions::default();
+    let mut refresh = false;
+    let mut table = None;
+
+    let mut args = command.split_whitespace();
+    while let Some(arg) = args.next() {
+        let mut number = |flag: &str| -> Result<usize> {
+            args.next()
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| TuiqlError::Command(format!("{} requires a number", flag)))
+        };
+        match arg {
+            "--limit" => options.row_limit = Some(number("--limit")?),
+            "--exact" => options.row_limit = None,
+            "--top" => options.top_k = number("--top")?,
+            "--buckets" => options.buckets = number("--buckets")?,
+            "--refresh" => refresh = true,
+            other if other.starts_with("--") => {
+                return Err(TuiqlError::Command(format!("Unknown option '{}'", other)))
+            }
+            other => {
+                if table.replace(other).is_some() {
+                    return Err(TuiqlError::Command("Only one table can be profiled at a time".to_string()));
+                }
+            }
+        }
+    }
+    let table = table.ok_or_else(|| {
+        TuiqlError::Command("Usage: :profile <table> [--limit N] [--exact] [--top K] [--buckets N] [--refresh]".to_string())
+    })?;
+
+    let database_path = db::DB_STATE
+        .get()
+        .and_then(|state| state.lock().ok()?.current_path.clone())
+        .unwrap_or_else(|| "main".to_string());
+
+    let profile = db::with_cancellable_connection(cancellation_monitor, |_| {}, |conn, _| {
+        if !refresh {
+            if let Some((cached, timestamp)) = storage.get_table_profile(&database_path, table)? {
+                if let Ok(profile) = serde_json::from_str::<TableProfile>(&cached) {
+                    if profile.is_current(&options, max_rowid(conn, table)?) {
+                        let cached_at = chrono::DateTime::from_timestamp(timestamp, 0)
+                            .unwrap_or_default()
+                            .format("%Y-%m-%d %H:%M:%S");
+                        print!("{}", format_profile(&profile));
+                        println!("🗄️  Cached at {}; use --refresh to recompute", cached_at);
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+        profile_table(conn, table, &options).map(Some)
+    })
+    .map_err(|e| {
+        if db::is_interrupted(&e) {
+            TuiqlError::Query("Profiling cancelled by user (Ctrl+C)".to_string())
+        } else {
+            e
+        }
+    })?;
+    let Some(profile) = profile else {
+        return Ok(());
+    };
+    storage.save_table_profile(&database_path, table, &serde_json::to_string(&profile)?)?;
+    print!("{}", format_profile(&profile));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT, age INTEGER, photo BLOB);
+             INSERT INTO people (name, age) VALUES
+                 ('ann', 30), ('bob', 40), ('ann', NULL), ('cy', 'unknown'), (NULL, 50);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_profile_table() {
+        let conn = setup();
+        let profile = profile_table(&conn, "people", &ProfileOptions::default()).unwrap();
+        assert_eq!(profile.rows_scanned, 5);
+        assert!(!profile.sampled);
+
+        let name = &profile.columns[1];
+        assert_eq!(name.nulls, 1);
+        assert_eq!(name.distinct, 3);
+        assert_eq!(name.min.as_deref(), Some("ann"));
+        assert_eq!(name.max.as_deref(), Some("cy"));
+        assert_eq!(name.top_values[0], ("ann".to_string(), 2));
+        assert_eq!(name.avg_length, Some(2.75));
+        assert!(name.histogram.is_empty());
+
+        let age = &profile.columns[2];
+        assert_eq!(age.nulls, 1);
+        assert_eq!(age.type_mismatches, 1);
+        assert_eq!(age.histogram.len(), 10);
+        assert_eq!(age.histogram.iter().sum::<u64>(), 3);
+        assert_eq!(age.histogram[0], 1);
+        assert_eq!(age.histogram[9], 1);
+
+        let photo = &profile.columns[3];
+        assert_eq!(photo.nulls, 5);
+        assert_eq!(photo.min, None);
+        assert!(photo.top_values.is_empty());
+
+        assert!(profile_table(&conn, "missing", &ProfileOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_profile_row_limit() {
+        let conn = setup();
+        let options = ProfileOptions {
+            row_limit: Some(2),
+            top_k: 1,
+            buckets: 4,
+        };
+        let profile = profile_table(&conn, "people", &options).unwrap();
+        assert_eq!(profile.rows_scanned, 2);
+        assert!(profile.sampled);
+        assert_eq!(profile.columns[1].top_values.len(), 1);
+        assert_eq!(profile.columns[2].histogram, vec![1, 0, 0, 1]);
+
+        // A limit equal to the table size scans every row
+        let options = ProfileOptions { row_limit: Some(5), ..options };
+        let profile = profile_table(&conn, "people", &options).unwrap();
+        assert_eq!(profile.rows_scanned, 5);
+        assert!(!profile.sampled);
+    }
+
+    #[test]
+    fn test_profile_grid_and_cache_round_trip() {
+        let conn = setup();
+        let profile = profile_table(&conn, "people", &ProfileOptions::default()).unwrap();
+
+        let grid = profile.to_grid();
+        assert_eq!(grid.rows.len(), 4);
+        assert_eq!(grid.headers[2], "null %");
+        assert_eq!(grid.rows[1].cells[2].content, "20.0");
+        assert!(format_profile(&profile).contains("📊 Profile of people (5 rows)"));
+
+        let json = serde_json::to_string(&profile).unwrap();
+        assert_eq!(serde_json::from_str::<TableProfile>(&json).unwrap(), profile);
+
+        // The cached profile goes stale once rows are added
+        let options = ProfileOptions::default();
+        assert_eq!(profile.max_rowid, Some(5));
+        assert!(profile.is_current(&options, max_rowid(&conn, "people").unwrap()));
+        conn.execute("INSERT INTO people (name) VALUES ('dee')", []).unwrap();
+        assert!(!profile.is_current(&options, max_rowid(&conn, "people").unwrap()));
+        assert!(!profile.is_current(&ProfileOptions { top_k: 1, ..options }, Some(5)));
+
+        conn.execute_batch("CREATE TABLE tags (name TEXT PRIMARY KEY) WITHOUT ROWID").unwrap();
+        assert_eq!(max_rowid(&conn, "tags").unwrap(), None);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[0, 0]), "");
+        assert_eq!(sparkline(&[1, 0, 7]), "▂ █");
+    }
+}
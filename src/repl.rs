@@ -1,7 +1,9 @@
 use crate::{
-    db, schema_navigator, schema_map,
-    storage::{HistoryEntry, Storage},
-    plan, fts5, json1, sql_completer::SqlCompleter, diff,
+    analyze, bench, catalog, db, schema_navigator, schema_map,
+    core::db::schema,
+    core::{Result, TuiqlError},
+    storage::{HistoryEntry, SchemaSnapshot, Storage},
+    plan, fts5, json1, sql_completer::SqlCompleter, diff, migrate, fkcheck, health, index_advisor, space, profile, schema_lint,
     results_grid::ResultsGrid,
     plugins::PluginManager,
     query_editor::QueryEditor,
@@ -22,6 +24,8 @@ use std::time::Instant;
 struct ReplState {
     /// Stores the last query result for export functionality
     pub last_result_grid: Option<ResultsGrid>,
+    /// Whether statement status counters are shown and recorded after each query
+    pub stats_enabled: bool,
 }
 
 impl ReplState {
@@ -41,6 +45,7 @@ impl ReplState {
     fn new() -> Self {
         Self {
             last_result_grid: None,
+            stats_enabled: false,
         }
     }
 
@@ -128,7 +133,7 @@ pub enum Command {
     Commit,
     Rollback,
     Pragma { name: String, value: Option<String> },
-    Plan,
+    Plan(Option<String>),
     PlanEnhanced,
     Fmt,
     Export { format: String, filename: Option<String> },
@@ -136,17 +141,138 @@ pub enum Command {
     Erd(Option<String>),
     Fts5(Option<String>),
     Json1(Option<String>),
+    Migrate(Option<String>),
+    FkCheck(Option<String>),
+    Health(Option<String>),
+    Space(Option<String>),
+    Profile(Option<String>),
+    Lint(Option<String>),
+    Stats(Option<String>),
+    Advise(Option<String>),
+    Bench(Option<String>),
+    Explain(Option<String>),
+    Analyze(Option<String>),
     Hist,
     Snip(String),
-    Diff { db_a: String, db_b: String },
+    Diff { db_a: String, db_b: String, format: String, renames: diff::RenameOptions },
+    DiffSince { snapshot: i64, format: String, renames: diff::RenameOptions },
+    Schema(Option<String>),
     Plugin { name: String, args: Vec<String> },
     NextPage,
     Help,
     Sql(String),
-    Tables,
+    Tables(Option<String>),
     Unknown(String),
 }
 
+/// Returns the current connection's database path and a snapshot of its schema.
+fn current_schema_snapshot() -> Result<SchemaSnapshot> {
+    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::App("No database connection found. Please connect to a database first.".to_string()))?;
+    let state_guard = state_cell
+        .lock()
+        .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+    let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+    let path = state_guard.current_path.as_deref().map_or_else(|| "main".to_string(), snapshot_database_key);
+
+    Ok(SchemaSnapshot::new(path, schema::schema_version(conn)?, schema::schema_ddl(conn)?))
+}
+
+/// Key under which a database's schema snapshots are stored: the canonical path of
+/// the file, so `app.db`, `./app.db` and its absolute path share one history
+fn snapshot_database_key(path: &str) -> String {
+    std::fs::canonicalize(path).map_or_else(|_| path.to_string(), |canonical| canonical.display().to_string())
+}
+
+/// Stores a snapshot of the connected database's schema unless it is already recorded.
+fn record_schema_snapshot(storage: &Storage) -> Result<Option<i64>> {
+    let snapshot = current_schema_snapshot()?;
+    Ok(storage.add_schema_snapshot(&snapshot)?)
+}
+
+/// Lists the schema snapshots stored for the connected database.
+fn schema_history(storage: &Storage) -> Result<String> {
+    let current = current_schema_snapshot()?;
+    let snapshots = storage.get_schema_snapshots(&current.database_path)?;
+
+    if snapshots.is_empty() {
+        return Ok(format!("No schema snapshots recorded for '{}'", current.database_path));
+    }
+
+    let mut output = format!("🕰️  Schema snapshots for '{}' ({}):\n", current.database_path, snapshots.len());
+    for snapshot in snapshots {
+        let timestamp = chrono::DateTime::from_timestamp(snapshot.timestamp, 0)
+            .unwrap_or_default()
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let marker = if snapshot.fingerprint == current.fingerprint { " (current)" } else { "" };
+        output.push_str(&format!(
+            "  #{} [{}] schema_version {} fingerprint {}{}\n",
+            snapshot.id, timestamp, snapshot.schema_version, snapshot.fingerprint, marker
+        ));
+    }
+    output.push_str("\n💡 Use ':diff --since <id>' to see what changed since a snapshot");
+    Ok(output)
+}
+
+/// Compares a stored schema snapshot against the connected database.
+fn diff_since_snapshot(storage: &Storage, id: i64, format: &str, renames: &diff::RenameOptions) -> Result<String> {
+    let snapshot = storage
+        .get_schema_snapshot(id)?
+        .ok_or_else(|| TuiqlError::Command(format!("Schema snapshot #{} not found", id)))?;
+    let current = current_schema_snapshot()?;
+
+    let comparison = diff::compare_schemas_with(
+        &schema::Schema::from_ddl(&snapshot.ddl)?,
+        &catalog::current_catalog()?.schema,
+        renames,
+    )?;
+    let source = format!(
+        "{} @ snapshot #{} (schema_version {})",
+        snapshot.database_path, snapshot.id, snapshot.schema_version
+    );
+    let target = format!("{} (schema_version {})", current.database_path, current.schema_version);
+    diff::render_comparison(&comparison, &source, &target, format)
+}
+
+/// Looks up the value of a `--name value` or `--name=value` flag in command arguments.
+fn parse_flag(args: &[&str], name: &str) -> Option<String> {
+    let long = format!("--{}", name);
+    let prefix = format!("{}=", long);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if *arg == long {
+            return iter.next().map(|v| v.to_string());
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Collects `--rename [table.]old=new` and `--no-rename [[table.]old=new]` flags;
+/// `--no-rename` without a pairing turns rename detection off.
+fn parse_rename_options(args: &[&str]) -> Result<diff::RenameOptions> {
+    let mut options = diff::RenameOptions::default();
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        if *arg == "--rename" {
+            let value = iter.next().ok_or_else(|| TuiqlError::Command("--rename requires old=new".to_string()))?;
+            options.accepted.push(diff::RenamePair::parse(value)?);
+        } else if let Some(value) = arg.strip_prefix("--rename=") {
+            options.accepted.push(diff::RenamePair::parse(value)?);
+        } else if *arg == "--no-rename" {
+            match iter.next_if(|next| next.contains('=') && !next.starts_with("--")) {
+                Some(value) => options.rejected.push(diff::RenamePair::parse(value)?),
+                None => options.disabled = true,
+            }
+        } else if let Some(value) = arg.strip_prefix("--no-rename=") {
+            options.rejected.push(diff::RenamePair::parse(value)?);
+        }
+    }
+    Ok(options)
+}
+
 /// Parses a user input string into a corresponding `Command`.
 ///
 /// If the input starts with a colon (`:`), it is interpreted as a command.
@@ -197,7 +323,14 @@ pub fn parse_command(input: &str) -> Command {
                 Command::Unknown(input.to_string())
             }
         }
-        "plan" => Command::Plan,
+        "plan" => {
+            let args = trimmed[parts[0].len()..].trim();
+            if args.is_empty() {
+                Command::Plan(None)
+            } else {
+                Command::Plan(Some(args.to_string()))
+            }
+        }
         "plan_enhanced" => Command::PlanEnhanced,
         "fmt" => Command::Fmt,
         "export" => {
@@ -222,7 +355,7 @@ pub fn parse_command(input: &str) -> Command {
         }
         "erd" => {
             if parts.len() >= 2 {
-                Command::Erd(Some(parts[1].to_string()))
+                Command::Erd(Some(parts[1..].join(" ")))
             } else {
                 Command::Erd(None)
             }
@@ -241,6 +374,94 @@ pub fn parse_command(input: &str) -> Command {
                 Command::Json1(None)
             }
         }
+        "migrate" => {
+            if parts.len() >= 2 {
+                Command::Migrate(Some(parts[1..].join(" ")))
+            } else {
+                Command::Migrate(None)
+            }
+        }
+        "fkcheck" => {
+            if parts.len() >= 2 {
+                Command::FkCheck(Some(parts[1..].join(" ")))
+            } else {
+                Command::FkCheck(None)
+            }
+        }
+        "health" => {
+            if parts.len() >= 2 {
+                Command::Health(Some(parts[1..].join(" ")))
+            } else {
+                Command::Health(None)
+            }
+        }
+        "space" => {
+            if parts.len() >= 2 {
+                Command::Space(Some(parts[1..].join(" ")))
+            } else {
+                Command::Space(None)
+            }
+        }
+        "profile" => {
+            if parts.len() >= 2 {
+                Command::Profile(Some(parts[1..].join(" ")))
+            } else {
+                Command::Profile(None)
+            }
+        }
+        "lint" => {
+            if parts.len() >= 2 {
+                Command::Lint(Some(parts[1..].join(" ")))
+            } else {
+                Command::Lint(None)
+            }
+        }
+        "stats" => {
+            if parts.len() >= 2 {
+                Command::Stats(Some(parts[1].to_string()))
+            } else {
+                Command::Stats(None)
+            }
+        }
+        "analyze" => {
+            if parts.len() >= 2 {
+                Command::Analyze(Some(parts[1..].join(" ")))
+            } else {
+                Command::Analyze(None)
+            }
+        }
+        "explain" => {
+            let query = trimmed[parts[0].len()..].trim();
+            if query.is_empty() {
+                Command::Explain(None)
+            } else {
+                Command::Explain(Some(query.to_string()))
+            }
+        }
+        "advise" => {
+            // Keep the query text as typed, string literals included
+            let query = trimmed[parts[0].len()..].trim();
+            if query.is_empty() {
+                Command::Advise(None)
+            } else {
+                Command::Advise(Some(query.to_string()))
+            }
+        }
+        "bench" => {
+            let args = trimmed[parts[0].len()..].trim();
+            if args.is_empty() {
+                Command::Bench(None)
+            } else {
+                Command::Bench(Some(args.to_string()))
+            }
+        }
+        "schema" => {
+            if parts.len() >= 2 {
+                Command::Schema(Some(parts[1..].join(" ")))
+            } else {
+                Command::Schema(None)
+            }
+        }
         "hist" => Command::Hist,
         "snip" => {
             if parts.len() >= 2 {
@@ -250,10 +471,21 @@ pub fn parse_command(input: &str) -> Command {
             }
         }
         "diff" => {
-            if parts.len() >= 3 {
+            let format = parse_flag(&parts[1..], "format").unwrap_or_else(|| "text".to_string());
+            let Ok(renames) = parse_rename_options(&parts[1..]) else {
+                return Command::Unknown(input.to_string());
+            };
+            if let Some(since) = parse_flag(&parts[1..], "since") {
+                match since.parse() {
+                    Ok(snapshot) => Command::DiffSince { snapshot, format, renames },
+                    Err(_) => Command::Unknown(input.to_string()),
+                }
+            } else if parts.len() >= 3 {
                 Command::Diff {
                     db_a: parts[1].to_string(),
                     db_b: parts[2].to_string(),
+                    format,
+                    renames,
                 }
             } else {
                 Command::Unknown(input.to_string())
@@ -269,12 +501,62 @@ pub fn parse_command(input: &str) -> Command {
             }
         }
         "help" => Command::Help,
-        "tables" => Command::Tables,
+        "tables" => {
+            if parts.len() >= 2 {
+                Command::Tables(Some(parts[1..].join(" ")))
+            } else {
+                Command::Tables(None)
+            }
+        }
         "nextpage" => Command::NextPage,
         _ => Command::Unknown(input.to_string()),
     }
 }
 
+/// Interrupts the running statement through its connection's interrupt handle
+type CancellationMonitor = Box<dyn FnOnce(rusqlite::InterruptHandle) + Send>;
+
+/// Runs `f` with a cancellation monitor that interrupts the statement when Ctrl+C is pressed.
+///
+/// The query is marked as executing while `f` runs, so the Ctrl+C handler signals
+/// the monitor instead of clearing the input line. The monitor thread stops once `f` returns.
+fn with_cancellation<T>(
+    executing_query: &Arc<std::sync::Mutex<bool>>,
+    global_cancel_rx: &Arc<std::sync::Mutex<Option<mpsc::Receiver<()>>>>,
+    f: impl FnOnce(CancellationMonitor) -> Result<T>,
+) -> Result<T> {
+    *executing_query.lock().unwrap() = true;
+    let global_rx_clone = global_cancel_rx.clone();
+    let exec_flag = executing_query.clone();
+    let done = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let monitor_done = done.clone();
+    let cancellation_monitor: CancellationMonitor = Box::new(move |interrupt_handle: rusqlite::InterruptHandle| {
+        std::thread::spawn(move || {
+            // Wait for cancellation signal from global Ctrl+C handler until the command finishes
+            let rx_opt = global_rx_clone.lock().unwrap();
+            let Some(rx) = rx_opt.as_ref() else {
+                return;
+            };
+            while !monitor_done.load(std::sync::atomic::Ordering::Relaxed) {
+                match rx.recv_timeout(std::time::Duration::from_millis(50)) {
+                    Ok(()) => {
+                        // Interruption requested
+                        interrupt_handle.interrupt();
+                        *exec_flag.lock().unwrap() = false;
+                        break;
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+    });
+    let result = f(cancellation_monitor);
+    done.store(true, std::sync::atomic::Ordering::Relaxed);
+    *executing_query.lock().unwrap() = false;
+    result
+}
+
 /// Enhanced REPL shell with readline support, persistent history, and auto-completion
 pub fn run_repl() {
     use crate::command_palette::CommandPalette;
@@ -322,6 +604,16 @@ pub fn run_repl() {
         Storage::new(PathBuf::from(":memory:")).expect("Failed to create in-memory storage")
     });
 
+    // A database connected before the REPL started, as with `tuiql app.db`, is snapshotted here
+    let connected = db::DB_STATE
+        .get()
+        .is_some_and(|state| state.lock().is_ok_and(|guard| guard.connection.is_some()));
+    if connected {
+        if let Err(e) = record_schema_snapshot(&storage) {
+            eprintln!("Failed to save schema snapshot: {}", e);
+        }
+    }
+
     // Initialize completer
     let completer = ReedlineCompleter::new();
 
@@ -398,6 +690,15 @@ pub fn run_repl() {
                             entry.duration_ms.unwrap_or(0),
                             entry.query
                         );
+                        let stats = entry
+                            .stats
+                            .as_deref()
+                            .and_then(|json| serde_json::from_str::<db::StatementStats>(json).ok());
+                        if let Some(stats) = stats {
+                            if stats.auto_indexes > 0 {
+                                println!("    ⚠️ built an automatic index ({} rows)", stats.auto_indexes);
+                            }
+                        }
                     }
                 }
                 Err(e) => eprintln!("Error retrieving history: {}", e),
@@ -413,25 +714,47 @@ pub fn run_repl() {
                 println!("  :commit - Commit current transaction");
                 println!("  :rollback - Rollback current transaction");
                 println!("  :pragma <n> [val] - ⚙️ View or set SQLite pragmas (coming soon!)");
-                println!("  :plan - Visualize the query plan");
+                println!("  :plan [<query>] - Visualize the query plan");
+                println!("  :plan --compare <query> - Diff the current plan against the one stored in history");
+                println!("  :plan regressions - List queries whose plan got worse after a schema change");
+                println!("  :plan --format json|dot <query> - 📤 Export the plan tree with tables, indexes and estimated costs");
                 println!("  :plan_enhanced - 🔬 Enhanced query plan with cost overlay and performance data");
+                println!("  :explain [--vm] <query> - Show the query plan, or with --vm the bytecode program with loops and cursors");
                 println!("  :fmt - 🛠️ Format the current query buffer (coming soon!)");
                 println!("  :export <format> [<file>] - 📤 Export current result set (supported: csv, json, markdown)");
                 println!("  :find <text> - 🔍 Search for text in the database schema or queries (coming soon!)");
-                println!("  :erd [table] - 📊 Show ER-diagram for the schema");
+                println!("  :erd [table] [--depth N] [--direction in|out|both] - 📊 Show ER-diagram for the schema or around one table");
+                println!("  :erd [table] --format dot|mermaid|plantuml|d2 [file] - 🖼️ Export the ER-diagram");
+                println!("  :erd --cycles - 🔁 List foreign-key cycles (strongly connected components)");
+                println!("  :erd <table> --cascade - 🧨 Show what ON DELETE actions do when deleting from a table");
                 println!("  :fts5 [cmd] - 🔍 FTS5 full-text search helper");
                 println!("  :json1 [cmd] - 🎯 JSON1 extension helper");
+                println!("  :migrate [status|up [n]|down [n]|redo] [--dir <path>] - 📦 Run versioned schema migrations");
                 println!("  :hist - Show command/query history");
                 println!("  :plugin <name> [args] - 🧩 Execute a configured plugin");
                 println!("  :snip <action> - 💾 Manage query snippets (coming soon!)");
-                println!("  :diff <dbA> <dbB> - 🔄 Perform a schema diff between databases (coming soon!)");
-                println!("  :tables - Show database schema information");
+                println!("  :diff <dbA> <dbB> [--format text|json|markdown] - 🔄 Perform a schema diff between databases");
+                println!("  :diff --since <snapshot> [--format ...] - 🕰️ Diff the current schema against a stored snapshot");
+                println!("  :diff ... --rename <table.old=new> | --no-rename [<table.old=new>] - Confirm or reject column renames");
+                println!("  :schema history - 🕰️ List schema snapshots recorded for the current database");
+                println!("  :schema order [load|delete] - 🔢 Print a topological load/delete order of tables");
+                println!("  :fkcheck [table] [--repair delete|null] - 🔗 Find orphaned rows and generate repair SQL for review");
+                println!("  :health [--quick] [--json [file]] - 🩺 Check integrity, free pages, WAL and statistics with suggested fixes");
+                println!("  :space [table] - 💾 Show page, payload and unused bytes per table and index, largest first");
+                println!("  :profile <table> [--limit N|--exact] [--top K] [--buckets N] [--refresh] - 📊 Per-column statistics, cached between runs");
+                println!("  :lint schema [--format text|json] - 🧹 Find schema design problems with suggested DDL");
+                println!("  :stats [on|off] - 📈 Show statement status counters (scans, sorts, automatic indexes) after each query");
+                println!("  :analyze [table] [--limit N] - 📊 Run ANALYZE; also show [table], set <table> [index] <stat>, delete <table> [index], export [file], import <file>");
+                println!("  :advise <query> - 🧭 Propose indexes for a query, compare plans and optionally create them");
+                println!("  :bench N [--warmup W] [--cold] [--param V]... <query> - ⏱️  Run a query N times and compare with the previous benchmark");
+                println!("  :tables [tables|views|virtual|triggers] [<name>] [--exact] [--expand] - 🌳 Browse the schema tree");
                 println!("\nOr enter SQL queries directly without any prefix.");
             }
-            Command::Tables => match schema_navigator::SchemaNavigator::new() {
-                Ok(navigator) => println!("{}", navigator.render()),
-                Err(e) => eprintln!("Error getting schema: {}", e),
-            },
+            Command::Tables(args) => {
+                if let Err(e) = schema_navigator::execute_tables_command(args.as_deref().unwrap_or("")) {
+                    println!("❌ Error getting schema: {}", e);
+                }
+            }
             Command::Begin => match db::execute_query("BEGIN TRANSACTION") {
                 Ok(_) => println!("Transaction started"),
                 Err(e) => eprintln!("Failed to start transaction: {}", e),
@@ -444,7 +767,11 @@ pub fn run_repl() {
                 Ok(_) => println!("Transaction rolled back"),
                 Err(e) => eprintln!("Failed to rollback transaction: {}", e),
             },
-            Command::Plan => {
+            Command::Plan(Some(args)) => match plan::execute_plan_command(&args, &storage) {
+                Ok(output) => println!("{}", output),
+                Err(e) => println!("❌ Error: {}", e),
+            },
+            Command::Plan(None) => {
                 println!("Enter a SQL query to visualize its execution plan:");
                 println!("(Note: Make sure a database is connected with :open first)");
                 loop {
@@ -486,15 +813,29 @@ pub fn run_repl() {
                         break;
                     }
                     println!("\nAnalyzing query execution plan with cost overlay...");
-                    println!("This may take a moment as it executes the query to gather timing data.");
-                    match plan::explain_query_enhanced(trimmed) {
+                    println!("This may take a moment as it executes the query to gather timing data. (Press Ctrl+C to cancel)");
+                    let progress_reporter = |progress: &db::QueryProgress| {
+                        print!("\r\x1b[2K{}", progress.status_line());
+                        let _ = io::stdout().flush();
+                    };
+                    let result = with_cancellation(&executing_query, &global_cancel_rx, |cancellation_monitor| {
+                        plan::explain_query_enhanced(trimmed, cancellation_monitor, progress_reporter)
+                    });
+                    print!("\r\x1b[2K");
+                    let _ = io::stdout().flush();
+                    match result {
                         Ok(plan_output) => println!("{}", plan_output),
                         Err(e) => eprintln!("Error generating enhanced plan: {}", e),
                     }
                 }
             }
             Command::Open(path) => match db::connect(&path) {
-                Ok(_) => println!("Successfully opened database: {}", path),
+                Ok(_) => {
+                    println!("Successfully opened database: {}", path);
+                    if let Err(e) = record_schema_snapshot(&storage) {
+                        eprintln!("Failed to save schema snapshot: {}", e);
+                    }
+                }
                 Err(e) => eprintln!("Error opening database: {}", e),
             },
             Command::Sql(sql) => {
@@ -523,35 +864,20 @@ pub fn run_repl() {
 
                 let start_time = Instant::now();
 
-                // Mark that we're executing a query
-                *executing_query.lock().unwrap() = true;
-
-                println!("Executing query... (Interrupt will be handled by Ctrl+C in input mode)");
-
-                // Extract the global cancellation receiver for this command
-                let global_rx_clone = global_cancel_rx.clone();
-                let exec_flag = executing_query.clone();
-
-                // Use the cancellable query function with a callback that monitors cancellation
-                let cancellation_monitor = move |interrupt_handle: rusqlite::InterruptHandle| {
-                    std::thread::spawn(move || {
-                        // Wait for cancellation signal from global Ctrl+C handler
-                        let rx_opt = global_rx_clone.lock().unwrap();
-                        if let Some(rx) = rx_opt.as_ref() {
-                            if let Ok(()) = rx.recv() {
-                                // Interruption requested
-                                interrupt_handle.interrupt();
-                                *exec_flag.lock().unwrap() = false;
-                            }
-                        }
-                    });
+                println!("Executing query... (Press Ctrl+C to cancel)");
+
+                // Report the progress of long queries on a live status line
+                let progress_reporter = |progress: &db::QueryProgress| {
+                    print!("\r\x1b[2K{}", progress.status_line());
+                    let _ = io::stdout().flush();
                 };
 
                 // Execute query with cancellation support
-                let result = db::execute_cancellable_query(&sql, cancellation_monitor);
-
-                // Mark query execution as complete
-                *executing_query.lock().unwrap() = false;
+                let result = with_cancellation(&executing_query, &global_cancel_rx, |cancellation_monitor| {
+                    db::execute_cancellable_query(&sql, cancellation_monitor, progress_reporter)
+                });
+                print!("\r\x1b[2K");
+                let _ = io::stdout().flush();
 
                 match result {
                     Ok(result) => {
@@ -573,8 +899,17 @@ pub fn run_repl() {
                         }
                         println!("💡 Tip: Use ':export <format>' to export results to CSV, JSON, or Markdown");
 
-                        // Record successful query in history
+                        let stats = if state.stats_enabled { result.stats } else { None };
+                        if let Some(stats) = stats {
+                            println!("{}", stats.summary());
+                            for warning in stats.warnings() {
+                                println!("⚠️  {}", warning);
+                            }
+                        }
+
+                        // Record successful query in history, with the plan it ran with
                         let duration = start_time.elapsed().as_millis() as i64;
+                        let query_plan = plan::capture_current_plan(&sql);
                         let entry = HistoryEntry::new(
                             sql.to_string(),
                             db::DB_STATE
@@ -588,7 +923,9 @@ pub fn run_repl() {
                             true,
                             Some(duration),
                             Some(displayed_rows as i64),
-                        );
+                        )
+                        .with_stats(stats.and_then(|stats| serde_json::to_string(&stats).ok()))
+                        .with_plan(query_plan.and_then(|plan| serde_json::to_string(&plan).ok()));
                         if let Err(e) = storage.add_history(entry) {
                             eprintln!("Failed to save to history: {}", e);
                         }
@@ -657,12 +994,10 @@ pub fn run_repl() {
                 println!("🔍 Search functionality is coming soon!");
                 println!("This will search your database schema and queries for: {:?}", search_term);
             }
-            Command::Erd(_table) => {
-                match schema_map::generate_schema_map() {
-                    Ok(schema_map) => {
-                        let diagram = schema_map::render_schema_map(&schema_map);
-                        println!("{}", diagram);
-                    }
+            Command::Erd(args) => {
+                match schema_map::execute_erd_command(args.as_deref().unwrap_or("")) {
+                    Ok(()) => {}
+                    Err(e @ TuiqlError::Command(_)) => println!("❌ {}", e),
                     Err(e) => {
                         println!("❌ Error generating schema map: {}", e);
                         println!("Make sure you have connected to a database with :open first.");
@@ -701,20 +1036,138 @@ pub fn run_repl() {
                     }
                 }
             }
+            Command::Migrate(args) => {
+                if let Err(e) = migrate::execute_migrate_command(args.as_deref().unwrap_or("status")) {
+                    println!("❌ Migration error: {}", e);
+                    println!("Usage: :migrate [status|up [n]|down [n]|redo] [--dir <path>] [--tracking table|user_version]");
+                }
+            }
             Command::Snip(action) => {
                 println!("💾 Query snippets functionality is coming soon!");
                 println!("This will manage saved query snippets. Action: {:?}", action);
             }
-            Command::Diff { db_a, db_b } => {
-                match diff::compare_databases(&db_a, &db_b) {
-                    Ok(comparison) => {
-                        let output = diff::format_comparison(&comparison, &db_a, &db_b);
+            Command::Diff { db_a, db_b, format, renames } => {
+                match diff::compare_databases_with(&db_a, &db_b, &renames)
+                    .and_then(|comparison| diff::render_comparison(&comparison, &db_a, &db_b, &format))
+                {
+                    Ok(output) => {
                         println!("{}", output);
                     }
                     Err(e) => {
                         println!("❌ Error performing schema diff: {}", e);
                         println!("Make sure both database files exist and are valid SQLite databases.");
-                        println!("Usage: :diff <database1> <database2>");
+                        println!(
+                            "Usage: :diff <database1> <database2> [--format text|json|markdown] [--rename <table.old=new>] [--no-rename [<table.old=new>]]"
+                        );
+                    }
+                }
+            }
+            Command::DiffSince { snapshot, format, renames } => {
+                match diff_since_snapshot(&storage, snapshot, &format, &renames) {
+                    Ok(output) => println!("{}", output),
+                    Err(e) => {
+                        println!("❌ Error performing schema diff: {}", e);
+                        println!("Use ':schema history' to list available snapshots.");
+                    }
+                }
+            }
+            Command::FkCheck(args) => {
+                if let Err(e) = fkcheck::execute_fkcheck_command(args.as_deref().unwrap_or("")) {
+                    println!("❌ Error checking foreign keys: {}", e);
+                }
+            }
+            Command::Health(args) => {
+                if let Err(e) = health::execute_health_command(args.as_deref().unwrap_or("")) {
+                    println!("❌ Error checking database health: {}", e);
+                }
+            }
+            Command::Space(args) => {
+                if let Err(e) = space::execute_space_command(args.as_deref().unwrap_or("")) {
+                    println!("❌ Error analyzing space: {}", e);
+                }
+            }
+            Command::Profile(args) => {
+                let result = with_cancellation(&executing_query, &global_cancel_rx, |cancellation_monitor| {
+                    profile::execute_profile_command(args.as_deref().unwrap_or(""), &storage, cancellation_monitor)
+                });
+                if let Err(e) = result {
+                    println!("❌ Error profiling table: {}", e);
+                }
+            }
+            Command::Bench(args) => {
+                let result = with_cancellation(&executing_query, &global_cancel_rx, |cancellation_monitor| {
+                    bench::execute_bench_command(args.as_deref().unwrap_or(""), &storage, cancellation_monitor)
+                });
+                if let Err(e) = result {
+                    println!("❌ Error running benchmark: {}", e);
+                }
+            }
+            Command::Stats(mode) => match mode.as_deref() {
+                Some("on") => {
+                    state.stats_enabled = true;
+                    println!("📈 Statement stats enabled");
+                }
+                Some("off") => {
+                    state.stats_enabled = false;
+                    println!("📈 Statement stats disabled");
+                }
+                None => println!(
+                    "📈 Statement stats are {}",
+                    if state.stats_enabled { "on" } else { "off" }
+                ),
+                Some(other) => println!("❌ Unknown stats mode '{}'. Use ':stats on' or ':stats off'", other),
+            },
+            Command::Analyze(args) => {
+                if let Err(e) = analyze::execute_analyze_command(args.as_deref().unwrap_or("")) {
+                    println!("❌ Error managing statistics: {}", e);
+                }
+            }
+            Command::Explain(args) => match plan::execute_explain_command(args.as_deref().unwrap_or("")) {
+                Ok(output) => println!("{}", output),
+                Err(e) => println!("❌ Error explaining query: {}", e),
+            },
+            Command::Advise(query) => match index_advisor::execute_advise_command(query.as_deref().unwrap_or("")) {
+                Ok(advice) if !advice.indexes.is_empty() => {
+                    print!("Create which indexes? (numbers, 'all', or Enter to skip): ");
+                    io::stdout().flush().expect("Failed to flush stdout");
+                    let mut selection = String::new();
+                    if io::stdin().read_line(&mut selection).is_err() {
+                        continue;
+                    }
+                    match index_advisor::apply_advice(&advice, &selection) {
+                        Ok(created) if created.is_empty() => println!("No indexes created."),
+                        Ok(created) => println!("✅ Created {}", created.join(", ")),
+                        Err(e) => println!("❌ Error creating indexes: {}", e),
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => println!("❌ Error advising on query: {}", e),
+            },
+            Command::Lint(args) => {
+                if let Err(e) = schema_lint::execute_lint_command(args.as_deref().unwrap_or("")) {
+                    println!("❌ {}", e);
+                }
+            }
+            Command::Schema(action) => {
+                let action = action.as_deref().unwrap_or("history");
+                let mut args = action.split_whitespace();
+                match args.next().unwrap_or("history") {
+                    "history" => match schema_history(&storage) {
+                        Ok(output) => println!("{}", output),
+                        Err(e) => println!("❌ Error listing schema snapshots: {}", e),
+                    },
+                    "order" => {
+                        let which = args.next().unwrap_or("both");
+                        match schema_map::generate_schema_map()
+                            .and_then(|map| schema_map::render_table_order(&map, which))
+                        {
+                            Ok(output) => print!("{}", output),
+                            Err(e) => println!("❌ Error computing table order: {}", e),
+                        }
+                    }
+                    other => {
+                        println!("❌ Unknown schema command: '{}'", other);
+                        println!("Usage: :schema history | :schema order [load|delete]");
                     }
                 }
             }
@@ -812,6 +1265,52 @@ mod tests {
         assert_eq!(rollback_cmd, Command::Rollback);
     }
 
+    #[test]
+    fn test_snapshot_database_key_is_canonical() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("app.db");
+        std::fs::write(&file, b"").unwrap();
+        let dotted = dir.path().join(".").join("app.db");
+
+        let key = snapshot_database_key(file.to_str().unwrap());
+        assert_eq!(snapshot_database_key(dotted.to_str().unwrap()), key);
+        assert_eq!(key, std::fs::canonicalize(&file).unwrap().display().to_string());
+        assert_eq!(snapshot_database_key(":memory:"), ":memory:");
+    }
+
+    #[test]
+    fn test_schema_snapshot_and_diff_since() {
+        db::tests::setup_test_db_global();
+        let storage = Storage::new(PathBuf::from(":memory:")).unwrap();
+
+        // Opening the same schema twice records a single snapshot
+        let id = record_schema_snapshot(&storage).unwrap();
+        assert!(id.is_some());
+        let history = schema_history(&storage).unwrap();
+        assert!(history.contains(&format!("#{}", id.unwrap())));
+
+        // Compare against an older snapshot that lacked the `value` column
+        let old = SchemaSnapshot::new(
+            ":memory:".to_string(),
+            1,
+            "CREATE TABLE test (id INTEGER PRIMARY KEY, name TEXT);\n".to_string(),
+        );
+        let old_id = storage.add_schema_snapshot(&old).unwrap().unwrap();
+        let output = diff_since_snapshot(&storage, old_id, "json", &diff::RenameOptions::default()).unwrap();
+        let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert!(report["source"].as_str().unwrap().contains(&format!("snapshot #{}", old_id)));
+        assert!(report["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|r| r["diff_type"] == "column_added" && r["field_name"] == "value"));
+
+        assert!(matches!(
+            diff_since_snapshot(&storage, 9999, "text", &diff::RenameOptions::default()),
+            Err(TuiqlError::Command(_))
+        ));
+    }
+
     #[test]
     fn test_transaction_execution() {
         // Setup test database
@@ -878,6 +1377,193 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_migrate_command() {
+        assert_eq!(parse_command(":migrate"), Command::Migrate(None));
+        assert_eq!(
+            parse_command(":migrate up 2 --dir db/migrations"),
+            Command::Migrate(Some("up 2 --dir db/migrations".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_diff_since_command() {
+        assert_eq!(
+            parse_command(":diff --since 12"),
+            Command::DiffSince {
+                snapshot: 12,
+                format: "text".to_string(),
+                renames: diff::RenameOptions::default()
+            }
+        );
+        assert_eq!(
+            parse_command(":diff --since 3 --format json"),
+            Command::DiffSince {
+                snapshot: 3,
+                format: "json".to_string(),
+                renames: diff::RenameOptions::default()
+            }
+        );
+        assert!(matches!(parse_command(":diff --since latest"), Command::Unknown(_)));
+    }
+
+    #[test]
+    fn test_parse_diff_rename_options() {
+        let Command::Diff { renames, .. } =
+            parse_command(":diff a.db b.db --rename users.name=full_name --no-rename bio=about --rename=age=years")
+        else {
+            panic!("expected a diff command");
+        };
+        assert_eq!(
+            renames.accepted,
+            vec![
+                diff::RenamePair {
+                    table: Some("users".to_string()),
+                    old: "name".to_string(),
+                    new: "full_name".to_string()
+                },
+                diff::RenamePair { table: None, old: "age".to_string(), new: "years".to_string() },
+            ]
+        );
+        assert_eq!(
+            renames.rejected,
+            vec![diff::RenamePair { table: None, old: "bio".to_string(), new: "about".to_string() }]
+        );
+        assert!(!renames.disabled);
+
+        let Command::DiffSince { renames, .. } = parse_command(":diff --since 3 --no-rename --format json") else {
+            panic!("expected a diff --since command");
+        };
+        assert!(renames.disabled && renames.rejected.is_empty());
+
+        assert!(matches!(parse_command(":diff a.db b.db --rename users.name"), Command::Unknown(_)));
+    }
+
+    #[test]
+    fn test_parse_erd_command() {
+        assert_eq!(parse_command(":erd"), Command::Erd(None));
+        assert_eq!(
+            parse_command(":erd orders --depth 2 --direction in"),
+            Command::Erd(Some("orders --depth 2 --direction in".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_fkcheck_command() {
+        assert_eq!(parse_command(":fkcheck"), Command::FkCheck(None));
+        assert_eq!(
+            parse_command(":fkcheck orders --repair null"),
+            Command::FkCheck(Some("orders --repair null".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_health_command() {
+        assert_eq!(parse_command(":health"), Command::Health(None));
+        assert_eq!(
+            parse_command(":health --quick --json report.json"),
+            Command::Health(Some("--quick --json report.json".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_space_command() {
+        assert_eq!(parse_command(":space"), Command::Space(None));
+        assert_eq!(parse_command(":space orders"), Command::Space(Some("orders".to_string())));
+    }
+
+    #[test]
+    fn test_parse_profile_command() {
+        assert_eq!(parse_command(":profile"), Command::Profile(None));
+        assert_eq!(
+            parse_command(":profile users --exact --top 3"),
+            Command::Profile(Some("users --exact --top 3".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_lint_command() {
+        assert_eq!(parse_command(":lint"), Command::Lint(None));
+        assert_eq!(
+            parse_command(":lint schema --format json"),
+            Command::Lint(Some("schema --format json".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_stats_command() {
+        assert_eq!(parse_command(":stats"), Command::Stats(None));
+        assert_eq!(parse_command(":stats on"), Command::Stats(Some("on".to_string())));
+    }
+
+    #[test]
+    fn test_parse_plan_command() {
+        assert_eq!(parse_command(":plan"), Command::Plan(None));
+        assert_eq!(parse_command(":plan regressions"), Command::Plan(Some("regressions".to_string())));
+        assert_eq!(
+            parse_command(":plan --compare SELECT * FROM users"),
+            Command::Plan(Some("--compare SELECT * FROM users".to_string()))
+        );
+        assert_eq!(
+            parse_command(":plan --format dot SELECT * FROM users"),
+            Command::Plan(Some("--format dot SELECT * FROM users".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_analyze_command() {
+        assert_eq!(parse_command(":analyze"), Command::Analyze(None));
+        assert_eq!(
+            parse_command(":analyze set users idx_users_email 10000 2"),
+            Command::Analyze(Some("set users idx_users_email 10000 2".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_explain_command() {
+        assert_eq!(parse_command(":explain"), Command::Explain(None));
+        assert_eq!(
+            parse_command(":explain --vm SELECT * FROM users"),
+            Command::Explain(Some("--vm SELECT * FROM users".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_advise_command() {
+        assert_eq!(parse_command(":advise"), Command::Advise(None));
+        assert_eq!(
+            parse_command(":advise SELECT * FROM users WHERE name = 'a  b'"),
+            Command::Advise(Some("SELECT * FROM users WHERE name = 'a  b'".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_bench_command() {
+        assert_eq!(parse_command(":bench"), Command::Bench(None));
+        assert_eq!(
+            parse_command(":bench 20 --cold SELECT * FROM users WHERE name = 'a  b'"),
+            Command::Bench(Some("20 --cold SELECT * FROM users WHERE name = 'a  b'".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_tables_command() {
+        assert_eq!(parse_command(":tables"), Command::Tables(None));
+        assert_eq!(
+            parse_command(":tables views --exact"),
+            Command::Tables(Some("views --exact".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_schema_command() {
+        assert_eq!(parse_command(":schema"), Command::Schema(None));
+        assert_eq!(
+            parse_command(":schema history"),
+            Command::Schema(Some("history".to_string()))
+        );
+    }
+
     #[test]
     fn test_parse_diff_command() {
         let cmd = parse_command(":diff db1.db db2.db");
@@ -885,9 +1571,28 @@ mod tests {
             cmd,
             Command::Diff {
                 db_a: "db1.db".to_string(),
-                db_b: "db2.db".to_string()
+                db_b: "db2.db".to_string(),
+                format: "text".to_string(),
+                renames: diff::RenameOptions::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_diff_command_with_format() {
+        let cmd = parse_command(":diff db1.db db2.db --format json");
+        assert_eq!(
+            cmd,
+            Command::Diff {
+                db_a: "db1.db".to_string(),
+                db_b: "db2.db".to_string(),
+                format: "json".to_string(),
+                renames: diff::RenameOptions::default()
             }
         );
+
+        let cmd = parse_command(":diff db1.db db2.db --format=markdown");
+        assert!(matches!(cmd, Command::Diff { format, .. } if format == "markdown"));
     }
 
     #[test]
@@ -952,6 +1657,7 @@ mod tests {
                 vec!["2".to_string(), "Bob".to_string()],
             ],
             row_count: 2,
+            stats: None,
         };
 
         // Store the result
@@ -977,6 +1683,7 @@ mod tests {
                 vec!["2".to_string(), "Bob".to_string()],
             ],
             row_count: 2,
+            stats: None,
         };
         state.store_result(&query_result);
 
@@ -1048,6 +1755,7 @@ mod tests {
                 vec!["2".to_string(), "Bob".to_string()],
             ],
             row_count: 2,
+            stats: None,
         };
         state.store_result(&query_result);
 
@@ -1,10 +1,9 @@
-use crate::core::{Result, TuiqlError};
-
-/// Results Grid Module for TUIQL
-///
-/// This module provides an implementation for rendering tabular results in the terminal.
-/// It includes features like virtualized rendering for large datasets, sticky headers, and export functionality.
+//! Results Grid Module for TUIQL
+//!
+//! This module provides an implementation for rendering tabular results in the terminal.
+//! It includes features like virtualized rendering for large datasets, sticky headers, and export functionality.
 
+use crate::core::{Result, TuiqlError};
 use std::collections::BTreeMap;
 
 /// Represents a single cell in the grid.
@@ -0,0 +1,491 @@
+//! Schema Lint Module
+//!
+//! Scans a `Schema` for design smells — missing primary keys, foreign keys
+//! without a supporting index, duplicate or redundant indexes, loosely typed
+//! non-STRICT tables, unneeded AUTOINCREMENT and nullable primary keys. Each
+//! finding carries a rule id, a severity and, where possible, suggested DDL.
+
+use crate::catalog;
+use crate::core::db::schema::{quote_identifier, Affinity, Index, Schema, Table};
+use crate::core::{Result, TuiqlError};
+use serde::Serialize;
+
+/// A schema lint rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintRule {
+    NoPrimaryKey,
+    UnindexedForeignKey,
+    DuplicateIndex,
+    RedundantIndex,
+    LooseTyping,
+    UnneededAutoincrement,
+    NullablePrimaryKey,
+}
+
+impl LintRule {
+    /// Stable rule id, usable for suppressions and CI reports
+    pub fn id(&self) -> &'static str {
+        match self {
+            LintRule::NoPrimaryKey => "S001",
+            LintRule::UnindexedForeignKey => "S002",
+            LintRule::DuplicateIndex => "S003",
+            LintRule::RedundantIndex => "S004",
+            LintRule::LooseTyping => "S005",
+            LintRule::UnneededAutoincrement => "S006",
+            LintRule::NullablePrimaryKey => "S007",
+        }
+    }
+
+    /// Short kebab-case rule name
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintRule::NoPrimaryKey => "no-primary-key",
+            LintRule::UnindexedForeignKey => "unindexed-foreign-key",
+            LintRule::DuplicateIndex => "duplicate-index",
+            LintRule::RedundantIndex => "redundant-index",
+            LintRule::LooseTyping => "loose-typing",
+            LintRule::UnneededAutoincrement => "unneeded-autoincrement",
+            LintRule::NullablePrimaryKey => "nullable-primary-key",
+        }
+    }
+
+    /// Severity level for findings of this rule: warning, note or info
+    pub fn severity(&self) -> &'static str {
+        match self {
+            LintRule::NoPrimaryKey
+            | LintRule::UnindexedForeignKey
+            | LintRule::DuplicateIndex
+            | LintRule::NullablePrimaryKey => "warning",
+            LintRule::RedundantIndex | LintRule::LooseTyping => "note",
+            // AUTOINCREMENT is often deliberate, so this is only informational
+            LintRule::UnneededAutoincrement => "info",
+        }
+    }
+}
+
+/// A single schema design finding
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    pub rule: LintRule,
+    pub table: String,
+    pub message: String,
+    /// DDL that fixes the finding; a `--` comment when the table must be rebuilt
+    pub suggested_ddl: Option<String>,
+}
+
+/// JSON representation of a finding
+#[derive(Serialize)]
+struct JsonFinding<'a> {
+    rule_id: &'static str,
+    rule: &'static str,
+    severity: &'static str,
+    table: &'a str,
+    message: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suggested_ddl: Option<&'a str>,
+}
+
+/// Runs all schema lint rules, returning findings ordered by table and rule id.
+pub fn lint_schema(schema: &Schema) -> Vec<LintFinding> {
+    let mut tables: Vec<&Table> = schema
+        .tables
+        .values()
+        .filter(|t| {
+            !t.sql
+                .as_deref()
+                .is_some_and(|sql| sql.trim_start().to_uppercase().starts_with("CREATE VIRTUAL TABLE"))
+        })
+        .collect();
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut findings = Vec::new();
+    for table in tables {
+        check_primary_key(table, &mut findings);
+        check_foreign_key_indexes(table, &mut findings);
+        check_indexes(table, &mut findings);
+        check_loose_typing(table, &mut findings);
+        if table.has_autoincrement() {
+            findings.push(LintFinding {
+                rule: LintRule::UnneededAutoincrement,
+                table: table.name.clone(),
+                message: format!(
+                    "{} uses AUTOINCREMENT; INTEGER PRIMARY KEY already assigns new ids, AUTOINCREMENT only prevents reuse of deleted ones at the cost of a sqlite_sequence update per insert",
+                    table.name
+                ),
+                suggested_ddl: Some(format!(
+                    "-- Unless ids must never be reused, recreate {} without AUTOINCREMENT",
+                    quote_identifier(&table.name)
+                )),
+            });
+        }
+    }
+    findings.sort_by(|a, b| a.table.cmp(&b.table).then_with(|| a.rule.id().cmp(b.rule.id())));
+    findings
+}
+
+/// Whether the table's primary key is a single `INTEGER PRIMARY KEY` rowid alias
+fn is_rowid_alias(table: &Table) -> bool {
+    let pk: Vec<_> = table.columns.iter().filter(|c| c.pk).collect();
+    pk.len() == 1 && pk[0].type_name.eq_ignore_ascii_case("INTEGER") && !table.is_without_rowid()
+}
+
+fn check_primary_key(table: &Table, findings: &mut Vec<LintFinding>) {
+    let pk: Vec<_> = table.columns.iter().filter(|c| c.pk).collect();
+    if pk.is_empty() {
+        findings.push(LintFinding {
+            rule: LintRule::NoPrimaryKey,
+            table: table.name.clone(),
+            message: format!("{} has no primary key; rows can only be addressed by their implicit rowid", table.name),
+            suggested_ddl: Some(format!(
+                "-- Recreate {} with a primary key, e.g. \"id\" INTEGER PRIMARY KEY",
+                quote_identifier(&table.name)
+            )),
+        });
+        return;
+    }
+    // Rowid aliases and WITHOUT ROWID keys are implicitly NOT NULL
+    if is_rowid_alias(table) || table.is_without_rowid() {
+        return;
+    }
+    let nullable: Vec<&str> = pk.iter().filter(|c| !c.notnull).map(|c| c.name.as_str()).collect();
+    if !nullable.is_empty() {
+        findings.push(LintFinding {
+            rule: LintRule::NullablePrimaryKey,
+            table: table.name.clone(),
+            message: format!(
+                "Primary key column(s) {} of {} allow NULL; SQLite does not enforce NOT NULL on primary keys of rowid tables",
+                nullable.join(", "),
+                table.name
+            ),
+            suggested_ddl: Some(format!(
+                "-- Recreate {} declaring {} NOT NULL",
+                quote_identifier(&table.name),
+                nullable.iter().map(|c| quote_identifier(c)).collect::<Vec<_>>().join(", ")
+            )),
+        });
+    }
+}
+
+/// Whether `columns` form a leading prefix of the index, in any order
+fn index_covers(index: &Index, columns: &[String]) -> bool {
+    index.columns.len() >= columns.len()
+        && columns
+            .iter()
+            .all(|c| index.columns[..columns.len()].iter().any(|i| i.eq_ignore_ascii_case(c)))
+}
+
+/// Whether the index's leading columns are exactly `columns`, in order
+fn starts_with_columns(index: &Index, columns: &[String]) -> bool {
+    index.columns.len() >= columns.len() && index.columns.iter().zip(columns).all(|(a, b)| a.eq_ignore_ascii_case(b))
+}
+
+fn check_foreign_key_indexes(table: &Table, findings: &mut Vec<LintFinding>) {
+    for fk in &table.foreign_keys {
+        let covered_by_rowid = is_rowid_alias(table)
+            && fk.from_columns.len() == 1
+            && table.columns.iter().any(|c| c.pk && c.name.eq_ignore_ascii_case(&fk.from_columns[0]));
+        if covered_by_rowid
+            || table
+                .indexes
+                .iter()
+                .any(|index| !index.partial && index_covers(index, &fk.from_columns))
+        {
+            continue;
+        }
+        let index_name = format!("idx_{}_{}", table.name, fk.from_columns.join("_"));
+        findings.push(LintFinding {
+            rule: LintRule::UnindexedForeignKey,
+            table: table.name.clone(),
+            message: format!(
+                "Foreign key {} has no supporting index; deletes and updates in {} scan {}",
+                fk.describe(),
+                fk.referenced_table,
+                table.name
+            ),
+            suggested_ddl: Some(format!(
+                "CREATE INDEX {} ON {} ({});",
+                quote_identifier(&index_name),
+                quote_identifier(&table.name),
+                fk.from_columns.iter().map(|c| quote_identifier(c)).collect::<Vec<_>>().join(", ")
+            )),
+        });
+    }
+}
+
+fn drop_index_ddl(index: &Index) -> Option<String> {
+    // Indexes backing PRIMARY KEY and UNIQUE constraints cannot be dropped
+    (!index.name.starts_with("sqlite_autoindex_")).then(|| format!("DROP INDEX {};", quote_identifier(&index.name)))
+}
+
+fn check_indexes(table: &Table, findings: &mut Vec<LintFinding>) {
+    // A partial index only covers the rows matching its WHERE clause, so it
+    // neither duplicates nor makes redundant an index on the same columns
+    let mut indexes: Vec<&Index> = table.indexes.iter().filter(|index| !index.partial).collect();
+    indexes.sort_by(|a, b| a.name.cmp(&b.name));
+    for (i, a) in indexes.iter().enumerate() {
+        for b in &indexes[i + 1..] {
+            if a.columns.len() == b.columns.len() && starts_with_columns(b, &a.columns) {
+                // Keep the index that backs a constraint or enforces uniqueness
+                let a_kept = a.name.starts_with("sqlite_autoindex_") || (a.unique && !b.unique);
+                let (kept, redundant) = if a_kept { (a, b) } else { (b, a) };
+                findings.push(LintFinding {
+                    rule: LintRule::DuplicateIndex,
+                    table: table.name.clone(),
+                    message: format!(
+                        "Indexes {} and {} both cover ({})",
+                        kept.name,
+                        redundant.name,
+                        a.columns.join(", ")
+                    ),
+                    suggested_ddl: drop_index_ddl(redundant),
+                });
+            }
+        }
+    }
+
+    for a in &indexes {
+        if a.unique {
+            continue;
+        }
+        if let Some(b) = indexes
+            .iter()
+            .find(|b| b.columns.len() > a.columns.len() && starts_with_columns(b, &a.columns))
+        {
+            findings.push(LintFinding {
+                rule: LintRule::RedundantIndex,
+                table: table.name.clone(),
+                message: format!(
+                    "Index {} ({}) is a prefix of {} ({}) and can usually be dropped",
+                    a.name,
+                    a.columns.join(", "),
+                    b.name,
+                    b.columns.join(", ")
+                ),
+                suggested_ddl: drop_index_ddl(a),
+            });
+        }
+    }
+}
+
+/// Type a column would get in a STRICT table
+fn strict_type(type_name: &str) -> &'static str {
+    match Affinity::of(type_name) {
+        Affinity::Integer => "INTEGER",
+        Affinity::Real => "REAL",
+        Affinity::Text => "TEXT",
+        Affinity::Blob if type_name.to_uppercase().contains("BLOB") => "BLOB",
+        Affinity::Blob | Affinity::Numeric => "ANY",
+    }
+}
+
+fn check_loose_typing(table: &Table, findings: &mut Vec<LintFinding>) {
+    if table.is_strict() {
+        return;
+    }
+    let loose: Vec<_> = table
+        .columns
+        .iter()
+        .filter(|c| c.type_name.is_empty() || Affinity::of(&c.type_name) == Affinity::Numeric)
+        .collect();
+    if loose.is_empty() {
+        return;
+    }
+    findings.push(LintFinding {
+        rule: LintRule::LooseTyping,
+        table: table.name.clone(),
+        message: format!(
+            "{} is not STRICT and {} accept values of any storage class: {}",
+            table.name,
+            if loose.len() == 1 { "this column may" } else { "these columns may" },
+            loose
+                .iter()
+                .map(|c| {
+                    let declared = if c.type_name.is_empty() { "no type" } else { c.type_name.as_str() };
+                    format!("{} ({}, {} affinity)", c.name, declared, Affinity::of(&c.type_name).as_str())
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        suggested_ddl: Some(format!(
+            "-- Recreate {} as STRICT with column types: {}",
+            quote_identifier(&table.name),
+            table
+                .columns
+                .iter()
+                .map(|c| format!("{} {}", quote_identifier(&c.name), strict_type(&c.type_name)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    });
+}
+
+/// Formats findings for the terminal, grouped by table.
+pub fn format_findings(findings: &[LintFinding]) -> String {
+    if findings.is_empty() {
+        return "✅ No schema design problems found.\n".to_string();
+    }
+    let mut output = format!("🧹 Schema lint: {} finding(s)\n", findings.len());
+    let mut current_table = None;
+    for finding in findings {
+        if current_table != Some(&finding.table) {
+            output.push_str(&format!("\n📋 {}\n", finding.table));
+            current_table = Some(&finding.table);
+        }
+        let icon = if finding.rule.severity() == "warning" { "⚠️ " } else { "ℹ️ " };
+        output.push_str(&format!(
+            "  {} {} {}: {}\n",
+            icon,
+            finding.rule.id(),
+            finding.rule.name(),
+            finding.message
+        ));
+        if let Some(ddl) = &finding.suggested_ddl {
+            output.push_str(&format!("      {}\n", ddl));
+        }
+    }
+    output
+}
+
+/// Serializes findings as a JSON array.
+pub fn findings_to_json(findings: &[LintFinding]) -> Result<String> {
+    let report: Vec<JsonFinding> = findings
+        .iter()
+        .map(|f| JsonFinding {
+            rule_id: f.rule.id(),
+            rule: f.rule.name(),
+            severity: f.rule.severity(),
+            table: &f.table,
+            message: &f.message,
+            suggested_ddl: f.suggested_ddl.as_deref(),
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&report)?)
+}
+
+/// Executes `:lint schema [--format text|json]`.
+pub fn execute_lint_command(command: &str) -> Result<()> {
+    let mut args = command.split_whitespace();
+    let target = args.next();
+    let mut format = "text";
+    while let Some(arg) = args.next() {
+        match arg {
+            "--format" => {
+                format = args
+                    .next()
+                    .ok_or_else(|| TuiqlError::Command("--format requires text or json".to_string()))?
+            }
+            other => return Err(TuiqlError::Command(format!("Unknown option '{}'", other))),
+        }
+    }
+
+    match target {
+        Some("schema") => {
+            let findings = lint_schema(&catalog::current_catalog()?.schema);
+            match format {
+                "text" => print!("{}", format_findings(&findings)),
+                "json" => println!("{}", findings_to_json(&findings)?),
+                other => {
+                    return Err(TuiqlError::Command(format!(
+                        "Unsupported lint format: '{}'. Supported formats: text, json",
+                        other
+                    )))
+                }
+            }
+            Ok(())
+        }
+        _ => Err(TuiqlError::Command("Usage: :lint schema [--format text|json]".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    fn lint(ddl: &str) -> Vec<LintFinding> {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(ddl).unwrap();
+        lint_schema(&Schema::from_connection(&conn).unwrap())
+    }
+
+    fn rules(findings: &[LintFinding]) -> Vec<(&str, &'static str)> {
+        findings.iter().map(|f| (f.table.as_str(), f.rule.id())).collect()
+    }
+
+    #[test]
+    fn test_clean_schema() {
+        let findings = lint(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL UNIQUE);
+             CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users(id), body TEXT);
+             CREATE INDEX idx_posts_user ON posts(user_id, id);
+             CREATE TABLE tags (name TEXT PRIMARY KEY, weight REAL) WITHOUT ROWID;
+             CREATE TABLE points (x INTEGER, y ANY, PRIMARY KEY (x)) STRICT;",
+        );
+        assert!(findings.is_empty(), "{:?}", findings);
+        assert!(format_findings(&findings).contains("No schema design problems"));
+    }
+
+    #[test]
+    fn test_keys_and_typing() {
+        let findings = lint(
+            "CREATE TABLE logs (message TEXT, created DATETIME, extra);
+             CREATE TABLE accounts (code TEXT PRIMARY KEY, seq INTEGER);
+             CREATE TABLE counters (id INTEGER PRIMARY KEY AUTOINCREMENT, n INT);",
+        );
+        assert_eq!(
+            rules(&findings),
+            vec![("accounts", "S007"), ("counters", "S006"), ("logs", "S001"), ("logs", "S005")]
+        );
+        let loose = &findings[3];
+        assert!(loose.message.contains("created (DATETIME, numeric affinity)"));
+        assert!(loose.message.contains("extra (no type, blob affinity)"));
+        assert_eq!(
+            loose.suggested_ddl.as_deref(),
+            Some("-- Recreate \"logs\" as STRICT with column types: \"message\" TEXT, \"created\" ANY, \"extra\" ANY")
+        );
+    }
+
+    #[test]
+    fn test_index_rules() {
+        let findings = lint(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT UNIQUE, name TEXT);
+             CREATE INDEX idx_users_email ON users(email);
+             CREATE INDEX idx_users_name ON users(name);
+             CREATE INDEX idx_users_name_email ON users(name, email);
+             CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL REFERENCES users(id));",
+        );
+        assert_eq!(rules(&findings), vec![("orders", "S002"), ("users", "S003"), ("users", "S004")]);
+        assert_eq!(
+            findings[0].suggested_ddl.as_deref(),
+            Some("CREATE INDEX \"idx_orders_user_id\" ON \"orders\" (\"user_id\");")
+        );
+        assert_eq!(findings[1].suggested_ddl.as_deref(), Some("DROP INDEX \"idx_users_email\";"));
+        assert_eq!(findings[2].suggested_ddl.as_deref(), Some("DROP INDEX \"idx_users_name\";"));
+
+        let json: serde_json::Value = serde_json::from_str(&findings_to_json(&findings).unwrap()).unwrap();
+        assert_eq!(json[0]["rule_id"], "S002");
+        assert_eq!(json[0]["rule"], "unindexed-foreign-key");
+        assert_eq!(json[0]["severity"], "warning");
+    }
+
+    #[test]
+    fn test_partial_indexes_are_not_duplicates() {
+        let findings = lint(
+            "CREATE TABLE jobs (id INTEGER PRIMARY KEY, state TEXT, queued_at INTEGER);
+             CREATE INDEX idx_jobs_state ON jobs(state, queued_at);
+             CREATE INDEX idx_jobs_pending ON jobs(state, queued_at) WHERE state = 'pending';
+             CREATE INDEX idx_jobs_failed ON jobs(state) WHERE state = 'failed';
+             CREATE TABLE runs (id INTEGER PRIMARY KEY, job_id INTEGER NOT NULL REFERENCES jobs(id));
+             CREATE INDEX idx_runs_job ON runs(job_id) WHERE job_id > 0;",
+        );
+        // Only the foreign key on runs is reported: its partial index does not cover every row
+        assert_eq!(rules(&findings), vec![("runs", "S002")]);
+    }
+
+    #[test]
+    fn test_autoincrement_is_info() {
+        let findings = lint("CREATE TABLE counters (id INTEGER PRIMARY KEY AUTOINCREMENT, n INTEGER NOT NULL);");
+        assert_eq!(rules(&findings), vec![("counters", "S006")]);
+        assert_eq!(findings[0].rule.severity(), "info");
+    }
+}
@@ -1,6 +1,7 @@
+use crate::catalog;
+use crate::core::db::schema::FkAction;
 use crate::core::{Result, TuiqlError};
-use crate::db;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 
 /*
  * Schema Map Module for ER-like Diagram Visualization
@@ -21,17 +22,137 @@ pub struct SchemaMap {
 #[derive(Debug, Clone)]
 pub struct TableNode {
     pub name: String,
-    pub columns: Vec<String>,
+    pub columns: Vec<MapColumn>,
     pub primary_keys: Vec<String>,
     pub outgoing_references: Vec<String>, // Tables this table references
 }
 
-#[derive(Debug, Clone)]
+/// A table column with its declared type, which is empty for untyped columns
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapColumn {
+    pub name: String,
+    pub type_name: String,
+}
+
+impl MapColumn {
+    pub fn new(name: &str, type_name: &str) -> Self {
+        MapColumn {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for MapColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.type_name.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{} {}", self.name, self.type_name)
+        }
+    }
+}
+
+/// One foreign key constraint; composite keys list several columns
+#[derive(Debug, Clone, Default)]
 pub struct Relationship {
     pub from_table: String,
-    pub from_column: String,
+    pub from_columns: Vec<String>,
     pub to_table: String,
-    pub to_column: String,
+    pub to_columns: Vec<String>,
+    pub on_delete: FkAction,
+    pub on_update: FkAction,
+}
+
+impl Relationship {
+    /// Child columns of the constraint, joined by ", " for display
+    pub fn from_column_list(&self) -> String {
+        self.from_columns.join(", ")
+    }
+
+    /// Parent columns of the constraint, joined by ", " for display
+    pub fn to_column_list(&self) -> String {
+        self.to_columns.join(", ")
+    }
+
+    /// Non-default ON DELETE / ON UPDATE actions, e.g. "ON DELETE CASCADE"
+    pub fn actions(&self) -> String {
+        let mut actions = Vec::new();
+        if self.on_delete != FkAction::NoAction {
+            actions.push(format!("ON DELETE {}", self.on_delete.as_sql()));
+        }
+        if self.on_update != FkAction::NoAction {
+            actions.push(format!("ON UPDATE {}", self.on_update.as_sql()));
+        }
+        actions.join(" ")
+    }
+
+    /// `from → to` column mapping followed by any actions
+    fn label(&self) -> String {
+        let actions = self.actions();
+        if actions.is_empty() {
+            format!("{} → {}", self.from_column_list(), self.to_column_list())
+        } else {
+            format!("{} → {}, {}", self.from_column_list(), self.to_column_list(), actions)
+        }
+    }
+}
+
+/// Which foreign-key edges to follow when focusing on a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeDirection {
+    /// Tables referencing the focus (children)
+    In,
+    /// Tables the focus references (parents)
+    Out,
+    Both,
+}
+
+impl EdgeDirection {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "in" => Ok(EdgeDirection::In),
+            "out" => Ok(EdgeDirection::Out),
+            "both" => Ok(EdgeDirection::Both),
+            other => Err(TuiqlError::Command(format!(
+                "Invalid direction '{}'. Expected one of: in, out, both",
+                other
+            ))),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            EdgeDirection::In => "in",
+            EdgeDirection::Out => "out",
+            EdgeDirection::Both => "both",
+        }
+    }
+}
+
+/// A schema map narrowed to the neighbourhood of one table
+#[derive(Debug, Clone)]
+pub struct FocusedMap {
+    pub focus: String,
+    pub depth: usize,
+    pub direction: EdgeDirection,
+    /// Tables within `depth` hops and the relationships between them
+    pub map: SchemaMap,
+    /// Hop distance of every included table from the focus
+    pub hops: BTreeMap<String, usize>,
+    /// Tables connected to the focus but further than `depth` hops (or only
+    /// reachable against `direction`)
+    pub beyond_depth: Vec<String>,
+    /// Connected groups of tables with no path to the focus, collapsed in the output
+    pub collapsed_clusters: Vec<Vec<String>>,
+}
+
+/// One step of a topological table order: a single table, or a group of tables
+/// whose foreign keys form a cycle and must be loaded together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderStep {
+    pub tables: Vec<String>,
+    pub cyclic: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -85,7 +206,7 @@ fn generate_table_groups(map: &SchemaMap) -> Vec<TableGroup> {
     }
 
     // Sort groups by connectivity (highly connected first)
-    components.sort_by(|a, b| b.connections.cmp(&a.connections));
+    components.sort_by_key(|g| std::cmp::Reverse(g.connections));
 
     components
 }
@@ -119,22 +240,293 @@ fn find_connected_component(
 }
 
 
-/// Generates a schema map from the current database schema.
-/// Uses the enhanced schema introspection system to capture real database relationships.
-pub fn generate_schema_map() -> Result<SchemaMap> {
-    use crate::core::db::schema::Schema;
+/// Finds strongly connected components of the FK graph that form cycles.
+///
+/// Returns components with more than one table, plus tables that reference
+/// themselves. Tables within a component and the components themselves are
+/// sorted by name.
+pub fn find_cycles(map: &SchemaMap) -> Vec<Vec<String>> {
+    let mut cycles: Vec<Vec<String>> = strongly_connected_components(map)
+        .into_iter()
+        .filter(|component| {
+            component.len() > 1
+                || map
+                    .relationships
+                    .iter()
+                    .any(|r| r.from_table == component[0] && r.to_table == component[0])
+        })
+        .collect();
+    cycles.sort();
+    cycles
+}
 
-    // Access the database connection and use the core schema system
-    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::Schema("No database connection found. Please connect to a database first.".to_string()))?;
-    let state_guard = state_cell
-        .lock()
-        .map_err(|_| TuiqlError::Schema("Failed to acquire database connection lock".to_string()))?;
-    let conn = state_guard
-        .connection
-        .as_ref()
-        .ok_or(TuiqlError::Schema("No active database connection".to_string()))?;
+/// Orders tables so that every table comes after the tables it references
+/// (load order). Reverse it for a delete order. Tables in an FK cycle are
+/// emitted together as one cyclic step; self-references do not affect the order.
+pub fn topological_order(map: &SchemaMap) -> Vec<OrderStep> {
+    let components = strongly_connected_components(map);
+    let mut component_of: HashMap<&str, usize> = HashMap::new();
+    for (i, component) in components.iter().enumerate() {
+        for table in component {
+            component_of.insert(table, i);
+        }
+    }
+
+    // Condensed graph: edges point from parent (referenced) to child component
+    let mut children: Vec<HashSet<usize>> = vec![HashSet::new(); components.len()];
+    let mut parents_left = vec![0usize; components.len()];
+    for rel in &map.relationships {
+        let (Some(&child), Some(&parent)) = (
+            component_of.get(rel.from_table.as_str()),
+            component_of.get(rel.to_table.as_str()),
+        ) else {
+            continue;
+        };
+        if child != parent && children[parent].insert(child) {
+            parents_left[child] += 1;
+        }
+    }
+
+    // Kahn's algorithm, picking ready components by name for deterministic output
+    let mut ready: std::collections::BTreeSet<(&str, usize)> = components
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| parents_left[*i] == 0)
+        .map(|(i, c)| (c[0].as_str(), i))
+        .collect();
+    let mut steps = Vec::with_capacity(components.len());
+    while let Some(&(name, i)) = ready.iter().next() {
+        ready.remove(&(name, i));
+        let tables = components[i].clone();
+        let cyclic = tables.len() > 1;
+        steps.push(OrderStep { tables, cyclic });
+        let mut next: Vec<usize> = children[i].iter().copied().collect();
+        next.sort_unstable();
+        for child in next {
+            parents_left[child] -= 1;
+            if parents_left[child] == 0 {
+                ready.insert((components[child][0].as_str(), child));
+            }
+        }
+    }
+    steps
+}
+
+/// Tarjan's algorithm over the tables of the map; each component is sorted by name
+fn strongly_connected_components(map: &SchemaMap) -> Vec<Vec<String>> {
+    struct State<'a> {
+        adjacency: HashMap<&'a str, Vec<&'a str>>,
+        index: HashMap<&'a str, usize>,
+        lowlink: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: HashSet<&'a str>,
+        next_index: usize,
+        components: Vec<Vec<String>>,
+    }
+
+    fn visit<'a>(table: &'a str, state: &mut State<'a>) {
+        state.index.insert(table, state.next_index);
+        state.lowlink.insert(table, state.next_index);
+        state.next_index += 1;
+        state.stack.push(table);
+        state.on_stack.insert(table);
+
+        let neighbours = state.adjacency.get(table).cloned().unwrap_or_default();
+        for next in neighbours {
+            if !state.index.contains_key(next) {
+                visit(next, state);
+                let low = state.lowlink[table].min(state.lowlink[next]);
+                state.lowlink.insert(table, low);
+            } else if state.on_stack.contains(next) {
+                let low = state.lowlink[table].min(state.index[next]);
+                state.lowlink.insert(table, low);
+            }
+        }
+
+        if state.lowlink[table] == state.index[table] {
+            let mut component = Vec::new();
+            while let Some(member) = state.stack.pop() {
+                state.on_stack.remove(member);
+                component.push(member.to_string());
+                if member == table {
+                    break;
+                }
+            }
+            component.sort();
+            state.components.push(component);
+        }
+    }
+
+    let mut names: Vec<&str> = map.tables.iter().map(|t| t.name.as_str()).collect();
+    names.sort_unstable();
+    let known: HashSet<&str> = names.iter().copied().collect();
 
-    let schema = Schema::from_connection(conn)?;
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    for rel in &map.relationships {
+        if known.contains(rel.from_table.as_str()) && known.contains(rel.to_table.as_str()) {
+            adjacency.entry(&rel.from_table).or_default().push(&rel.to_table);
+        }
+    }
+    for targets in adjacency.values_mut() {
+        targets.sort_unstable();
+        targets.dedup();
+    }
+
+    let mut state = State {
+        adjacency,
+        index: HashMap::new(),
+        lowlink: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+    for name in names {
+        if !state.index.contains_key(name) {
+            visit(name, &mut state);
+        }
+    }
+    state.components
+}
+
+/// Renders what deleting a row from `table` does to referencing tables: which
+/// tables are deleted through ON DELETE CASCADE (recursively), which have their
+/// keys set to NULL or DEFAULT, and which block the delete.
+pub fn render_cascade_paths(map: &SchemaMap, table: &str) -> Result<String> {
+    let root = map
+        .tables
+        .iter()
+        .find(|t| t.name == table)
+        .or_else(|| map.tables.iter().find(|t| t.name.eq_ignore_ascii_case(table)))
+        .map(|t| t.name.clone())
+        .ok_or_else(|| TuiqlError::Schema(format!("Table '{}' not found", table)))?;
+
+    fn walk(map: &SchemaMap, table: &str, indent: usize, path: &mut Vec<String>, output: &mut String) {
+        let mut incoming: Vec<&Relationship> = map.relationships.iter().filter(|r| r.to_table == table).collect();
+        incoming.sort_by(|a, b| (&a.from_table, &a.from_columns).cmp(&(&b.from_table, &b.from_columns)));
+
+        for rel in incoming {
+            let pad = "   ".repeat(indent);
+            let via = format!("{} via {} → {}", rel.from_table, rel.from_column_list(), rel.to_column_list());
+            match rel.on_delete {
+                FkAction::Cascade if path.contains(&rel.from_table) => {
+                    output.push_str(&format!("  {}└─ 🔁 {} (cycle, already deleted)\n", pad, via));
+                }
+                FkAction::Cascade => {
+                    output.push_str(&format!("  {}└─ 🗑️  {}: ON DELETE CASCADE\n", pad, via));
+                    path.push(rel.from_table.clone());
+                    walk(map, &rel.from_table, indent + 1, path, output);
+                    path.pop();
+                }
+                FkAction::SetNull | FkAction::SetDefault => {
+                    output.push_str(&format!("  {}└─ ✏️  {}: ON DELETE {}\n", pad, via, rel.on_delete.as_sql()));
+                }
+                FkAction::NoAction | FkAction::Restrict => {
+                    output.push_str(&format!(
+                        "  {}└─ ⛔ {}: {} blocks the delete while rows reference it\n",
+                        pad,
+                        via,
+                        rel.on_delete.as_sql()
+                    ));
+                }
+            }
+        }
+    }
+
+    let mut output = format!("🧨 Deleting a row from {} affects:\n  {}\n", root, root);
+    let before = output.len();
+    walk(map, &root, 0, &mut vec![root.clone()], &mut output);
+    if output.len() == before {
+        output.push_str("  (no tables reference it)\n");
+    }
+    Ok(output)
+}
+
+/// Renders the FK cycles of a schema map with the edges that form them.
+pub fn render_cycles(map: &SchemaMap) -> String {
+    let cycles = find_cycles(map);
+    if cycles.is_empty() {
+        return "✅ No foreign-key cycles found.\n".to_string();
+    }
+
+    let mut output = format!("🔁 {} foreign-key cycle(s):\n", cycles.len());
+    for (i, cycle) in cycles.iter().enumerate() {
+        let members: HashSet<&str> = cycle.iter().map(|t| t.as_str()).collect();
+        let mut edges: Vec<String> = map
+            .relationships
+            .iter()
+            .filter(|r| members.contains(r.from_table.as_str()) && members.contains(r.to_table.as_str()))
+            .map(|r| format!("{}.{} → {}.{}", r.from_table, r.from_column_list(), r.to_table, r.to_column_list()))
+            .collect();
+        edges.sort();
+        edges.dedup();
+        let label = if cycle.len() == 1 {
+            format!("{} (self-reference)", cycle[0])
+        } else {
+            cycle.join(" ↔ ")
+        };
+        output.push_str(&format!("  {}. {}\n", i + 1, label));
+        for edge in edges {
+            output.push_str(&format!("       {}\n", edge));
+        }
+    }
+    output
+}
+
+/// Renders the load and/or delete order of the tables in a schema map.
+///
+/// `which` is "load", "delete" or "both".
+pub fn render_table_order(map: &SchemaMap, which: &str) -> Result<String> {
+    let (load, delete) = match which {
+        "load" => (true, false),
+        "delete" => (false, true),
+        "both" => (true, true),
+        other => {
+            return Err(TuiqlError::Command(format!(
+                "Unknown order '{}'. Expected load or delete",
+                other
+            )))
+        }
+    };
+
+    let steps = topological_order(map);
+    let render_steps = |steps: &mut dyn Iterator<Item = &OrderStep>, output: &mut String| {
+        for (i, step) in steps.enumerate() {
+            if step.cyclic {
+                output.push_str(&format!("  {:>3}. ⚠️  {} (cycle: load together with deferred FKs)\n", i + 1, step.tables.join(", ")));
+            } else {
+                output.push_str(&format!("  {:>3}. {}\n", i + 1, step.tables[0]));
+            }
+        }
+    };
+
+    let mut output = String::new();
+    if steps.is_empty() {
+        output.push_str("No tables found in the database.\n");
+        return Ok(output);
+    }
+    if load {
+        output.push_str("📥 Load order (referenced tables first):\n");
+        render_steps(&mut steps.iter(), &mut output);
+    }
+    if delete {
+        if load {
+            output.push('\n');
+        }
+        output.push_str("🗑️  Delete order (referencing tables first):\n");
+        render_steps(&mut steps.iter().rev(), &mut output);
+    }
+    if steps.iter().any(|s| s.cyclic) {
+        output.push_str("\nTip: wrap cyclic groups in a transaction with PRAGMA defer_foreign_keys = ON.\n");
+    }
+    Ok(output)
+}
+
+/// Generates a schema map from the current database schema.
+/// Uses the shared catalog cache, so repeated calls only re-introspect after schema changes.
+pub fn generate_schema_map() -> Result<SchemaMap> {
+    let catalog = catalog::current_catalog()?;
+    let schema = &catalog.schema;
 
     let mut tables = Vec::new();
     let mut relationships = Vec::new();
@@ -145,7 +537,7 @@ pub fn generate_schema_map() -> Result<SchemaMap> {
         let mut primary_keys = Vec::new();
 
         for col in &table_info.columns {
-            columns.push(format!("{} {}", col.name, col.type_name));
+            columns.push(MapColumn::new(&col.name, &col.type_name));
             if col.pk {
                 primary_keys.push(col.name.clone());
             }
@@ -158,9 +550,11 @@ pub fn generate_schema_map() -> Result<SchemaMap> {
         for fk in &table_info.foreign_keys {
             relationships.push(Relationship {
                 from_table: table_name.clone(),
-                from_column: fk.from_column.clone(),
+                from_columns: fk.from_columns.clone(),
                 to_table: fk.referenced_table.clone(),
-                to_column: fk.to_column.clone(),
+                to_columns: fk.to_columns.clone(),
+                on_delete: fk.on_delete,
+                on_update: fk.on_update,
             });
 
             if !outgoing_references.contains(&fk.referenced_table) {
@@ -179,6 +573,553 @@ pub fn generate_schema_map() -> Result<SchemaMap> {
     Ok(SchemaMap { tables, relationships })
 }
 
+/// Narrows a schema map to the tables within `depth` foreign-key hops of `focus`,
+/// following only edges in `direction`. Tables outside the focus's connected
+/// group are collapsed into clusters.
+pub fn focus_schema_map(map: &SchemaMap, focus: &str, depth: usize, direction: EdgeDirection) -> Result<FocusedMap> {
+    let focus = map
+        .tables
+        .iter()
+        .find(|t| t.name == focus)
+        .or_else(|| map.tables.iter().find(|t| t.name.eq_ignore_ascii_case(focus)))
+        .map(|t| t.name.clone())
+        .ok_or_else(|| TuiqlError::Schema(format!("Table '{}' not found", focus)))?;
+
+    // Breadth-first search along the requested edge directions
+    let mut hops: BTreeMap<String, usize> = BTreeMap::new();
+    hops.insert(focus.clone(), 0);
+    let mut queue = VecDeque::from([focus.clone()]);
+    while let Some(table) = queue.pop_front() {
+        let distance = hops[&table];
+        if distance == depth {
+            continue;
+        }
+        for rel in &map.relationships {
+            let next = if rel.from_table == table && direction != EdgeDirection::In {
+                &rel.to_table
+            } else if rel.to_table == table && direction != EdgeDirection::Out {
+                &rel.from_table
+            } else {
+                continue;
+            };
+            if !hops.contains_key(next) && map.tables.iter().any(|t| &t.name == next) {
+                hops.insert(next.clone(), distance + 1);
+                queue.push_back(next.clone());
+            }
+        }
+    }
+
+    let tables: Vec<TableNode> = map.tables.iter().filter(|t| hops.contains_key(&t.name)).cloned().collect();
+    let relationships: Vec<Relationship> = map
+        .relationships
+        .iter()
+        .filter(|r| hops.contains_key(&r.from_table) && hops.contains_key(&r.to_table))
+        .cloned()
+        .collect();
+
+    let mut beyond_depth = Vec::new();
+    let mut collapsed_clusters = Vec::new();
+    for group in generate_table_groups(map) {
+        if group.tables.contains(&focus) {
+            beyond_depth.extend(group.tables.into_iter().filter(|t| !hops.contains_key(t)));
+        } else {
+            let mut cluster = group.tables;
+            cluster.sort();
+            collapsed_clusters.push(cluster);
+        }
+    }
+    beyond_depth.sort();
+    collapsed_clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+    Ok(FocusedMap {
+        focus,
+        depth,
+        direction,
+        map: SchemaMap { tables, relationships },
+        hops,
+        beyond_depth,
+        collapsed_clusters,
+    })
+}
+
+/// Renders a focused schema map, distinguishing outgoing (→) and incoming (←) edges.
+pub fn render_focused_map(focused: &FocusedMap) -> String {
+    let mut diagram = format!(
+        "=== ER Diagram: {} (depth {}, direction {}) ===\n\n",
+        focused.focus,
+        focused.depth,
+        focused.direction.as_str()
+    );
+
+    // Focus first, then by hop distance and name
+    let mut order: Vec<(&usize, &String)> = focused.hops.iter().map(|(t, d)| (d, t)).collect();
+    order.sort();
+
+    for (distance, table_name) in order {
+        let Some(table) = focused.map.tables.iter().find(|t| &t.name == table_name) else {
+            continue;
+        };
+        if *distance == 0 {
+            diagram.push_str(&format!("🎯 Table: {}\n", table.name));
+        } else {
+            let plural = if *distance == 1 { "" } else { "s" };
+            diagram.push_str(&format!("📋 Table: {} ({} hop{})\n", table.name, distance, plural));
+        }
+        if !table.primary_keys.is_empty() {
+            diagram.push_str(&format!("  🔑 Primary Keys: {}\n", table.primary_keys.join(", ")));
+        }
+        diagram.push_str("  📝 Columns:\n");
+        for col in &table.columns {
+            diagram.push_str(&format!("    - {}\n", col));
+        }
+
+        let mut outgoing: Vec<&Relationship> =
+            focused.map.relationships.iter().filter(|r| r.from_table == table.name).collect();
+        outgoing.sort_by(|a, b| (&a.to_table, &a.from_columns).cmp(&(&b.to_table, &b.from_columns)));
+        let mut incoming: Vec<&Relationship> =
+            focused.map.relationships.iter().filter(|r| r.to_table == table.name).collect();
+        incoming.sort_by(|a, b| (&a.from_table, &a.from_columns).cmp(&(&b.from_table, &b.from_columns)));
+
+        if !outgoing.is_empty() {
+            diagram.push_str("  🔗 References (outgoing):\n");
+            for rel in outgoing {
+                diagram.push_str(&format!("    → {} ({})\n", rel.to_table, rel.label()));
+            }
+        }
+        if !incoming.is_empty() {
+            diagram.push_str("  ↙ Referenced by (incoming):\n");
+            for rel in incoming {
+                diagram.push_str(&format!("    ← {} ({})\n", rel.from_table, rel.label()));
+            }
+        }
+        diagram.push('\n');
+    }
+
+    if !focused.beyond_depth.is_empty() {
+        diagram.push_str(&format!(
+            "… {} more related table(s) not shown: {}\n",
+            focused.beyond_depth.len(),
+            summarize_names(&focused.beyond_depth)
+        ));
+    }
+    if !focused.collapsed_clusters.is_empty() {
+        let hidden: usize = focused.collapsed_clusters.iter().map(|c| c.len()).sum();
+        diagram.push_str(&format!(
+            "📦 {} unrelated cluster(s) collapsed ({} table(s)):\n",
+            focused.collapsed_clusters.len(),
+            hidden
+        ));
+        for cluster in &focused.collapsed_clusters {
+            diagram.push_str(&format!("  [{}] {}\n", cluster.len(), summarize_names(cluster)));
+        }
+    }
+
+    diagram.push_str("\n=== End ER Diagram ===\n");
+    diagram
+}
+
+/// Lists up to five names, summarising the rest
+fn summarize_names(names: &[String]) -> String {
+    const SHOWN: usize = 5;
+    if names.len() <= SHOWN {
+        names.join(", ")
+    } else {
+        format!("{}, … (+{} more)", names[..SHOWN].join(", "), names.len() - SHOWN)
+    }
+}
+
+/// Serializes a schema map as a diagram in one of the supported text formats.
+///
+/// Output is deterministic: tables, columns and edges are emitted in a stable
+/// order so generated diagrams can be committed and diffed.
+pub fn export_schema_map(map: &SchemaMap, format: &str) -> Result<String> {
+    match format.to_lowercase().as_str() {
+        "dot" | "graphviz" => Ok(export_dot(map)),
+        "mermaid" => Ok(export_mermaid(map)),
+        "plantuml" | "puml" => Ok(export_plantuml(map)),
+        "d2" => Ok(export_d2(map)),
+        _ => Err(TuiqlError::Ui(format!(
+            "Unsupported ERD format: '{}'. Supported formats: dot, mermaid, plantuml, d2",
+            format
+        ))),
+    }
+}
+
+/// A table column with its key markers
+struct ExportColumn<'a> {
+    name: &'a str,
+    type_name: &'a str,
+    pk: bool,
+    fk: bool,
+}
+
+/// Tables sorted by name and relationships sorted by endpoints, for stable output
+fn sorted_for_export(map: &SchemaMap) -> (Vec<&TableNode>, Vec<&Relationship>) {
+    let mut tables: Vec<&TableNode> = map.tables.iter().collect();
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut relationships: Vec<&Relationship> = map.relationships.iter().collect();
+    relationships.sort_by(|a, b| {
+        (&a.from_table, &a.from_columns, &a.to_table, &a.to_columns)
+            .cmp(&(&b.from_table, &b.from_columns, &b.to_table, &b.to_columns))
+    });
+    (tables, relationships)
+}
+
+fn export_columns<'a>(map: &'a SchemaMap, table: &'a TableNode) -> Vec<ExportColumn<'a>> {
+    table
+        .columns
+        .iter()
+        .map(|col| ExportColumn {
+            name: &col.name,
+            type_name: &col.type_name,
+            pk: table.primary_keys.contains(&col.name),
+            fk: map
+                .relationships
+                .iter()
+                .any(|r| r.from_table == table.name && r.from_columns.contains(&col.name)),
+        })
+        .collect()
+}
+
+/// Returns `name` unchanged if it is a plain identifier, otherwise `quote(name)`
+fn quote_if_needed(name: &str, quote: impl Fn(&str) -> String) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
+
+/// Replaces every character that is not valid in a bare identifier with `_`
+fn sanitize_identifier(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if cleaned.is_empty() {
+        "_".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// First column of a possibly composite column list, used for diagram ports
+fn first_column(columns: &[String]) -> &str {
+    columns.first().map(String::as_str).unwrap_or_default()
+}
+
+/// Edge label with the column mapping and any non-default actions, in ASCII
+fn export_label(rel: &Relationship) -> String {
+    let actions = rel.actions();
+    if actions.is_empty() {
+        format!("{} -> {}", rel.from_column_list(), rel.to_column_list())
+    } else {
+        format!("{} -> {} {}", rel.from_column_list(), rel.to_column_list(), actions)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn dot_quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn export_dot(map: &SchemaMap) -> String {
+    let (tables, relationships) = sorted_for_export(map);
+    let mut out = String::from("digraph schema {\n    rankdir=LR;\n    node [shape=plaintext];\n\n");
+
+    for table in &tables {
+        out.push_str(&format!(
+            "    {} [label=<<TABLE BORDER=\"0\" CELLBORDER=\"1\" CELLSPACING=\"0\">\n        <TR><TD BGCOLOR=\"lightgrey\"><B>{}</B></TD></TR>\n",
+            dot_quote(&table.name),
+            escape_html(&table.name)
+        ));
+        for col in export_columns(map, table) {
+            let mut label = escape_html(col.name);
+            if col.pk {
+                label = format!("<U>{}</U>", label);
+            }
+            if !col.type_name.is_empty() {
+                label.push_str(&format!(" : {}", escape_html(col.type_name)));
+            }
+            let mut keys = Vec::new();
+            if col.pk {
+                keys.push("PK");
+            }
+            if col.fk {
+                keys.push("FK");
+            }
+            if !keys.is_empty() {
+                label.push_str(&format!(" [{}]", keys.join(", ")));
+            }
+            out.push_str(&format!(
+                "        <TR><TD ALIGN=\"LEFT\" PORT={}>{}</TD></TR>\n",
+                dot_quote(col.name),
+                label
+            ));
+        }
+        out.push_str("    </TABLE>>];\n");
+    }
+
+    if !relationships.is_empty() {
+        out.push('\n');
+    }
+    for rel in relationships {
+        out.push_str(&format!(
+            "    {}:{} -> {}:{} [label={}];\n",
+            dot_quote(&rel.from_table),
+            dot_quote(first_column(&rel.from_columns)),
+            dot_quote(&rel.to_table),
+            dot_quote(first_column(&rel.to_columns)),
+            dot_quote(&export_label(rel))
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn export_mermaid(map: &SchemaMap) -> String {
+    let (tables, relationships) = sorted_for_export(map);
+    let entity = |name: &str| quote_if_needed(name, |n| format!("\"{}\"", n.replace('"', "'")));
+    let mut out = String::from("erDiagram\n");
+
+    for table in &tables {
+        out.push_str(&format!("    {} {{\n", entity(&table.name)));
+        for col in export_columns(map, table) {
+            let type_name = if col.type_name.is_empty() {
+                "ANY".to_string()
+            } else {
+                sanitize_identifier(col.type_name)
+            };
+            let keys: Vec<&str> = [(col.pk, "PK"), (col.fk, "FK")]
+                .iter()
+                .filter(|(set, _)| *set)
+                .map(|(_, key)| *key)
+                .collect();
+            let keys = if keys.is_empty() {
+                String::new()
+            } else {
+                format!(" {}", keys.join(", "))
+            };
+            out.push_str(&format!("        {} {}{}\n", type_name, sanitize_identifier(col.name), keys));
+        }
+        out.push_str("    }\n");
+    }
+
+    for rel in relationships {
+        out.push_str(&format!(
+            "    {} }}o--|| {} : \"{}\"\n",
+            entity(&rel.from_table),
+            entity(&rel.to_table),
+            export_label(rel).replace('"', "'")
+        ));
+    }
+    out
+}
+
+/// PlantUML aliases for every table in the map; names that sanitize to the same
+/// identifier, such as `a-b` and `a_b`, get a numeric suffix in name order
+fn plantuml_aliases(map: &SchemaMap) -> HashMap<&str, String> {
+    let names: BTreeSet<&str> = map
+        .tables
+        .iter()
+        .map(|t| t.name.as_str())
+        .chain(map.relationships.iter().flat_map(|r| [r.from_table.as_str(), r.to_table.as_str()]))
+        .collect();
+    let mut used = HashSet::new();
+    let mut aliases = HashMap::new();
+    for name in names {
+        let base = sanitize_identifier(name);
+        let mut alias = base.clone();
+        let mut suffix = 2;
+        while !used.insert(alias.clone()) {
+            alias = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        aliases.insert(name, alias);
+    }
+    aliases
+}
+
+fn export_plantuml(map: &SchemaMap) -> String {
+    let (tables, relationships) = sorted_for_export(map);
+    let aliases = plantuml_aliases(map);
+    let mut out = String::from("@startuml\nhide circle\nskinparam linetype ortho\n\n");
+
+    for table in &tables {
+        out.push_str(&format!(
+            "entity \"{}\" as {} {{\n",
+            table.name.replace('"', "'"),
+            aliases[table.name.as_str()]
+        ));
+        let columns = export_columns(map, table);
+        let (keys, others): (Vec<_>, Vec<_>) = columns.iter().partition(|c| c.pk);
+        let column_line = |col: &ExportColumn| {
+            let mut line = format!("  {}{}", if col.pk { "* " } else { "" }, col.name);
+            if !col.type_name.is_empty() {
+                line.push_str(&format!(" : {}", col.type_name));
+            }
+            if col.pk {
+                line.push_str(" <<PK>>");
+            }
+            if col.fk {
+                line.push_str(" <<FK>>");
+            }
+            line.push('\n');
+            line
+        };
+        for col in &keys {
+            out.push_str(&column_line(col));
+        }
+        if !keys.is_empty() {
+            out.push_str("  --\n");
+        }
+        for col in &others {
+            out.push_str(&column_line(col));
+        }
+        out.push_str("}\n\n");
+    }
+
+    for rel in relationships {
+        out.push_str(&format!(
+            "{} }}o--|| {} : {}\n",
+            aliases[rel.from_table.as_str()],
+            aliases[rel.to_table.as_str()],
+            export_label(rel)
+        ));
+    }
+    out.push_str("@enduml\n");
+    out
+}
+
+fn export_d2(map: &SchemaMap) -> String {
+    let (tables, relationships) = sorted_for_export(map);
+    let key = |name: &str| quote_if_needed(name, |n| format!("\"{}\"", n.replace('\\', "\\\\").replace('"', "\\\"")));
+    let mut out = String::new();
+
+    for table in &tables {
+        out.push_str(&format!("{}: {{\n  shape: sql_table\n", key(&table.name)));
+        for col in export_columns(map, table) {
+            let type_name = if col.type_name.is_empty() { "ANY" } else { col.type_name };
+            let constraint = match (col.pk, col.fk) {
+                (true, true) => " {constraint: [primary_key; foreign_key]}",
+                (true, false) => " {constraint: primary_key}",
+                (false, true) => " {constraint: foreign_key}",
+                (false, false) => "",
+            };
+            out.push_str(&format!("  {}: {}{}\n", key(col.name), key(type_name), constraint));
+        }
+        out.push_str("}\n\n");
+    }
+
+    for rel in relationships {
+        let actions = rel.actions();
+        let label = if actions.is_empty() {
+            String::new()
+        } else {
+            format!(": {}", key(&actions))
+        };
+        out.push_str(&format!(
+            "{}.{} -> {}.{}{}\n",
+            key(&rel.from_table),
+            key(first_column(&rel.from_columns)),
+            key(&rel.to_table),
+            key(first_column(&rel.to_columns)),
+            label
+        ));
+    }
+    out
+}
+
+/// Executes `:erd [table] [--depth N] [--direction in|out|both] [--format F [file]]`,
+/// `:erd <table> --cascade` or `:erd --cycles`.
+///
+/// Without `--format` the ASCII diagram is printed. With a format the diagram is
+/// serialized and written to `file`, or printed if no file is given.
+pub fn execute_erd_command(command: &str) -> Result<()> {
+    let mut cycles = false;
+    let mut cascade = false;
+    let mut focus = None;
+    let mut depth = 1;
+    let mut direction = EdgeDirection::Both;
+    let mut format = None;
+    let mut output_file = None;
+
+    let mut args = command.split_whitespace().peekable();
+    while let Some(arg) = args.next() {
+        match arg {
+            "--depth" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| TuiqlError::Command("--depth requires a number".to_string()))?;
+                depth = value
+                    .parse::<usize>()
+                    .map_err(|_| TuiqlError::Command(format!("Invalid depth: '{}'", value)))?;
+            }
+            "--direction" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| TuiqlError::Command("--direction requires in, out or both".to_string()))?;
+                direction = EdgeDirection::parse(value)?;
+            }
+            "--format" => {
+                format = Some(args.next().ok_or_else(|| {
+                    TuiqlError::Command("--format requires dot, mermaid, plantuml or d2".to_string())
+                })?);
+                // An optional output file follows the format
+                if let Some(file) = args.next_if(|next| !next.starts_with("--")) {
+                    output_file = Some(file);
+                }
+            }
+            "--cycles" => cycles = true,
+            "--cascade" => cascade = true,
+            _ if arg.starts_with("--") => {
+                return Err(TuiqlError::Command(format!("Unknown option '{}'", arg)));
+            }
+            _ if focus.is_none() => focus = Some(arg),
+            _ => return Err(TuiqlError::Command(format!("Unexpected argument '{}'", arg))),
+        }
+    }
+
+    let schema_map = generate_schema_map()?;
+    if cycles {
+        print!("{}", render_cycles(&schema_map));
+        return Ok(());
+    }
+    if cascade {
+        let table = focus.ok_or_else(|| TuiqlError::Command("--cascade requires a table".to_string()))?;
+        print!("{}", render_cascade_paths(&schema_map, table)?);
+        return Ok(());
+    }
+    let focused = match focus {
+        Some(table) => Some(focus_schema_map(&schema_map, table, depth, direction)?),
+        None => None,
+    };
+
+    let Some(format) = format else {
+        match &focused {
+            Some(focused) => println!("{}", render_focused_map(focused)),
+            None => println!("{}", render_schema_map(&schema_map)),
+        }
+        return Ok(());
+    };
+
+    let diagram = export_schema_map(focused.as_ref().map_or(&schema_map, |f| &f.map), format)?;
+    match output_file {
+        Some(path) => {
+            std::fs::write(path, &diagram)?;
+            println!("✅ ERD written to {} ({})", path, format);
+        }
+        None => print!("{}", diagram),
+    }
+    Ok(())
+}
+
 /// Renders the schema map as a comprehensive ER-like ASCII diagram.
 /// Shows tables, columns, relationships, and important metadata.
 pub fn render_schema_map(map: &SchemaMap) -> String {
@@ -236,13 +1177,13 @@ pub fn render_schema_map(map: &SchemaMap) -> String {
 
                         if relationships.len() == 1 {
                             let rel = &relationships[0];
-                            diagram.push_str(&format!("    → {} ({} → {})\n", ref_table, rel.from_column, rel.to_column));
+                            diagram.push_str(&format!("    → {} ({})\n", ref_table, rel.label()));
                         } else {
                             // Multiple relationships to the same table
                             diagram.push_str(&format!("    → {} (", ref_table));
                             for (i, rel) in relationships.iter().enumerate() {
                                 if i > 0 { diagram.push_str(", "); }
-                                diagram.push_str(&format!("{}→{}", rel.from_column, rel.to_column));
+                                diagram.push_str(&format!("{}→{}", rel.from_column_list(), rel.to_column_list()));
                             }
                             diagram.push_str(")\n");
                         }
@@ -281,7 +1222,7 @@ pub fn render_schema_map(map: &SchemaMap) -> String {
         for (from_table, relationships) in relationships_by_from {
             for rel in relationships {
                 diagram.push_str(&format!("{} → {} ({} → {})\n",
-                    from_table, rel.to_table, rel.from_column, rel.to_column));
+                    from_table, rel.to_table, rel.from_column_list(), rel.to_column_list()));
             }
         }
 
@@ -338,13 +1279,13 @@ mod tests {
             tables: vec![
                 TableNode {
                     name: "users".to_string(),
-                    columns: vec!["id INTEGER".to_string(), "name TEXT".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("name", "TEXT")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec!["orders".to_string()],
                 },
                 TableNode {
                     name: "orders".to_string(),
-                    columns: vec!["id INTEGER".to_string(), "user_id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("user_id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec![],
                 },
@@ -352,9 +1293,10 @@ mod tests {
             relationships: vec![
                 Relationship {
                     from_table: "orders".to_string(),
-                    from_column: "user_id".to_string(),
+                    from_columns: vec!["user_id".to_string()],
                     to_table: "users".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 }
             ],
         };
@@ -372,31 +1314,31 @@ mod tests {
             tables: vec![
                 TableNode {
                     name: "users".to_string(),
-                    columns: vec!["id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec!["orders".to_string(), "posts".to_string()],
                 },
                 TableNode {
                     name: "orders".to_string(),
-                    columns: vec!["id INTEGER".to_string(), "user_id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("user_id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec!["products".to_string()],
                 },
                 TableNode {
                     name: "posts".to_string(),
-                    columns: vec!["id INTEGER".to_string(), "user_id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("user_id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec![],
                 },
                 TableNode {
                     name: "products".to_string(),
-                    columns: vec!["id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec![],
                 },
                 TableNode {
                     name: "categories".to_string(),
-                    columns: vec!["id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec![], // No relationships - should be in independent group
                 },
@@ -404,21 +1346,24 @@ mod tests {
             relationships: vec![
                 Relationship {
                     from_table: "orders".to_string(),
-                    from_column: "user_id".to_string(),
+                    from_columns: vec!["user_id".to_string()],
                     to_table: "users".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 },
                 Relationship {
                     from_table: "posts".to_string(),
-                    from_column: "user_id".to_string(),
+                    from_columns: vec!["user_id".to_string()],
                     to_table: "users".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 },
                 Relationship {
                     from_table: "orders".to_string(),
-                    from_column: "product_id".to_string(),
+                    from_columns: vec!["product_id".to_string()],
                     to_table: "products".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 },
             ],
         };
@@ -488,19 +1433,19 @@ mod tests {
             tables: vec![
                 TableNode {
                     name: "users".to_string(),
-                    columns: vec!["id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec!["orders".to_string()],
                 },
                 TableNode {
                     name: "orders".to_string(),
-                    columns: vec!["id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec![],
                 },
                 TableNode {
                     name: "products".to_string(),
-                    columns: vec!["id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec![], // No relationships
                 },
@@ -508,9 +1453,10 @@ mod tests {
             relationships: vec![
                 Relationship {
                     from_table: "orders".to_string(),
-                    from_column: "user_id".to_string(),
+                    from_columns: vec!["user_id".to_string()],
                     to_table: "users".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 }
             ],
         };
@@ -535,13 +1481,13 @@ mod tests {
             tables: vec![
                 TableNode {
                     name: "users".to_string(),
-                    columns: vec!["id INTEGER PRIMARY KEY".to_string(), "name TEXT".to_string(), "email TEXT".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER PRIMARY KEY"), MapColumn::new("name", "TEXT"), MapColumn::new("email", "TEXT")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec!["orders".to_string()],
                 },
                 TableNode {
                     name: "orders".to_string(),
-                    columns: vec!["id INTEGER PRIMARY KEY".to_string(), "user_id INTEGER".to_string(), "amount REAL".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER PRIMARY KEY"), MapColumn::new("user_id", "INTEGER"), MapColumn::new("amount", "REAL")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec![],
                 },
@@ -549,9 +1495,10 @@ mod tests {
             relationships: vec![
                 Relationship {
                     from_table: "orders".to_string(),
-                    from_column: "user_id".to_string(),
+                    from_columns: vec!["user_id".to_string()],
                     to_table: "users".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 }
             ],
         };
@@ -565,31 +1512,31 @@ mod tests {
             tables: vec![
                 TableNode {
                     name: "users".to_string(),
-                    columns: vec!["id INTEGER".to_string(), "name TEXT".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("name", "TEXT")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec!["orders".to_string(), "posts".to_string()],
                 },
                 TableNode {
                     name: "orders".to_string(),
-                    columns: vec!["id INTEGER".to_string(), "user_id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("user_id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec!["products".to_string()],
                 },
                 TableNode {
                     name: "posts".to_string(),
-                    columns: vec!["id INTEGER".to_string(), "user_id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("user_id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec![],
                 },
                 TableNode {
                     name: "products".to_string(),
-                    columns: vec!["id INTEGER".to_string(), "category_id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("category_id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec!["categories".to_string()],
                 },
                 TableNode {
                     name: "categories".to_string(),
-                    columns: vec!["id INTEGER".to_string(), "name TEXT".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("name", "TEXT")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec![],
                 },
@@ -597,27 +1544,31 @@ mod tests {
             relationships: vec![
                 Relationship {
                     from_table: "orders".to_string(),
-                    from_column: "user_id".to_string(),
+                    from_columns: vec!["user_id".to_string()],
                     to_table: "users".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 },
                 Relationship {
                     from_table: "posts".to_string(),
-                    from_column: "user_id".to_string(),
+                    from_columns: vec!["user_id".to_string()],
                     to_table: "users".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 },
                 Relationship {
                     from_table: "orders".to_string(),
-                    from_column: "product_id".to_string(),
+                    from_columns: vec!["product_id".to_string()],
                     to_table: "products".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 },
                 Relationship {
                     from_table: "products".to_string(),
-                    from_column: "category_id".to_string(),
+                    from_columns: vec!["category_id".to_string()],
                     to_table: "categories".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 },
             ],
         };
@@ -631,13 +1582,13 @@ mod tests {
             tables: vec![
                 TableNode {
                     name: "users".to_string(),
-                    columns: vec!["id INTEGER".to_string(), "name TEXT".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("name", "TEXT")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec![],
                 },
                 TableNode {
                     name: "logs".to_string(),
-                    columns: vec!["timestamp DATETIME".to_string(), "message TEXT".to_string()],
+                    columns: vec![MapColumn::new("timestamp", "DATETIME"), MapColumn::new("message", "TEXT")],
                     primary_keys: vec![],
                     outgoing_references: vec![],
                 },
@@ -654,13 +1605,13 @@ mod tests {
             tables: vec![
                 TableNode {
                     name: "users".to_string(),
-                    columns: vec!["id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec!["posts".to_string()],
                 },
                 TableNode {
                     name: "posts".to_string(),
-                    columns: vec!["id INTEGER".to_string(), "user_id INTEGER".to_string(), "parent_post_id INTEGER".to_string()],
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("user_id", "INTEGER"), MapColumn::new("parent_post_id", "INTEGER")],
                     primary_keys: vec!["id".to_string()],
                     outgoing_references: vec!["users".to_string(), "posts".to_string()], // Circular
                 },
@@ -668,26 +1619,349 @@ mod tests {
             relationships: vec![
                 Relationship {
                     from_table: "posts".to_string(),
-                    from_column: "user_id".to_string(),
+                    from_columns: vec!["user_id".to_string()],
                     to_table: "users".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 },
                 Relationship {
                     from_table: "posts".to_string(),
-                    from_column: "parent_post_id".to_string(),
+                    from_columns: vec!["parent_post_id".to_string()],
                     to_table: "posts".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 },
                 // Add reverse relationship to create circular
                 Relationship {
                     from_table: "users".to_string(),
-                    from_column: "main_post_id".to_string(),
+                    from_columns: vec!["main_post_id".to_string()],
                     to_table: "posts".to_string(),
-                    to_column: "id".to_string(),
+                    to_columns: vec!["id".to_string()],
+                    ..Default::default()
                 },
             ],
         };
         let output = render_schema_map(&map);
         assert_snapshot!("schema_map_circular_reference", output);
     }
+
+    /// Single-column foreign key `from.column -> to.id`
+    fn fk(from: &str, column: &str, to: &str) -> Relationship {
+        Relationship {
+            from_table: from.to_string(),
+            from_columns: vec![column.to_string()],
+            to_table: to.to_string(),
+            to_columns: vec!["id".to_string()],
+            ..Default::default()
+        }
+    }
+
+    /// Map of tables with an `id` primary key, linked by `relationships`
+    fn fk_map(tables: &[&str], relationships: Vec<Relationship>) -> SchemaMap {
+        let tables = tables
+            .iter()
+            .map(|name| {
+                let mut outgoing_references: Vec<String> = Vec::new();
+                for rel in relationships.iter().filter(|r| r.from_table == *name) {
+                    if !outgoing_references.contains(&rel.to_table) {
+                        outgoing_references.push(rel.to_table.clone());
+                    }
+                }
+                TableNode {
+                    name: name.to_string(),
+                    columns: vec![MapColumn::new("id", "INTEGER")],
+                    primary_keys: vec!["id".to_string()],
+                    outgoing_references,
+                }
+            })
+            .collect();
+        SchemaMap { tables, relationships }
+    }
+
+    fn chain_map() -> SchemaMap {
+        // customers <- orders <- order_items -> products -> categories; tags is unrelated
+        fk_map(
+            &["customers", "orders", "order_items", "products", "categories", "tags", "tag_links"],
+            vec![
+                fk("orders", "customer_id", "customers"),
+                fk("order_items", "order_id", "orders"),
+                fk("order_items", "product_id", "products"),
+                fk("products", "category_id", "categories"),
+                fk("tag_links", "tag_id", "tags"),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_focus_schema_map_depth_and_direction() {
+        let map = chain_map();
+
+        let focused = focus_schema_map(&map, "orders", 1, EdgeDirection::Both).unwrap();
+        let names: Vec<&str> = focused.hops.keys().map(|s| s.as_str()).collect();
+        assert_eq!(names, vec!["customers", "order_items", "orders"]);
+        assert_eq!(focused.beyond_depth, vec!["categories", "products"]);
+        assert_eq!(focused.collapsed_clusters, vec![vec!["tag_links".to_string(), "tags".to_string()]]);
+        assert_eq!(focused.map.relationships.len(), 2);
+
+        let focused = focus_schema_map(&map, "orders", 2, EdgeDirection::Both).unwrap();
+        assert_eq!(focused.hops.get("products"), Some(&2));
+        assert!(!focused.hops.contains_key("categories"));
+
+        let focused = focus_schema_map(&map, "orders", 5, EdgeDirection::Out).unwrap();
+        let names: Vec<&str> = focused.hops.keys().map(|s| s.as_str()).collect();
+        assert_eq!(names, vec!["customers", "orders"]);
+
+        let focused = focus_schema_map(&map, "ORDERS", 5, EdgeDirection::In).unwrap();
+        let names: Vec<&str> = focused.hops.keys().map(|s| s.as_str()).collect();
+        assert_eq!(names, vec!["order_items", "orders"]);
+
+        assert!(focus_schema_map(&map, "missing", 1, EdgeDirection::Both).is_err());
+    }
+
+    #[test]
+    fn test_render_focused_map() {
+        let focused = focus_schema_map(&chain_map(), "orders", 1, EdgeDirection::Both).unwrap();
+        let output = render_focused_map(&focused);
+
+        assert!(output.starts_with("=== ER Diagram: orders (depth 1, direction both) ===\n"));
+        assert!(output.contains("🎯 Table: orders\n"));
+        assert!(output.contains("📋 Table: customers (1 hop)\n"));
+        assert!(output.contains("    → customers (customer_id → id)\n"));
+        assert!(output.contains("    ← order_items (order_id → id)\n"));
+        assert!(output.contains("… 2 more related table(s) not shown: categories, products\n"));
+        assert!(output.contains("📦 1 unrelated cluster(s) collapsed (2 table(s)):\n  [2] tag_links, tags\n"));
+        // The focus is listed before its neighbours
+        assert!(output.find("Table: orders").unwrap() < output.find("Table: customers").unwrap());
+    }
+
+    #[test]
+    fn test_edge_direction_parse() {
+        assert_eq!(EdgeDirection::parse("IN").unwrap(), EdgeDirection::In);
+        assert_eq!(EdgeDirection::parse("both").unwrap(), EdgeDirection::Both);
+        assert!(EdgeDirection::parse("sideways").is_err());
+    }
+
+    #[test]
+    fn test_summarize_names() {
+        let names: Vec<String> = (1..=7).map(|i| format!("t{}", i)).collect();
+        assert_eq!(summarize_names(&names[..2]), "t1, t2");
+        assert_eq!(summarize_names(&names), "t1, t2, t3, t4, t5, … (+2 more)");
+    }
+
+    #[test]
+    fn test_export_schema_map_formats() {
+        let map = chain_map();
+        for format in ["dot", "mermaid", "plantuml", "d2"] {
+            let output = export_schema_map(&map, format).unwrap();
+            // Input order must not leak into the output
+            let mut reversed = map.clone();
+            reversed.tables.reverse();
+            reversed.relationships.reverse();
+            assert_eq!(output, export_schema_map(&reversed, format).unwrap(), "{} output is not deterministic", format);
+        }
+        assert!(export_schema_map(&map, "svg").is_err());
+    }
+
+    fn export_map() -> SchemaMap {
+        SchemaMap {
+            tables: vec![
+                TableNode {
+                    name: "users".to_string(),
+                    columns: vec![MapColumn::new("id", "INTEGER"), MapColumn::new("name", "TEXT")],
+                    primary_keys: vec!["id".to_string()],
+                    outgoing_references: vec![],
+                },
+                TableNode {
+                    name: "order lines".to_string(),
+                    columns: vec![
+                        MapColumn::new("id", "INTEGER"),
+                        MapColumn::new("user_id", "INTEGER"),
+                        MapColumn::new("price", "DECIMAL(10,2)"),
+                        MapColumn::new("note", ""),
+                    ],
+                    primary_keys: vec!["id".to_string()],
+                    outgoing_references: vec!["users".to_string()],
+                },
+            ],
+            relationships: vec![Relationship {
+                from_table: "order lines".to_string(),
+                from_columns: vec!["user_id".to_string()],
+                to_table: "users".to_string(),
+                to_columns: vec!["id".to_string()],
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[test]
+    fn test_export_schema_map_golden_dot() {
+        assert_snapshot!("erd_export_dot", export_schema_map(&export_map(), "dot").unwrap());
+    }
+
+    #[test]
+    fn test_export_schema_map_golden_mermaid() {
+        assert_snapshot!("erd_export_mermaid", export_schema_map(&export_map(), "mermaid").unwrap());
+    }
+
+    #[test]
+    fn test_export_schema_map_golden_plantuml() {
+        assert_snapshot!("erd_export_plantuml", export_schema_map(&export_map(), "plantuml").unwrap());
+    }
+
+    #[test]
+    fn test_export_schema_map_golden_d2() {
+        assert_snapshot!("erd_export_d2", export_schema_map(&export_map(), "d2").unwrap());
+    }
+
+    #[test]
+    fn test_export_plantuml_names_with_spaces_and_colliding_aliases() {
+        let table = |name: &str, columns: Vec<MapColumn>| TableNode {
+            name: name.to_string(),
+            columns,
+            primary_keys: vec![],
+            outgoing_references: vec![],
+        };
+        let map = SchemaMap {
+            tables: vec![
+                table("a-b", vec![MapColumn::new("unit price", "DECIMAL(10,2)")]),
+                table("a_b", vec![MapColumn::new("id", "INTEGER")]),
+            ],
+            relationships: vec![Relationship {
+                from_table: "a-b".to_string(),
+                from_columns: vec!["unit price".to_string()],
+                to_table: "a_b".to_string(),
+                to_columns: vec!["id".to_string()],
+                ..Default::default()
+            }],
+        };
+        let output = export_schema_map(&map, "plantuml").unwrap();
+        assert!(output.contains("entity \"a-b\" as a_b {\n  unit price : DECIMAL(10,2) <<FK>>\n"), "{}", output);
+        assert!(output.contains("entity \"a_b\" as a_b_2 {"), "{}", output);
+        assert!(output.contains("a_b }o--|| a_b_2 : unit price -> id\n"), "{}", output);
+    }
+
+    fn cyclic_map() -> SchemaMap {
+        // users <-> teams cycle, categories self-reference, orders -> users
+        fk_map(
+            &["orders", "users", "teams", "categories", "logs"],
+            vec![
+                fk("users", "team_id", "teams"),
+                fk("teams", "owner_id", "users"),
+                fk("categories", "parent_id", "categories"),
+                fk("orders", "user_id", "users"),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_find_cycles() {
+        let cycles = find_cycles(&cyclic_map());
+        assert_eq!(
+            cycles,
+            vec![vec!["categories".to_string()], vec!["teams".to_string(), "users".to_string()]]
+        );
+        assert!(find_cycles(&chain_map()).is_empty());
+    }
+
+    #[test]
+    fn test_render_cycles() {
+        let output = render_cycles(&cyclic_map());
+        assert!(output.starts_with("🔁 2 foreign-key cycle(s):\n"));
+        assert!(output.contains("  1. categories (self-reference)\n       categories.parent_id → categories.id\n"));
+        assert!(output.contains("  2. teams ↔ users\n       teams.owner_id → users.id\n       users.team_id → teams.id\n"));
+        assert_eq!(render_cycles(&chain_map()), "✅ No foreign-key cycles found.\n");
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let steps = topological_order(&chain_map());
+        let order: Vec<&str> = steps.iter().map(|s| s.tables[0].as_str()).collect();
+        assert_eq!(
+            order,
+            vec!["categories", "customers", "orders", "products", "order_items", "tags", "tag_links"]
+        );
+        assert!(steps.iter().all(|s| !s.cyclic));
+
+        let steps = topological_order(&cyclic_map());
+        assert_eq!(
+            steps,
+            vec![
+                OrderStep { tables: vec!["categories".to_string()], cyclic: false },
+                OrderStep { tables: vec!["logs".to_string()], cyclic: false },
+                OrderStep { tables: vec!["teams".to_string(), "users".to_string()], cyclic: true },
+                OrderStep { tables: vec!["orders".to_string()], cyclic: false },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_table_order() {
+        let output = render_table_order(&cyclic_map(), "both").unwrap();
+        assert!(output.contains("📥 Load order (referenced tables first):\n    1. categories\n"));
+        assert!(output.contains("    3. ⚠️  teams, users (cycle: load together with deferred FKs)\n"));
+        assert!(output.contains("🗑️  Delete order (referencing tables first):\n    1. orders\n"));
+        assert!(output.contains("PRAGMA defer_foreign_keys"));
+
+        let delete = render_table_order(&chain_map(), "delete").unwrap();
+        assert!(!delete.contains("Load order"));
+        assert!(render_table_order(&chain_map(), "sideways").is_err());
+    }
+
+    fn cascade_map() -> SchemaMap {
+        let on_delete = |action, rel: Relationship| Relationship { on_delete: action, ..rel };
+        fk_map(
+            &["customers", "orders", "order_items", "invoices", "payments"],
+            vec![
+                on_delete(FkAction::Cascade, fk("orders", "customer_id", "customers")),
+                on_delete(FkAction::Cascade, fk("order_items", "order_id", "orders")),
+                on_delete(FkAction::SetNull, fk("invoices", "customer_id", "customers")),
+                on_delete(FkAction::Restrict, fk("payments", "order_id", "orders")),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_render_cascade_paths() {
+        let output = render_cascade_paths(&cascade_map(), "customers").unwrap();
+        assert_eq!(
+            output,
+            "🧨 Deleting a row from customers affects:\n  customers\n\
+             \x20 └─ ✏️  invoices via customer_id → id: ON DELETE SET NULL\n\
+             \x20 └─ 🗑️  orders via customer_id → id: ON DELETE CASCADE\n\
+             \x20    └─ 🗑️  order_items via order_id → id: ON DELETE CASCADE\n\
+             \x20    └─ ⛔ payments via order_id → id: RESTRICT blocks the delete while rows reference it\n"
+        );
+
+        let leaf = render_cascade_paths(&cascade_map(), "payments").unwrap();
+        assert!(leaf.ends_with("(no tables reference it)\n"));
+        assert!(render_cascade_paths(&cascade_map(), "missing").is_err());
+    }
+
+    #[test]
+    fn test_composite_relationship_labels() {
+        let mut map = cascade_map();
+        map.tables.push(TableNode {
+            name: "shipments".to_string(),
+            columns: vec![MapColumn::new("order_id", "INTEGER"), MapColumn::new("line", "INTEGER")],
+            primary_keys: vec![],
+            outgoing_references: vec!["order_items".to_string()],
+        });
+        map.relationships.push(Relationship {
+            from_table: "shipments".to_string(),
+            from_columns: vec!["order_id".to_string(), "line".to_string()],
+            to_table: "order_items".to_string(),
+            to_columns: vec!["order_id".to_string(), "line".to_string()],
+            on_update: FkAction::Cascade,
+            ..Default::default()
+        });
+
+        let mermaid = export_schema_map(&map, "mermaid").unwrap();
+        assert!(mermaid.contains("        INTEGER line FK\n"));
+        assert!(mermaid.contains("shipments }o--|| order_items : \"order_id, line -> order_id, line ON UPDATE CASCADE\""));
+        let d2 = export_schema_map(&map, "d2").unwrap();
+        assert!(d2.contains("shipments.order_id -> order_items.order_id: \"ON UPDATE CASCADE\"\n"));
+
+        let focused = focus_schema_map(&map, "shipments", 1, EdgeDirection::Out).unwrap();
+        assert!(render_focused_map(&focused).contains("    → order_items (order_id, line → order_id, line, ON UPDATE CASCADE)\n"));
+    }
 }
@@ -0,0 +1,314 @@
+//! Space Analyzer Module
+//!
+//! Breaks down the database file by table and index using the `dbstat` virtual
+//! table, in the spirit of `sqlite3_analyzer`: page counts, payload and unused
+//! bytes, overflow pages and fragmentation, sorted by size.
+
+use crate::core::{Result, TuiqlError};
+use crate::db;
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+/// Space used by a single table or index b-tree
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpaceUsage {
+    pub name: String,
+    /// Table the b-tree belongs to; the table itself for tables
+    pub table_name: String,
+    /// "table" or "index"
+    pub kind: String,
+    pub pages: u64,
+    pub leaf_pages: u64,
+    pub interior_pages: u64,
+    pub overflow_pages: u64,
+    pub cells: u64,
+    pub payload_bytes: u64,
+    pub unused_bytes: u64,
+    /// Total bytes of all pages of the b-tree
+    pub total_bytes: u64,
+    /// Pages that do not directly follow the previous page of the b-tree
+    pub gaps: u64,
+}
+
+impl SpaceUsage {
+    /// Percentage of pages that are out of sequence, as reported by sqlite3_analyzer
+    pub fn fragmentation(&self) -> f64 {
+        if self.pages <= 1 {
+            0.0
+        } else {
+            self.gaps as f64 * 100.0 / (self.pages - 1) as f64
+        }
+    }
+
+    /// Percentage of page bytes holding payload
+    pub fn fill(&self) -> f64 {
+        if self.total_bytes == 0 {
+            0.0
+        } else {
+            self.payload_bytes as f64 * 100.0 / self.total_bytes as f64
+        }
+    }
+}
+
+/// Collects per-table and per-index space usage, largest first.
+pub fn analyze_space(conn: &Connection) -> Result<Vec<SpaceUsage>> {
+    let mut owners: HashMap<String, (String, String)> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT name, type, tbl_name FROM sqlite_master WHERE type IN ('table', 'index')")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?;
+    for row in rows {
+        let (name, kind, table_name) = row?;
+        owners.insert(name, (kind, table_name));
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT name, pageno, pagetype, ncell, payload, unused, pgsize FROM dbstat('main')")
+        .map_err(|e| TuiqlError::Query(format!("dbstat virtual table is not available: {}", e)))?;
+    let mut rows = stmt.query([])?;
+
+    let mut usages: Vec<SpaceUsage> = Vec::new();
+    let mut index_of: HashMap<String, usize> = HashMap::new();
+    let mut last_page: HashMap<String, i64> = HashMap::new();
+    while let Some(row) = rows.next()? {
+        let name: String = row.get(0)?;
+        let pageno: i64 = row.get(1)?;
+        let pagetype: String = row.get(2)?;
+        let idx = *index_of.entry(name.clone()).or_insert_with(|| {
+            let (kind, table_name) = owners
+                .get(&name)
+                .cloned()
+                .unwrap_or_else(|| ("table".to_string(), name.clone()));
+            usages.push(SpaceUsage {
+                name: name.clone(),
+                table_name,
+                kind,
+                ..Default::default()
+            });
+            usages.len() - 1
+        });
+
+        let usage = &mut usages[idx];
+        usage.pages += 1;
+        match pagetype.as_str() {
+            "leaf" => usage.leaf_pages += 1,
+            "internal" => usage.interior_pages += 1,
+            _ => usage.overflow_pages += 1,
+        }
+        usage.cells += row.get::<_, i64>(3)? as u64;
+        usage.payload_bytes += row.get::<_, i64>(4)? as u64;
+        usage.unused_bytes += row.get::<_, i64>(5)? as u64;
+        usage.total_bytes += row.get::<_, i64>(6)? as u64;
+        if let Some(previous) = last_page.insert(name, pageno) {
+            if pageno != previous + 1 {
+                usage.gaps += 1;
+            }
+        }
+    }
+
+    usages.sort_by(|a, b| b.total_bytes.cmp(&a.total_bytes).then_with(|| a.name.cmp(&b.name)));
+    Ok(usages)
+}
+
+/// Formats a byte count as B, KB, MB or GB.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+fn render_rows(usages: &[SpaceUsage], whole: u64, output: &mut String) {
+    let width = usages.iter().map(|u| u.name.len()).max().unwrap_or(4).max(4);
+    output.push_str(&format!(
+        "{:<width$}  {:<5}  {:>6}  {:>6}  {:>10}  {:>10}  {:>10}  {:>8}  {:>5}\n",
+        "Name", "Kind", "Share", "Pages", "Size", "Payload", "Unused", "Overflow", "Frag",
+        width = width
+    ));
+    for usage in usages {
+        output.push_str(&format!(
+            "{:<width$}  {:<5}  {:>5.1}%  {:>6}  {:>10}  {:>10}  {:>10}  {:>8}  {:>4.0}%\n",
+            usage.name,
+            usage.kind,
+            percent(usage.total_bytes, whole),
+            usage.pages,
+            format_bytes(usage.total_bytes),
+            format_bytes(usage.payload_bytes),
+            format_bytes(usage.unused_bytes),
+            usage.overflow_pages,
+            usage.fragmentation(),
+            width = width
+        ));
+    }
+}
+
+/// Renders the space used by every table and index, largest first.
+pub fn render_space_report(usages: &[SpaceUsage]) -> String {
+    let total: u64 = usages.iter().map(|u| u.total_bytes).sum();
+    let mut output = format!("💾 Space usage ({} in {} b-trees)\n", format_bytes(total), usages.len());
+    output.push_str(&"=".repeat(60));
+    output.push('\n');
+    render_rows(usages, total, &mut output);
+    output
+}
+
+/// Renders a drill-down of one table and its indexes.
+pub fn render_table_space(usages: &[SpaceUsage], table: &str) -> Result<String> {
+    let objects: Vec<SpaceUsage> = usages
+        .iter()
+        .filter(|u| u.table_name.eq_ignore_ascii_case(table))
+        .cloned()
+        .collect();
+    if objects.is_empty() {
+        return Err(TuiqlError::Schema(format!("Table '{}' not found", table)));
+    }
+
+    let database_total: u64 = usages.iter().map(|u| u.total_bytes).sum();
+    let table_total: u64 = objects.iter().map(|u| u.total_bytes).sum();
+    let index_total: u64 = objects.iter().filter(|u| u.kind == "index").map(|u| u.total_bytes).sum();
+
+    let mut output = format!(
+        "💾 Space used by {} ({}, {:.1}% of the database)\n",
+        objects[0].table_name,
+        format_bytes(table_total),
+        percent(table_total, database_total)
+    );
+    output.push_str(&"=".repeat(60));
+    output.push('\n');
+    render_rows(&objects, table_total, &mut output);
+
+    output.push('\n');
+    for usage in &objects {
+        output.push_str(&format!(
+            "{} {}: {} leaf, {} interior, {} overflow page(s); {} cell(s), {:.0}% full",
+            if usage.kind == "index" { "📇" } else { "📋" },
+            usage.name,
+            usage.leaf_pages,
+            usage.interior_pages,
+            usage.overflow_pages,
+            usage.cells,
+            usage.fill()
+        ));
+        if let Some(per_cell) = usage.payload_bytes.checked_div(usage.cells) {
+            output.push_str(&format!(", {} payload per cell", format_bytes(per_cell)));
+        }
+        output.push('\n');
+    }
+    if index_total > 0 {
+        output.push_str(&format!(
+            "\nIndexes take {} ({:.1}% of the table's space)\n",
+            format_bytes(index_total),
+            percent(index_total, table_total)
+        ));
+    }
+    Ok(output)
+}
+
+/// Executes `:space [table]`.
+pub fn execute_space_command(command: &str) -> Result<()> {
+    let mut table = None;
+    for arg in command.split_whitespace() {
+        if arg.starts_with("--") {
+            return Err(TuiqlError::Command(format!("Unknown option '{}'", arg)));
+        }
+        if table.replace(arg).is_some() {
+            return Err(TuiqlError::Command("Usage: :space [table]".to_string()));
+        }
+    }
+
+    let state_cell = db::DB_STATE.get().ok_or(TuiqlError::App("No database connection found".to_string()))?;
+    let state_guard = state_cell
+        .lock()
+        .map_err(|_| TuiqlError::App("Failed to acquire database lock".to_string()))?;
+    let conn = state_guard.connection.as_ref().ok_or(TuiqlError::App("No active connection".to_string()))?;
+
+    let usages = analyze_space(conn)?;
+    match table {
+        Some(table) => print!("{}", render_table_space(&usages, table)?),
+        None => print!("{}", render_space_report(&usages)),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE docs (id INTEGER PRIMARY KEY, title TEXT, body TEXT);
+             CREATE INDEX idx_docs_title ON docs(title);
+             CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT UNIQUE);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 300)
+             INSERT INTO docs (title, body) SELECT 'title ' || i, hex(randomblob(300)) FROM n;
+             INSERT INTO docs (title, body) VALUES ('big', hex(randomblob(20000)));
+             INSERT INTO tags (name) VALUES ('a'), ('b');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_analyze_space() {
+        let conn = setup();
+        let usages = analyze_space(&conn).unwrap();
+
+        assert_eq!(usages[0].name, "docs");
+        assert!(usages.windows(2).all(|w| w[0].total_bytes >= w[1].total_bytes));
+
+        let docs = &usages[0];
+        assert_eq!(docs.kind, "table");
+        assert!(docs.overflow_pages > 0);
+        assert!(docs.interior_pages > 0);
+        assert_eq!(docs.pages, docs.leaf_pages + docs.interior_pages + docs.overflow_pages);
+        assert!(docs.payload_bytes + docs.unused_bytes <= docs.total_bytes);
+
+        let index = usages.iter().find(|u| u.name == "idx_docs_title").unwrap();
+        assert_eq!(index.kind, "index");
+        assert_eq!(index.table_name, "docs");
+        assert_eq!(index.cells, 301);
+
+        let autoindex = usages.iter().find(|u| u.name.starts_with("sqlite_autoindex_tags")).unwrap();
+        assert_eq!(autoindex.table_name, "tags");
+    }
+
+    #[test]
+    fn test_render_space() {
+        let conn = setup();
+        let usages = analyze_space(&conn).unwrap();
+
+        let report = render_space_report(&usages);
+        assert!(report.contains("idx_docs_title"));
+        assert!(report.contains("Overflow"));
+
+        let drill_down = render_table_space(&usages, "DOCS").unwrap();
+        assert!(drill_down.contains("Space used by docs"));
+        assert!(drill_down.contains("📇 idx_docs_title"));
+        assert!(drill_down.contains("Indexes take"));
+        assert!(!drill_down.contains("tags"));
+
+        assert!(render_table_space(&usages, "missing").is_err());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(4096), "4.0 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 / 2), "1.5 MB");
+    }
+}
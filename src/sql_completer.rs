@@ -1,6 +1,7 @@
+use crate::catalog::{self, Catalog};
 use crate::core::{Result, TuiqlError};
-use crate::db::{get_schema, Schema};
 use regex::Regex;
+use std::sync::Arc;
 
 /// SQL keywords that are commonly used in SQLite
 const SQL_KEYWORDS: &[&str] = &[
@@ -166,8 +167,7 @@ const SQLITE_PRAGMAS: &[&str] = &[
 
 /// Context-aware SQL completer that provides suggestions based on current query and schema
 pub struct SqlCompleter {
-    schema: Option<Schema>,
-    last_update: std::time::Instant,
+    catalog: Option<Arc<Catalog>>,
 }
 
 impl Default for SqlCompleter {
@@ -179,23 +179,22 @@ impl Default for SqlCompleter {
 impl SqlCompleter {
     /// Creates a new SQL completer
     pub fn new() -> Self {
-        SqlCompleter {
-            schema: None,
-            last_update: std::time::Instant::now(),
-        }
+        SqlCompleter { catalog: None }
     }
 
     /// Updates the completer with current schema information
+    ///
+    /// The catalog is cached and only rebuilt after a schema change, so this is
+    /// cheap enough to call before every completion.
     pub fn update_schema(&mut self) -> Result<()> {
-        match get_schema() {
-            Ok(schema) => {
-                self.schema = Some(schema);
-                self.last_update = std::time::Instant::now();
+        match catalog::current_catalog() {
+            Ok(catalog) => {
+                self.catalog = Some(catalog);
                 Ok(())
             }
             Err(TuiqlError::Schema(_)) => {
                 // No database connected, keep empty schema
-                self.schema = None;
+                self.catalog = None;
                 Ok(())
             }
             Err(e) => Err(e),
@@ -204,10 +203,7 @@ impl SqlCompleter {
 
     /// Gets completion suggestions for the given query and cursor position
     pub fn complete(&mut self, query: &str, cursor_pos: usize) -> Result<Vec<String>> {
-        // Update schema periodically (every 5 seconds to avoid performance issues)
-        if self.last_update.elapsed() > std::time::Duration::from_secs(5) {
-            self.update_schema()?;
-        }
+        self.update_schema()?;
 
         if query.is_empty() {
             return Ok(Vec::new());
@@ -409,7 +405,7 @@ impl SqlCompleter {
                 // Add common table-related keywords first, then table names
                 suggestions.extend(self.filter_keywords(&["JOIN", "INNER", "LEFT", "RIGHT", "OUTER", "ON", "WHERE", "GROUP", "ORDER", "BY", "LIMIT"], prefix));
                 // Add table names
-                if let Some(schema) = &self.schema {
+                if let Some(schema) = self.catalog.as_ref().map(|c| &c.schema) {
                     suggestions.extend(self.filter_keywords(
                         &schema.tables.keys().map(|s| s.as_str()).collect::<Vec<_>>(),
                         prefix,
@@ -419,7 +415,7 @@ impl SqlCompleter {
             CompletionContext::ColumnName => {
                 suggestions.extend(self.filter_keywords(SQL_KEYWORDS, prefix));
                 // Add column names from all tables (this is a simplification)
-                if let Some(schema) = &self.schema {
+                if let Some(schema) = self.catalog.as_ref().map(|c| &c.schema) {
                     let mut column_names: Vec<&str> = Vec::new();
                     for table in schema.tables.values() {
                         for column in &table.columns {
@@ -450,7 +446,7 @@ impl SqlCompleter {
                 // Suggest FTS5-specific functions
                 suggestions.extend(self.filter_keywords(&["highlight", "snippet", "bm25"], prefix));
                 // Also include FTS5 tables for function context
-                if let Some(schema) = &self.schema {
+                if let Some(schema) = self.catalog.as_ref().map(|c| &c.schema) {
                     let fts5_tables: Vec<&str> = schema.tables.keys()
                         .filter(|table_name| table_name.contains("_fts") || table_name.contains("fts5"))
                         .map(|s| s.as_str())
@@ -572,7 +568,7 @@ mod tests {
     #[test]
     fn test_sql_completer_creation() {
         let completer = SqlCompleter::new();
-        assert!(completer.schema.is_none());
+        assert!(completer.catalog.is_none());
     }
 
     #[test]
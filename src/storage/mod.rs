@@ -1,5 +1,6 @@
 //! Storage module for managing persistent data like query history and configuration
-use rusqlite::{Connection, Result as SqlResult};
+use crate::core::db::schema::ddl_fingerprint;
+use rusqlite::{Connection, OptionalExtension, Result as SqlResult};
 use std::path::PathBuf;
 use std::time::SystemTime;
 use tracing::{debug, error};
@@ -12,7 +13,38 @@ CREATE TABLE IF NOT EXISTS query_history (
     database_path TEXT NOT NULL,
     success BOOLEAN NOT NULL,
     duration_ms INTEGER,
-    row_count INTEGER
+    row_count INTEGER,
+    stats TEXT,
+    plan TEXT
+)"#;
+
+const SCHEMA_SNAPSHOTS_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS schema_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    database_path TEXT NOT NULL,
+    schema_version INTEGER NOT NULL,
+    fingerprint TEXT NOT NULL,
+    ddl TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    UNIQUE (database_path, schema_version, fingerprint)
+)"#;
+
+const TABLE_PROFILES_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS table_profiles (
+    database_path TEXT NOT NULL,
+    table_name TEXT NOT NULL,
+    profile TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (database_path, table_name)
+)"#;
+
+const BENCHMARKS_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS benchmarks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    database_path TEXT NOT NULL,
+    query TEXT NOT NULL,
+    result TEXT NOT NULL,
+    timestamp INTEGER NOT NULL
 )"#;
 
 #[derive(Debug, Clone)]
@@ -24,6 +56,10 @@ pub struct HistoryEntry {
     pub success: bool,
     pub duration_ms: Option<i64>,
     pub row_count: Option<i64>,
+    /// Statement status counters as JSON, recorded while `:stats on` is active
+    pub stats: Option<String>,
+    /// Normalized query plan as JSON, for successful queries that have one
+    pub plan: Option<String>,
 }
 
 impl HistoryEntry {
@@ -45,8 +81,76 @@ impl HistoryEntry {
             success,
             duration_ms,
             row_count,
+            stats: None,
+            plan: None,
+        }
+    }
+
+    /// Attaches statement status counters, serialized as JSON, to the entry
+    pub fn with_stats(mut self, stats: Option<String>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Attaches the query plan the query ran with, serialized as JSON
+    pub fn with_plan(mut self, plan: Option<String>) -> Self {
+        self.plan = plan;
+        self
+    }
+
+    fn from_row(row: &rusqlite::Row) -> SqlResult<Self> {
+        Ok(HistoryEntry {
+            id: row.get(0)?,
+            query: row.get(1)?,
+            timestamp: row.get(2)?,
+            database_path: row.get(3)?,
+            success: row.get(4)?,
+            duration_ms: row.get(5)?,
+            row_count: row.get(6)?,
+            stats: row.get(7)?,
+            plan: row.get(8)?,
+        })
+    }
+}
+
+/// A copy of a database's schema DDL taken when the database was opened
+#[derive(Debug, Clone)]
+pub struct SchemaSnapshot {
+    pub id: i64,
+    pub database_path: String,
+    /// `PRAGMA schema_version` at the time of the snapshot
+    pub schema_version: i64,
+    /// Fingerprint of `ddl`, used to tell snapshots apart at a glance
+    pub fingerprint: String,
+    pub ddl: String,
+    pub timestamp: i64,
+}
+
+impl SchemaSnapshot {
+    pub fn new(database_path: String, schema_version: i64, ddl: String) -> Self {
+        Self {
+            id: 0, // Will be set by database
+            database_path,
+            schema_version,
+            fingerprint: ddl_fingerprint(&ddl),
+            ddl,
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i64,
         }
     }
+
+    fn from_row(row: &rusqlite::Row) -> SqlResult<Self> {
+        Ok(SchemaSnapshot {
+            id: row.get(0)?,
+            database_path: row.get(1)?,
+            schema_version: row.get(2)?,
+            fingerprint: row.get(3)?,
+            ddl: row.get(4)?,
+            timestamp: row.get(5)?,
+        })
+    }
 }
 
 pub struct Storage {
@@ -66,14 +170,34 @@ impl Storage {
     /// Initialize the storage schema
     fn init(&self) -> SqlResult<()> {
         self.conn.execute(HISTORY_TABLE_SQL, [])?;
+        // History tables created before statement stats and plans were recorded lack the columns
+        self.ensure_column("query_history", "stats", "TEXT")?;
+        self.ensure_column("query_history", "plan", "TEXT")?;
+        self.conn.execute(SCHEMA_SNAPSHOTS_TABLE_SQL, [])?;
+        self.conn.execute(TABLE_PROFILES_TABLE_SQL, [])?;
+        self.conn.execute(BENCHMARKS_TABLE_SQL, [])?;
+        Ok(())
+    }
+
+    /// Adds `column` to `table` unless it already has it
+    fn ensure_column(&self, table: &str, column: &str, column_type: &str) -> SqlResult<()> {
+        let exists: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+            [table, column],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            self.conn
+                .execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, column_type), [])?;
+        }
         Ok(())
     }
 
     /// Add a query execution to history
     pub fn add_history(&self, entry: HistoryEntry) -> SqlResult<i64> {
         let result = self.conn.execute(
-            "INSERT INTO query_history (query, timestamp, database_path, success, duration_ms, row_count)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO query_history (query, timestamp, database_path, success, duration_ms, row_count, stats, plan)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             (
                 &entry.query,
                 entry.timestamp,
@@ -81,6 +205,8 @@ impl Storage {
                 entry.success,
                 entry.duration_ms,
                 entry.row_count,
+                entry.stats,
+                entry.plan,
             ),
         );
 
@@ -100,23 +226,13 @@ impl Storage {
     /// Get the most recent history entries, limited to count
     pub fn get_recent_history(&self, count: usize) -> SqlResult<Vec<HistoryEntry>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, query, timestamp, database_path, success, duration_ms, row_count
+            "SELECT id, query, timestamp, database_path, success, duration_ms, row_count, stats, plan
              FROM query_history
              ORDER BY timestamp DESC
              LIMIT ?1",
         )?;
 
-        let entries = stmt.query_map([count as i64], |row| {
-            Ok(HistoryEntry {
-                id: row.get(0)?,
-                query: row.get(1)?,
-                timestamp: row.get(2)?,
-                database_path: row.get(3)?,
-                success: row.get(4)?,
-                duration_ms: row.get(5)?,
-                row_count: row.get(6)?,
-            })
-        })?;
+        let entries = stmt.query_map([count as i64], HistoryEntry::from_row)?;
 
         entries.collect()
     }
@@ -124,23 +240,13 @@ impl Storage {
     /// Search query history
     pub fn search_history(&self, search_term: &str) -> SqlResult<Vec<HistoryEntry>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, query, timestamp, database_path, success, duration_ms, row_count
+            "SELECT id, query, timestamp, database_path, success, duration_ms, row_count, stats, plan
              FROM query_history
              WHERE query LIKE ?1
              ORDER BY timestamp DESC",
         )?;
 
-        let entries = stmt.query_map([format!("%{}%", search_term)], |row| {
-            Ok(HistoryEntry {
-                id: row.get(0)?,
-                query: row.get(1)?,
-                timestamp: row.get(2)?,
-                database_path: row.get(3)?,
-                success: row.get(4)?,
-                duration_ms: row.get(5)?,
-                row_count: row.get(6)?,
-            })
-        })?;
+        let entries = stmt.query_map([format!("%{}%", search_term)], HistoryEntry::from_row)?;
 
         entries.collect()
     }
@@ -148,25 +254,135 @@ impl Storage {
     /// Get a specific history entry by ID
     pub fn get_history_entry(&self, id: i64) -> SqlResult<Option<HistoryEntry>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, query, timestamp, database_path, success, duration_ms, row_count
+            "SELECT id, query, timestamp, database_path, success, duration_ms, row_count, stats, plan
              FROM query_history
              WHERE id = ?1",
         )?;
 
-        let mut entries = stmt.query_map([id], |row| {
-            Ok(HistoryEntry {
-                id: row.get(0)?,
-                query: row.get(1)?,
-                timestamp: row.get(2)?,
-                database_path: row.get(3)?,
-                success: row.get(4)?,
-                duration_ms: row.get(5)?,
-                row_count: row.get(6)?,
-            })
-        })?;
+        let mut entries = stmt.query_map([id], HistoryEntry::from_row)?;
 
         entries.next().transpose()
     }
+
+    /// History entries of a database that have a stored query plan, newest first
+    pub fn get_history_with_plans(&self, database_path: &str) -> SqlResult<Vec<HistoryEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, query, timestamp, database_path, success, duration_ms, row_count, stats, plan
+             FROM query_history
+             WHERE database_path = ?1 AND plan IS NOT NULL
+             ORDER BY timestamp DESC, id DESC",
+        )?;
+
+        let entries = stmt.query_map([database_path], HistoryEntry::from_row)?;
+
+        entries.collect()
+    }
+
+    /// Stores a schema snapshot unless an identical one exists for the same path and schema version
+    ///
+    /// Returns the id of the new snapshot, or `None` if it was already recorded.
+    pub fn add_schema_snapshot(&self, snapshot: &SchemaSnapshot) -> SqlResult<Option<i64>> {
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO schema_snapshots (database_path, schema_version, fingerprint, ddl, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            (
+                &snapshot.database_path,
+                snapshot.schema_version,
+                &snapshot.fingerprint,
+                &snapshot.ddl,
+                snapshot.timestamp,
+            ),
+        )?;
+
+        if inserted == 0 {
+            return Ok(None);
+        }
+        let id = self.conn.last_insert_rowid();
+        debug!("Added schema snapshot with id {}", id);
+        Ok(Some(id))
+    }
+
+    /// Get all schema snapshots for a database, newest first
+    pub fn get_schema_snapshots(&self, database_path: &str) -> SqlResult<Vec<SchemaSnapshot>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, database_path, schema_version, fingerprint, ddl, timestamp
+             FROM schema_snapshots
+             WHERE database_path = ?1
+             ORDER BY timestamp DESC, id DESC",
+        )?;
+
+        let snapshots = stmt.query_map([database_path], SchemaSnapshot::from_row)?;
+        snapshots.collect()
+    }
+
+    /// Get a specific schema snapshot by ID
+    pub fn get_schema_snapshot(&self, id: i64) -> SqlResult<Option<SchemaSnapshot>> {
+        self.conn
+            .query_row(
+                "SELECT id, database_path, schema_version, fingerprint, ddl, timestamp
+                 FROM schema_snapshots
+                 WHERE id = ?1",
+                [id],
+                SchemaSnapshot::from_row,
+            )
+            .optional()
+    }
+
+    /// Stores a serialized table profile, replacing any earlier one for the same table
+    pub fn save_table_profile(&self, database_path: &str, table_name: &str, profile: &str) -> SqlResult<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO table_profiles (database_path, table_name, profile, timestamp)
+             VALUES (?1, ?2, ?3, ?4)",
+            (database_path, table_name, profile, timestamp),
+        )?;
+        debug!("Cached profile of {} in {}", table_name, database_path);
+        Ok(())
+    }
+
+    /// Get the cached profile of a table and the time it was stored
+    pub fn get_table_profile(&self, database_path: &str, table_name: &str) -> SqlResult<Option<(String, i64)>> {
+        self.conn
+            .query_row(
+                "SELECT profile, timestamp FROM table_profiles
+                 WHERE database_path = ?1 AND table_name = ?2",
+                [database_path, table_name],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+    }
+
+    /// Store the result of a benchmark run, keeping earlier results for comparison
+    pub fn save_benchmark(&self, database_path: &str, query: &str, result: &str) -> SqlResult<i64> {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        self.conn.execute(
+            "INSERT INTO benchmarks (database_path, query, result, timestamp) VALUES (?1, ?2, ?3, ?4)",
+            (database_path, query, result, timestamp),
+        )?;
+        let id = self.conn.last_insert_rowid();
+        debug!("Saved benchmark {} of {}", id, database_path);
+        Ok(id)
+    }
+
+    /// Get the most recent benchmark result of a query and the time it was stored
+    pub fn get_last_benchmark(&self, database_path: &str, query: &str) -> SqlResult<Option<(String, i64)>> {
+        self.conn
+            .query_row(
+                "SELECT result, timestamp FROM benchmarks
+                 WHERE database_path = ?1 AND query = ?2
+                 ORDER BY timestamp DESC, id DESC
+                 LIMIT 1",
+                [database_path, query],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+    }
 }
 
 #[cfg(test)]
@@ -258,4 +474,123 @@ mod tests {
         let results = storage.search_history("nonexistent").unwrap();
         assert_eq!(results.len(), 0);
     }
+
+    #[test]
+    fn test_schema_snapshots() {
+        let storage = create_test_storage();
+
+        let first = SchemaSnapshot::new("app.db".to_string(), 1, "CREATE TABLE a (id INTEGER);\n".to_string());
+        let id = storage.add_schema_snapshot(&first).unwrap();
+        assert!(id.is_some());
+
+        // The same path, version and DDL is only recorded once
+        assert_eq!(storage.add_schema_snapshot(&first).unwrap(), None);
+
+        let second = SchemaSnapshot::new(
+            "app.db".to_string(),
+            2,
+            "CREATE TABLE a (id INTEGER, name TEXT);\n".to_string(),
+        );
+        let second_id = storage.add_schema_snapshot(&second).unwrap().unwrap();
+        storage
+            .add_schema_snapshot(&SchemaSnapshot::new("other.db".to_string(), 1, String::new()))
+            .unwrap();
+
+        let snapshots = storage.get_schema_snapshots("app.db").unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].id, second_id);
+        assert_eq!(snapshots[0].schema_version, 2);
+        assert_ne!(snapshots[0].fingerprint, snapshots[1].fingerprint);
+
+        let retrieved = storage.get_schema_snapshot(second_id).unwrap().unwrap();
+        assert_eq!(retrieved.ddl, second.ddl);
+        assert!(storage.get_schema_snapshot(9999).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_table_profile_cache() {
+        let storage = create_test_storage();
+        assert!(storage.get_table_profile("app.db", "users").unwrap().is_none());
+
+        storage.save_table_profile("app.db", "users", "{\"rows\":1}").unwrap();
+        storage.save_table_profile("app.db", "users", "{\"rows\":2}").unwrap();
+        let (profile, _) = storage.get_table_profile("app.db", "users").unwrap().unwrap();
+        assert_eq!(profile, "{\"rows\":2}");
+        assert!(storage.get_table_profile("other.db", "users").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_benchmarks() {
+        let storage = create_test_storage();
+        assert!(storage.get_last_benchmark("app.db", "SELECT 1").unwrap().is_none());
+
+        storage.save_benchmark("app.db", "SELECT 1", "{\"median_ms\":2.0}").unwrap();
+        storage.save_benchmark("app.db", "SELECT 1", "{\"median_ms\":1.0}").unwrap();
+        storage.save_benchmark("other.db", "SELECT 1", "{\"median_ms\":9.0}").unwrap();
+        let (result, _) = storage.get_last_benchmark("app.db", "SELECT 1").unwrap().unwrap();
+        assert_eq!(result, "{\"median_ms\":1.0}");
+        assert!(storage.get_last_benchmark("app.db", "SELECT 2").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_history_stats() {
+        let storage = create_test_storage();
+        let stats = "{\"auto_indexes\":42,\"vm_steps\":100}".to_string();
+        let entry = HistoryEntry::new("SELECT 1".to_string(), "test.db".to_string(), true, Some(1), Some(1))
+            .with_stats(Some(stats.clone()));
+        let id = storage.add_history(entry).unwrap();
+        assert_eq!(storage.get_history_entry(id).unwrap().unwrap().stats, Some(stats));
+
+        let plain = HistoryEntry::new("SELECT 2".to_string(), "test.db".to_string(), true, None, None);
+        let id = storage.add_history(plain).unwrap();
+        assert_eq!(storage.get_history_entry(id).unwrap().unwrap().stats, None);
+    }
+
+    #[test]
+    fn test_history_without_stats_column_is_migrated() {
+        let mut path = temp_dir();
+        path.push(format!("test_history_{}.db", Uuid::new_v4()));
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE query_history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT, query TEXT NOT NULL, timestamp INTEGER NOT NULL,
+                    database_path TEXT NOT NULL, success BOOLEAN NOT NULL, duration_ms INTEGER, row_count INTEGER);
+                 INSERT INTO query_history (query, timestamp, database_path, success) VALUES ('SELECT 1', 0, 'a.db', 1);",
+            )
+            .unwrap();
+
+        let storage = Storage::new(path).unwrap();
+        let entries = storage.get_recent_history(10).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].stats, None);
+        assert_eq!(entries[0].plan, None);
+    }
+
+    #[test]
+    fn test_history_plans() {
+        let storage = create_test_storage();
+        let plan = "{\"lines\":[\"SEARCH users USING INDEX idx_users_email (email=?)\"],\"schema_version\":3}"
+            .to_string();
+        storage
+            .add_history(HistoryEntry::new("SELECT 1".to_string(), "a.db".to_string(), true, None, None))
+            .unwrap();
+        let id = storage
+            .add_history(
+                HistoryEntry::new("SELECT * FROM users".to_string(), "a.db".to_string(), true, None, None)
+                    .with_plan(Some(plan.clone())),
+            )
+            .unwrap();
+        storage
+            .add_history(
+                HistoryEntry::new("SELECT * FROM users".to_string(), "b.db".to_string(), true, None, None)
+                    .with_plan(Some(plan.clone())),
+            )
+            .unwrap();
+
+        assert_eq!(storage.get_history_entry(id).unwrap().unwrap().plan, Some(plan));
+        let entries = storage.get_history_with_plans("a.db").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, id);
+    }
 }
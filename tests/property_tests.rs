@@ -78,6 +78,7 @@ mod tests {
                     columns,
                     indexes: Vec::new(), // For simplicity, start without indexes
                     foreign_keys: Vec::new(), // For simplicity, start without FKs
+                    sql: None,
                 }
             })
         })
@@ -207,6 +208,7 @@ mod tests {
             columns: Vec::new(),
             indexes: Vec::new(),
             foreign_keys: Vec::new(),
+            sql: None,
         });
         let minimal_schema = Schema { tables: single_table };
 
@@ -441,6 +443,7 @@ mod tests {
                 }],
                 indexes: Vec::new(),
                 foreign_keys: Vec::new(),
+                sql: None,
             };
 
             let table2 = Table {
@@ -454,6 +457,7 @@ mod tests {
                 }],
                 indexes: Vec::new(),
                 foreign_keys: Vec::new(),
+                sql: None,
             };
 
             schema_a.tables.insert("table1".to_string(), table1.clone());